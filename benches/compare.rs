@@ -0,0 +1,100 @@
+//!Compares `Global` against `once_cell::sync::Lazy` and `std::sync::LazyLock` with criterion.
+//!
+//!`deref` is the cost of an access once the value is there, which is what almost every access
+//!pays. `contended deref` is the same from several threads at once, so any shared writes on the
+//!read path would show up as cache line bouncing. `first access` is the time until every one of
+//!several threads racing on an empty cell has the value, which includes running the
+//!initializer once.
+//!
+//!Run with `cargo bench --bench compare`.
+use std::{
+    hint::black_box,
+    sync::{Barrier, LazyLock},
+    thread,
+    time::{Duration, Instant},
+};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use global_static::Global;
+use once_cell::sync::Lazy;
+
+const THREADS: [usize; 3] = [2, 4, 8];
+
+//stands in for parsing a config file or building a table
+fn expensive() -> u64 {
+    (0..20_000u64).fold(0, |acc, n| black_box(acc.wrapping_mul(31).wrapping_add(n)))
+}
+
+static GLOBAL: Global<u64> = Global::new(expensive);
+static ONCE_CELL: Lazy<u64> = Lazy::new(expensive);
+static LAZY_LOCK: LazyLock<u64> = LazyLock::new(expensive);
+
+fn deref(c: &mut Criterion) {
+    let mut group = c.benchmark_group("deref");
+    group.bench_function("Global", |b| b.iter(|| *black_box(&GLOBAL).force()));
+    group.bench_function("once_cell", |b| b.iter(|| **black_box(&ONCE_CELL)));
+    group.bench_function("LazyLock", |b| b.iter(|| **black_box(&LAZY_LOCK)));
+    group.finish();
+}
+
+//runs `access` `iters` times on each thread at once, returning the time taken by the slowest
+fn on_threads<G: Sync>(threads: usize, iters: u64, global: &G, access: impl Fn(&G) -> u64 + Sync) -> Duration {
+    let barrier = Barrier::new(threads);
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                s.spawn(|| {
+                    barrier.wait();
+                    let start = Instant::now();
+                    let mut sum = 0u64;
+                    for _ in 0..iters {
+                        sum = sum.wrapping_add(access(black_box(global)));
+                    }
+                    black_box(sum);
+                    start.elapsed()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).max().unwrap()
+    })
+}
+
+fn contended_deref(c: &mut Criterion) {
+    let mut group = c.benchmark_group("contended deref");
+    for threads in THREADS {
+        group.bench_with_input(BenchmarkId::new("Global", threads), &threads, |b, &n| {
+            b.iter_custom(|iters| on_threads(n, iters, &GLOBAL, |g| *g.force()))
+        });
+        group.bench_with_input(BenchmarkId::new("once_cell", threads), &threads, |b, &n| {
+            b.iter_custom(|iters| on_threads(n, iters, &ONCE_CELL, |g| **g))
+        });
+        group.bench_with_input(BenchmarkId::new("LazyLock", threads), &threads, |b, &n| {
+            b.iter_custom(|iters| on_threads(n, iters, &LAZY_LOCK, |g| **g))
+        });
+    }
+    group.finish();
+}
+
+//each iteration races the threads on a fresh cell, so every one pays for initialization
+fn racing<G: Sync>(threads: usize, iters: u64, new: impl Fn() -> G, access: impl Fn(&G) -> u64 + Sync + Copy) -> Duration {
+    (0..iters).map(|_| on_threads(threads, 1, &new(), access)).sum()
+}
+
+fn first_access(c: &mut Criterion) {
+    let mut group = c.benchmark_group("first access");
+    for threads in THREADS {
+        group.bench_with_input(BenchmarkId::new("Global", threads), &threads, |b, &n| {
+            b.iter_custom(|iters| racing(n, iters, || Global::<u64>::new(expensive), |g| *g.force()))
+        });
+        group.bench_with_input(BenchmarkId::new("once_cell", threads), &threads, |b, &n| {
+            b.iter_custom(|iters| racing(n, iters, || Lazy::new(expensive), |g| **g))
+        });
+        group.bench_with_input(BenchmarkId::new("LazyLock", threads), &threads, |b, &n| {
+            b.iter_custom(|iters| racing(n, iters, || LazyLock::new(expensive), |g| **g))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, deref, contended_deref, first_access);
+criterion_main!(benches);
@@ -0,0 +1,110 @@
+//!Compares eager and lazy initialization when many threads access a global at once.
+//!
+//!For each thread count, every thread waits on a barrier and then accesses the same global.
+//!`first access` is the time until every thread has the value. Eager globals are initialized
+//!before the threads start, as `ctor_static!` does, so only lazy ones pay for the initializer
+//!there, and their threads block on each other while it runs. `deref` is the cost of each access
+//!once the value is there.
+//!
+//!Run with `cargo bench --bench contention`.
+use std::{
+    hint::black_box,
+    sync::{Barrier, LazyLock},
+    thread,
+    time::{Duration, Instant},
+};
+
+use global_static::Global;
+
+const ITERS: u32 = 5_000_000;
+const RUNS: u32 = 5;
+
+//stands in for parsing a config file or building a table
+fn expensive() -> u64 {
+    (0..200_000u64).fold(0, |acc, n| black_box(acc.wrapping_mul(31).wrapping_add(n)))
+}
+
+//runs `access` on every thread at once, returning how long it took from the first thread
+//starting until the last one was done
+fn contended<G: Sync>(threads: usize, global: &G, access: impl Fn(&G) -> u64 + Sync) -> Duration {
+    let barrier = Barrier::new(threads);
+    let spans: Vec<(Instant, Instant)> = thread::scope(|s| {
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                s.spawn(|| {
+                    barrier.wait();
+                    let start = Instant::now();
+                    black_box(access(global));
+                    (start, Instant::now())
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    let start = spans.iter().map(|s| s.0).min().unwrap();
+    let end = spans.iter().map(|s| s.1).max().unwrap();
+    end - start
+}
+
+//best of several runs with a fresh global each time, to filter out noise from the rest of the
+//system
+fn first_access<G: Sync>(threads: usize, new: impl Fn() -> G, access: impl Fn(&G) -> u64 + Sync + Copy) -> Duration {
+    (0..RUNS).map(|_| contended(threads, &new(), access)).min().unwrap()
+}
+
+fn deref<G: Sync>(threads: usize, global: &G, access: impl Fn(&G) -> u64 + Sync + Copy) -> f64 {
+    access(global);
+    let elapsed = (0..RUNS)
+        .map(|_| {
+            contended(threads, global, |g| {
+                let mut sum = 0u64;
+                for _ in 0..ITERS {
+                    sum = sum.wrapping_add(access(black_box(g)));
+                }
+                sum
+            })
+        })
+        .min()
+        .unwrap();
+    elapsed.as_nanos() as f64 / ITERS as f64
+}
+
+fn main() {
+    let max = thread::available_parallelism().map_or(4, |n| n.get());
+    let counts: Vec<usize> = [1, 2, 4, 8, 16, 32].into_iter().filter(|&n| n <= max.max(8)).collect();
+
+    println!("first access (µs, all threads)");
+    println!("{:>8} {:>12} {:>12} {:>12}", "threads", "eager", "Global", "LazyLock");
+    for &threads in &counts {
+        let eager = first_access(
+            threads,
+            || {
+                let global = Global::<u64>::new(expensive);
+                global.init();
+                global
+            },
+            |g| *g.force(),
+        );
+        let lazy = first_access(threads, || Global::<u64>::new(expensive), |g| *g.force());
+        let lock = first_access(threads, || LazyLock::new(expensive), |g| **g);
+        println!(
+            "{threads:>8} {:>12.1} {:>12.1} {:>12.1}",
+            eager.as_secs_f64() * 1e6,
+            lazy.as_secs_f64() * 1e6,
+            lock.as_secs_f64() * 1e6,
+        );
+    }
+
+    println!();
+    println!("deref (ns of wall time per access on each thread)");
+    println!("{:>8} {:>12} {:>12}", "threads", "Global", "LazyLock");
+    let global = Global::<u64>::new(expensive);
+    let lock = LazyLock::new(expensive);
+    for &threads in &counts {
+        println!(
+            "{threads:>8} {:>12.3} {:>12.3}",
+            deref(threads, &global, |g| *g.force()),
+            deref(threads, &lock, |g| **g),
+        );
+    }
+}
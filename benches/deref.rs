@@ -0,0 +1,45 @@
+//!Measures the cost of dereferencing an already initialized global.
+//!
+//!The `boxed` case reproduces the previous storage scheme, where the value was leaked into a
+//!`Box` and the cell held a pointer to it.
+use std::{
+    hint::black_box,
+    sync::{LazyLock, OnceLock},
+    time::{Duration, Instant},
+};
+
+use global_static::Global;
+
+const ITERS: u32 = 50_000_000;
+
+static INLINE: Global<u64> = Global::new(|| 7);
+static LAZY: LazyLock<u64> = LazyLock::new(|| 7);
+static BOXED: OnceLock<&'static u64> = OnceLock::new();
+
+fn boxed() -> &'static u64 {
+    BOXED.get_or_init(|| Box::leak(Box::new(7)))
+}
+
+const RUNS: u32 = 5;
+
+//best of several runs, to filter out noise from the rest of the system
+fn bench(name: &str, f: impl Fn() -> u64) -> Duration {
+    f();
+    let best = (0..RUNS).map(|_| {
+        let start = Instant::now();
+        let mut sum = 0u64;
+        for _ in 0..ITERS {
+            sum = sum.wrapping_add(black_box(&f)());
+        }
+        black_box(sum);
+        start.elapsed()
+    }).min().unwrap();
+    println!("{name:>10}: {:>6.3} ns/deref", best.as_nanos() as f64 / ITERS as f64);
+    best
+}
+
+fn main() {
+    bench("boxed", || *boxed());
+    bench("LazyLock", || *LAZY);
+    bench("Global", || *INLINE.force());
+}
@@ -2,45 +2,377 @@ use pm::Span;
 use proc_macro as pm;
 
 use quote::{quote, ToTokens};
-use syn::{parse_macro_input, ItemStruct, Expr, Ident, ItemFn, spanned::Spanned};
+use syn::{parse_macro_input, Item, Expr, Ident, ItemFn, DeriveInput, Data, Token, Type, Visibility, Attribute};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+
+//platforms without life-before-main, where singletons are initialized by `init_all_globals!`
+//instead. wasm is detected by the generated code, since this crate is built for the host
+const NO_CTOR: bool = cfg!(feature = "ctor-fallback");
+
+//version of the interface between the code generated here and global-static, which has to match
+//the one `__check_macro_version` accepts there. bumped whenever generated code starts relying on
+//something new, so partial upgrades fail with an error naming both versions
+const RUNTIME_ABI: &str = "1";
+
+fn version_check() -> impl ToTokens {
+    let abi = syn::LitInt::new(RUNTIME_ABI, Span::call_site().into());
+    let version = env!("CARGO_PKG_VERSION");
+    quote! { const _: () = global_static::__check_macro_version(#version, #abi); }
+}
+
+struct SingletonArgs {
+    arc: bool,
+    mutable: bool,
+    init: Option<Expr>,
+    ty: Option<Type>,
+    name: Option<Ident>,
+    vis: Option<Visibility>,
+    no_ctor: bool,
+    thread_local: bool,
+    inject: bool,
+    instance: bool,
+    //cfg predicate for the real global, and the initializer of the stub
+    stub: Option<(syn::Meta, Option<Expr>)>,
+}
+
+impl Parse for SingletonArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = SingletonArgs {
+            arc: false, mutable: false, init: None, ty: None, name: None, vis: None, no_ctor: false,
+            thread_local: false, inject: false, instance: false, stub: None,
+        };
+        while !input.is_empty() {
+            let fork = input.fork();
+            let flag = fork.parse::<Ident>().ok()
+                .filter(|i| ["arc", "no_ctor", "thread_local", "inject", "instance"].iter().any(|f| i == f))
+                .filter(|_| fork.is_empty() || fork.peek(Token![,]));
+            let is_key = input.peek(Ident) && input.peek2(Token![=]) && !input.peek2(Token![==]);
+            let is_stub = input.peek(Ident) && input.peek2(syn::token::Paren)
+                && input.fork().parse::<Ident>().is_ok_and(|i| i == "stub");
+            if is_stub {
+                input.parse::<Ident>()?;
+                let content;
+                syn::parenthesized!(content in input);
+                let cfg = content.parse()?;
+                let init = if content.is_empty() {
+                    None
+                } else {
+                    content.parse::<Token![,]>()?;
+                    Some(content.parse()?)
+                };
+                args.stub = Some((cfg, init));
+            } else if is_key {
+                let key = input.parse::<Ident>()?;
+                input.parse::<Token![=]>()?;
+                match key.to_string().as_str() {
+                    "ty" => args.ty = Some(input.parse()?),
+                    "name" => args.name = Some(input.parse()?),
+                    "vis" => args.vis = Some(input.parse()?),
+                    "init" if args.init.is_none() => args.init = Some(input.parse()?),
+                    "init" => return Err(syn::Error::new(key.span(), "initializer was already given")),
+                    _ => return Err(syn::Error::new(key.span(), format!("unknown argument `{key}`"))),
+                }
+            } else if input.peek(Token![mut]) {
+                input.parse::<Token![mut]>()?;
+                args.mutable = true;
+            } else if let Some(flag) = flag {
+                input.parse::<Ident>()?;
+                match flag.to_string().as_str() {
+                    "arc" => args.arc = true,
+                    "thread_local" => args.thread_local = true,
+                    "inject" => args.inject = true,
+                    "instance" => args.instance = true,
+                    _ => args.no_ctor = true,
+                }
+            } else if args.init.is_none() {
+                args.init = Some(input.parse()?);
+            } else {
+                return Err(input.error("unexpected argument, initializer was already given"));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        if args.arc && args.mutable {
+            return Err(input.error("`arc` and `mut` cannot be combined"));
+        }
+        if args.inject && args.init.is_some() {
+            return Err(input.error("`inject` generates the initializer, so one can't be given"));
+        }
+        if args.instance && (args.arc || args.mutable || args.thread_local) {
+            return Err(input.error("`instance` cannot be combined with `arc`, `mut` or `thread_local`"));
+        }
+        if args.stub.is_some() && (args.arc || args.mutable || args.thread_local) {
+            return Err(input.error("`stub` cannot be combined with `arc`, `mut` or `thread_local`"));
+        }
+        if args.thread_local && (args.arc || args.mutable) {
+            return Err(input.error("`thread_local` cannot be combined with `arc` or `mut`"));
+        }
+        Ok(args)
+    }
+}
+
+///Splits the attributes of an item into doc comments, which are copied onto the generated
+///static, and lint and `cfg` attributes, which are copied onto every generated item.
+fn forwarded_attrs(attrs: &[Attribute]) -> (Vec<&Attribute>, Vec<&Attribute>) {
+    let docs = attrs.iter().filter(|a| a.path().is_ident("doc")).collect();
+    let lints = attrs.iter()
+        .filter(|a| ["allow", "warn", "deny", "expect", "cfg"].iter().any(|l| a.path().is_ident(l)))
+        .collect();
+    (docs, lints)
+}
+
+///The text of the doc comments among `attrs`, one line each, in the form
+///`global_static::__document` expects.
+fn doc_text(attrs: &[Attribute]) -> String {
+    attrs.iter().filter_map(|a| match &a.meta {
+        syn::Meta::NameValue(nv) if nv.path.is_ident("doc") => match &nv.value {
+            Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. }) => Some(s.value() + "\n"),
+            _ => None,
+        },
+        _ => None,
+    }).collect()
+}
+
+//asserts that every field is `Sync`, so one that isn't is reported at the field, by its type,
+//rather than through the bound on the static. generic types are left to that bound, since their
+//fields can't be checked without concrete arguments
+fn sync_fields<'a>(types: impl IntoIterator<Item = &'a Type>, mutable: bool) -> impl ToTokens {
+    let note = if mutable {
+        "`#[singleton(mut)]` already locks the whole value, so use a plain field, \
+        or a `std::sync::Mutex` field to lock it separately"
+    } else {
+        "use `#[singleton(mut)]` with a plain field, or a `std::sync::Mutex` field for interior mutability instead"
+    };
+    let checks = types.into_iter().map(|ty| quote::quote_spanned! { ty.span()=> assert_sync::<#ty>(); });
+    quote! {
+        const _: () = {
+            #[diagnostic::on_unimplemented(
+                message = "`{Self}` is not thread safe and cannot be stored in a singleton",
+                label = "this field is not `Sync`",
+                note = #note,
+            )]
+            trait SingletonField {}
+            impl<T: ?::core::marker::Sized + ::core::marker::Sync> SingletonField for T {}
+            fn assert_sync<T: ?::core::marker::Sized + SingletonField>() {}
+            #[allow(dead_code)]
+            fn fields() { #(#checks)* }
+        };
+    }
+}
 
 #[proc_macro_attribute]
-///Generate a ctor static of this struct.
+///Generate a ctor static of this struct or enum.
 ///By defeault, uses `Default` if the type implements it. You can pass an expression to the
 ///attribute to use it instead.
 ///```rust,ignore
 ///#[singleton] //using Default::default
 ///#[singleton(MyType::parse)] //using MyType::parse
 ///#[singleton(|| MyType::new())] //closures work too
+///```
+///Fields that aren't `Sync`, such as a `Cell` or `RefCell`, are rejected with a hint, since the
+///singleton is shared between threads.
+///
+///Passing `arc` generates a `GlobalArc` instead, along with a `MyType::shared()` function that
+///returns an `Arc<MyType>`. Passing `mut` generates a `GlobalMut` that can be locked for writing.
+///```rust,ignore
+///#[singleton(arc)]
+///#[singleton(arc, MyType::parse)]
+///#[singleton(mut)]
+///```
+///
+///Passing `instance` implements `global_static::Singleton` for the type, so it is accessed with
+///`MyType::instance()` and `MyType::try_instance()`, and keeps the static private unless `vis`
+///is given.
+///```rust,ignore
+///#[singleton(instance)]
+///```
+///
+///Passing `stub(..)` with a `cfg` predicate generates a stub in place of the singleton when the
+///predicate doesn't hold, which panics when accessed, or uses the initializer given after the
+///predicate instead. Call sites can then use the singleton without `cfg` attributes.
+///```rust,ignore
+///#[singleton(stub(feature = "telemetry"))]
+///#[singleton(stub(feature = "telemetry", Exporter::noop))]
+///```
+///
+///Passing `thread_local` generates a `LocalGlobal` with a separate instance for each thread,
+///created on first use in that thread. Fields may use `Cell` and `RefCell` then.
+///```rust,ignore
+///#[singleton(thread_local)]
+///```
+///
+///Passing `inject` generates an initializer that resolves each field from
+///`GlobalRegistry::global()` by its type, panicking if a dependency isn't registered.
+///`&'static T` fields borrow the registered value and other fields are cloned from it. Since
+///dependencies may be registered in any order at startup, injected singletons are initialized on
+///first use rather than by a ctor.
+///```rust,ignore
+///#[singleton(inject)]
+///struct Service { db: &'static Database, config: Config }
+///```
+///
+///Generic types can't be stored in a static directly, so they need concrete type arguments and
+///an initializer.
+///```rust,ignore
+///#[singleton(ty = Cache<String, u64>, init = Cache::new)]
+///struct Cache<K, V> { entries: Vec<(K, V)> }
+///```
+///
+///The static is `pub` and named after the type by default. `name` and `vis` change this, and
+///`no_ctor` leaves the singleton to be initialized lazily on first use instead of at startup.
+///This is always the case on platforms without life-before-main, such as wasm, where the
+///singleton can be listed in `init_all_globals!` instead.
+///Doc comments on the type are copied onto the static, and `allow`, `warn`, `deny`, `expect` and
+///`cfg` attributes onto everything generated.
+///```rust,ignore
+///#[singleton(name = APP_CONFIG, vis = pub(crate), no_ctor)]
+///```
 pub fn singleton(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
-    let data = parse_macro_input!(item as ItemStruct);
-    let attr_expr = syn::parse::<Expr>(attr.clone());
+    let data = parse_macro_input!(item as Item);
+    let args = parse_macro_input!(attr as SingletonArgs);
 
-    let default = syn::parse::<Expr>(quote! { Default::default }.into()).unwrap();
-    let expr = match attr_expr {
-        Ok(tree) => tree,
-        Err(_) if attr.is_empty() => default,
-        Err(e) => return e.to_compile_error().into(),
+    let (ident, generics, field_types): (_, _, Vec<_>) = match &data {
+        Item::Struct(s) => (&s.ident, &s.generics, s.fields.iter().map(|f| &f.ty).collect()),
+        Item::Enum(e) => (&e.ident, &e.generics, e.variants.iter().flat_map(|v| &v.fields).map(|f| &f.ty).collect()),
+        _ => return syn::Error::new(data.span(), "`#[singleton]` can only be used on structs and enums")
+            .to_compile_error().into(),
     };
 
-    let struct_name = &data.ident;
-    let static_name = syn::Ident::new(&struct_name.to_string().to_uppercase(), struct_name.span());
+    //thread-local singletons are never shared, so cells are fine there
+    let sync_check = (!args.thread_local && generics.params.is_empty())
+        .then(|| sync_fields(field_types, args.mutable));
+
+    //a static can't be generic, so generic types need to be given concrete arguments
+    let ty = match args.ty {
+        Some(ty) => ty,
+        None if generics.params.is_empty() => syn::parse_quote! { #ident },
+        None => {
+            let msg = format!(
+                "generic singletons need concrete type arguments, \
+                e.g. `#[singleton(ty = {ident}<..>, init = {ident}::new)]`");
+            return syn::Error::new(generics.span(), msg).to_compile_error().into();
+        },
+    };
+
+    let expr = match (args.inject, &data) {
+        (true, Item::Struct(s)) if s.generics.params.is_empty() => injected(s),
+        (true, _) => return syn::Error::new(ident.span(), "`inject` can only be used on non-generic structs")
+            .to_compile_error().into(),
+        (false, _) => args.init.unwrap_or_else(|| syn::parse_quote! { Default::default }),
+    };
+
+    let static_name = args.name
+        .unwrap_or_else(|| syn::Ident::new(&ident.to_string().to_uppercase(), ident.span()));
     let fn_name = syn::Ident::new(
-        &format!("_{}_global_init", struct_name.to_string().to_lowercase()), 
+        &format!("_{}_global_init", static_name.to_string().to_lowercase()), 
         Span::call_site().into());
-    
+    //the static stays private when the type is accessed through `Singleton::instance`
+    let vis = match args.vis {
+        Some(vis) => vis,
+        None if args.instance => Visibility::Inherited,
+        None => syn::parse_quote! { pub },
+    };
 
-    let out = quote! {
-        pub static #static_name: global_static::Global<#struct_name> = global_static::Global::new(#expr);
+    let attrs = match &data {
+        Item::Struct(s) => &s.attrs,
+        Item::Enum(e) => &e.attrs,
+        _ => unreachable!(),
+    };
+    let (docs, lints) = forwarded_attrs(attrs);
+    let doc = doc_text(attrs);
+
+    let (global_ty, accessor) = if args.arc {
+        (quote! { global_static::GlobalArc }, quote! {
+            #(#lints)*
+            impl #ty {
+                ///Returns a shared handle to the singleton instance.
+                #vis fn shared() -> ::std::sync::Arc<#ty> {
+                    #static_name.shared()
+                }
+            }
+        })
+    } else if args.mutable {
+        (quote! { global_static::GlobalMut }, quote! {})
+    } else if args.thread_local {
+        (quote! { global_static::LocalGlobal }, quote! {})
+    } else if args.instance {
+        (quote! { global_static::Global }, quote! {
+            #(#lints)*
+            impl global_static::Singleton for #ty {
+                fn instance() -> &'static Self {
+                    #static_name.force()
+                }
+                fn try_instance() -> ::core::result::Result<&'static Self, global_static::Poisoned> {
+                    #static_name.try_get()
+                }
+            }
+        })
+    } else {
+        (quote! { global_static::Global }, quote! {})
+    };
+
+    let init = if args.thread_local {
+        quote! {{
+            ::std::thread_local! {
+                static KEY: ::std::cell::OnceCell<#ty> = const { ::std::cell::OnceCell::new() };
+            }
+            global_static::LocalGlobal::new(&KEY, #expr)
+        }}
+    } else if args.arc || args.mutable {
+        quote! { #global_ty::new(#expr) }
+    } else {
+        quote! { <#global_ty<#ty>>::new(#expr).named(concat!(module_path!(), "::", stringify!(#static_name))) }
+    };
+
+    //the stub replaces the static when the predicate doesn't hold, and panicking stubs are never
+    //initialized at startup
+    let (stub_cfg, stub, stub_ctor_cfg) = match &args.stub {
+        Some((cfg, init)) => {
+            let stub_init = match init {
+                Some(init) => quote! { <#global_ty<#ty>>::new(#init) },
+                None => quote! { <#global_ty<#ty>>::stub() },
+            };
+            let stub = quote! {
+                #(#docs)*
+                #(#lints)*
+                #[cfg(not(#cfg))]
+                #vis static #static_name: #global_ty<#ty> = #stub_init
+                    .named(concat!(module_path!(), "::", stringify!(#static_name)));
+            };
+            (Some(quote! { #[cfg(#cfg)] }), Some(stub), init.is_none().then(|| quote! { #[cfg(#cfg)] }))
+        },
+        None => (None, None, None),
+    };
+
+    //thread-local singletons are initialized on first use in each thread, and injected ones once
+    //their dependencies have been registered, which may happen in any order at startup
+    let ctor = (!args.no_ctor && !args.thread_local && !args.inject && !NO_CTOR).then(|| quote! {
+        #(#lints)*
+        #stub_ctor_cfg
+        #[cfg(not(target_family = "wasm"))]
         #[global_static::ctor::ctor]
         fn #fn_name() {
-            #static_name.init()
+            global_static::__document(&#static_name, concat!(module_path!(), "::", stringify!(#static_name)), #doc);
+            global_static::__ctor_init(&#static_name)
         }
+    });
+
+    let check = version_check();
+    let out = quote! {
+        #check
+        #sync_check
+        #(#docs)*
+        #(#lints)*
+        #stub_cfg
+        #vis static #static_name: #global_ty<#ty> = #init;
+        #stub
+        #ctor
+        #accessor
         #data
     };
 
-    
-
     out.into() 
 }
 
@@ -52,14 +384,23 @@ pub fn singleton(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStrea
 ///#[singleton_fn(MY_STATIC)] //using MY_STATIC as name 
 ///fn make_thing() -> Thing;
 ///```
+///Functions returning `Result<T, E>` generate a `TryGlobal<T, E>`, and `async` functions
+///generate an `AsyncGlobal<T>`. Async globals are not initialized at startup, since there is no
+///executor to run them on yet.
+///```rust,ignore
+///#[singleton_fn]
+///fn load_config() -> Result<Config, ConfigError>;
+///#[singleton_fn]
+///async fn make_pool() -> Pool;
+///```
 pub fn singleton_fn(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
     let data = parse_macro_input!(item as ItemFn);
     let attr_ident = syn::parse::<Ident>(attr).ok();
 
     let item_name = &data.sig.ident;
-    let struct_name = match &data.sig.output {
-        syn::ReturnType::Default => quote! { () },
-        syn::ReturnType::Type(_, ty) => quote! { #ty },
+    let ret = match &data.sig.output {
+        syn::ReturnType::Default => syn::parse_quote! { () },
+        syn::ReturnType::Type(_, ty) => (**ty).clone(),
     };
 
     let static_name = match attr_ident {
@@ -70,12 +411,276 @@ pub fn singleton_fn(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenSt
         &format!("_{}_global_init", static_name.to_string().to_lowercase()), 
         Span::call_site().into());
 
-    quote!{ 
-        pub static #static_name: global_static::Global<#struct_name> = global_static::Global::new(#item_name);
+    let check = version_check();
+    if data.sig.asyncness.is_some() {
+        return quote! {
+            #check
+            pub static #static_name: global_static::AsyncGlobal<#ret> =
+                global_static::AsyncGlobal::new(|| ::std::boxed::Box::pin(#item_name()));
+            #data
+        }.into();
+    }
+
+    let (global, named) = match result_args(&ret) {
+        Some(Ok((ok, err))) => (quote! { global_static::TryGlobal<#ok, #err> }, quote! {}),
+        Some(Err(e)) => return e.to_compile_error().into(),
+        None => (quote! { global_static::Global<#ret> }, quote! {
+            .named(concat!(module_path!(), "::", stringify!(#static_name)))
+        }),
+    };
+
+    let doc = doc_text(&data.attrs);
+    let ctor = (!NO_CTOR).then(|| quote! {
+        #[cfg(not(target_family = "wasm"))]
         #[global_static::ctor::ctor]
         fn #fn_name() {
-            #static_name.init()
+            global_static::__document(&#static_name, concat!(module_path!(), "::", stringify!(#static_name)), #doc);
+            global_static::__ctor_init(&#static_name)
         }
+    });
+
+    quote!{ 
+        #check
+        pub static #static_name: #global = <#global>::new(#item_name)#named;
+        #ctor
         #data
     }.into()
 }
+
+///An initializer that resolves each field from the process-wide registry by type. `&'static T`
+///fields borrow the registered `T`, and other fields are cloned from the registered value.
+fn injected(data: &syn::ItemStruct) -> Expr {
+    let ident = &data.ident;
+    let values: Vec<_> = data.fields.iter().map(|f| match &f.ty {
+        Type::Reference(r) if r.mutability.is_none()
+            && r.lifetime.as_ref().is_some_and(|l| l.ident == "static") => {
+            let elem = &r.elem;
+            quote! { global_static::__inject::<#elem>(stringify!(#ident)) }
+        },
+        ty => quote! { ::core::clone::Clone::clone(global_static::__inject::<#ty>(stringify!(#ident))) },
+    }).collect();
+    let names = data.fields.iter().map(|f| &f.ident);
+    match &data.fields {
+        syn::Fields::Named(_) => syn::parse_quote! { || #ident { #(#names: #values),* } },
+        syn::Fields::Unnamed(_) => syn::parse_quote! { || #ident(#(#values),*) },
+        syn::Fields::Unit => syn::parse_quote! { || #ident },
+    }
+}
+
+///The value and error types of a `Result` return type, or `None` if it isn't one.
+fn result_args(ty: &Type) -> Option<syn::Result<(Type, Type)>> {
+    let Type::Path(path) = ty else { return None };
+    let last = path.path.segments.last()?;
+    if last.ident != "Result" {
+        return None;
+    }
+    let args: Vec<_> = match &last.arguments {
+        syn::PathArguments::AngleBracketed(a) => a.args.iter().filter_map(|a| match a {
+            syn::GenericArgument::Type(t) => Some(t.clone()),
+            _ => None,
+        }).collect(),
+        _ => Vec::new(),
+    };
+    match <[Type; 2]>::try_from(args) {
+        Ok([ok, err]) => Some(Ok((ok, err))),
+        Err(_) => Some(Err(syn::Error::new(
+            ty.span(),
+            "`singleton_fn` needs the error type of a `Result` to be written out, \
+            e.g. `Result<T, std::io::Error>` instead of `std::io::Result<T>`"))),
+    }
+}
+
+
+#[doc(hidden)]
+#[proc_macro]
+///Checks that global-static expects the same interface version as this crate generates code
+///for. Called by global-static itself, so the check also catches an upgrade of it alone.
+pub fn __check_runtime_abi(item: pm::TokenStream) -> pm::TokenStream {
+    let abi = parse_macro_input!(item as syn::LitInt);
+    if abi.base10_digits() == RUNTIME_ABI {
+        return pm::TokenStream::new();
+    }
+    let msg = format!(
+        " expects code generated for interface version {abi}, but global-static-singleton {} \
+        generates version {RUNTIME_ABI}; update both crates to matching versions",
+        env!("CARGO_PKG_VERSION"),
+    );
+    //expanded in global-static, so this is its version
+    quote! { compile_error!(concat!("global-static ", env!("CARGO_PKG_VERSION"), #msg)); }.into()
+}
+
+#[proc_macro_derive(FfiSafe)]
+///Implement `FfiSafe` for a `#[repr(C)]` or `#[repr(transparent)]` type.
+///Every field is checked to be `FfiSafe` as well.
+///```rust,ignore
+///#[derive(FfiSafe)]
+///#[repr(C)]
+///struct Limits { max: u32 }
+///```
+pub fn ffi_safe(item: pm::TokenStream) -> pm::TokenStream {
+    let mut data = parse_macro_input!(item as DeriveInput);
+
+    let mut c_layout = false;
+    for attr in data.attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            let is_int = ["u8", "u16", "u32", "u64", "usize", "i8", "i16", "i32", "i64", "isize"]
+                .iter().any(|i| meta.path.is_ident(i));
+            if meta.path.is_ident("C") || meta.path.is_ident("transparent") || is_int {
+                c_layout = true;
+            }
+            Ok(())
+        });
+    }
+    if !c_layout {
+        return syn::Error::new(
+            data.ident.span(), 
+            "`FfiSafe` requires `#[repr(C)]` or `#[repr(transparent)]`"
+            ).to_compile_error().into();
+    }
+
+    let fields: Vec<_> = match &data.data {
+        Data::Struct(s) => s.fields.iter().map(|f| f.ty.clone()).collect(),
+        Data::Enum(e) => {
+            if let Some(v) = e.variants.iter().find(|v| !v.fields.is_empty()) {
+                return syn::Error::new(v.ident.span(), "`FfiSafe` enums cannot have fields")
+                    .to_compile_error().into();
+            }
+            Vec::new()
+        },
+        Data::Union(u) => u.fields.named.iter().map(|f| f.ty.clone()).collect(),
+    };
+
+    let params: Vec<_> = data.generics.type_params().map(|p| p.ident.clone()).collect();
+    let where_clause = data.generics.make_where_clause();
+    for param in params {
+        where_clause.predicates.push(syn::parse_quote!(#param: global_static::FfiSafe));
+    }
+
+    let name = &data.ident;
+    let (impl_generics, ty_generics, where_clause) = data.generics.split_for_impl();
+    let check = version_check();
+    quote! {
+        #check
+        unsafe impl #impl_generics global_static::FfiSafe for #name #ty_generics #where_clause {}
+        const _: () = {
+            fn __ffi_safe<T: global_static::FfiSafe + ?Sized>() {}
+            #[allow(dead_code)]
+            fn __assert_fields #impl_generics () #where_clause {
+                #(__ffi_safe::<#fields>();)*
+            }
+        };
+    }.into()
+}
+
+#[proc_macro_derive(CType)]
+///Implement `CType` for a `#[repr(C)]` or `#[repr(transparent)]` type, so it can be declared in
+///a header generated by `export_global!`. Every field's type must implement `CType` as well.
+///```rust,ignore
+///#[derive(FfiSafe, CType)]
+///#[repr(C)]
+///struct Limits { max: u32 }
+///```
+pub fn c_type(item: pm::TokenStream) -> pm::TokenStream {
+    let data = parse_macro_input!(item as DeriveInput);
+    let name = &data.ident;
+    let c_name = name.to_string();
+
+    let mut repr = None;
+    for attr in data.attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            if let Some(ident) = meta.path.get_ident() {
+                repr = Some(ident.to_string());
+            }
+            Ok(())
+        });
+    }
+    let Some(repr) = repr else {
+        return syn::Error::new(name.span(), "`CType` requires `#[repr(C)]` or `#[repr(transparent)]`")
+            .to_compile_error().into();
+    };
+    if !data.generics.params.is_empty() {
+        return syn::Error::new(data.generics.span(), "`CType` can't be derived for generic types")
+            .to_compile_error().into();
+    }
+
+    let fields = |fields: &syn::Fields| -> Vec<(String, Type)> {
+        fields.iter().enumerate().map(|(i, f)| {
+            let name = f.ident.as_ref().map_or_else(|| format!("_{i}"), Ident::to_string);
+            (name, f.ty.clone())
+        }).collect()
+    };
+    let (decl, define) = match &data.data {
+        Data::Struct(s) if repr == "transparent" => {
+            let Some(inner) = s.fields.iter().next().map(|f| &f.ty) else {
+                return syn::Error::new(name.span(), "`CType` requires a field").to_compile_error().into();
+            };
+            (
+                quote!(<#inner as global_static::CType>::c_decl(declarator)),
+                quote!(<#inner as global_static::CType>::c_define(defs)),
+            )
+        },
+        Data::Struct(_) | Data::Union(_) => {
+            let (keyword, fields) = match &data.data {
+                Data::Struct(s) => ("struct", fields(&s.fields)),
+                Data::Union(u) => ("union", fields(&syn::Fields::Named(u.fields.clone()))),
+                Data::Enum(_) => unreachable!(),
+            };
+            let tag = format!("{keyword} {c_name}");
+            let (names, types): (Vec<_>, Vec<_>) = fields.into_iter().unzip();
+            let define = quote! {
+                if defs.start(#c_name) {
+                    #(<#types as global_static::CType>::c_define(defs);)*
+                    let mut def = ::std::format!("{} {{\n", #tag);
+                    #(def += &::std::format!("    {};\n", <#types as global_static::CType>::c_decl(#names));)*
+                    def += "};";
+                    defs.add(def);
+                }
+            };
+            (quote!(::std::format!("{} {}", #tag, declarator)), define)
+        },
+        Data::Enum(e) => {
+            if let Some(v) = e.variants.iter().find(|v| !v.fields.is_empty()) {
+                return syn::Error::new(v.ident.span(), "`CType` enums cannot have fields")
+                    .to_compile_error().into();
+            }
+            let variants: Vec<_> = e.variants.iter().map(|v| &v.ident).collect();
+            let labels: Vec<_> = variants.iter().map(|v| format!("{c_name}_{v}")).collect();
+            //integer representations keep their size, which a C enum doesn't guarantee
+            let (decl, head) = if repr == "C" {
+                (format!("enum {c_name}"), format!("enum {c_name} {{\n"))
+            } else {
+                let int = match repr.as_str() {
+                    "u8" | "u16" | "u32" | "u64" => format!("uint{}_t", &repr[1..]),
+                    "i8" | "i16" | "i32" | "i64" => format!("int{}_t", &repr[1..]),
+                    "usize" => "uintptr_t".to_owned(),
+                    "isize" => "intptr_t".to_owned(),
+                    _ => return syn::Error::new(name.span(), "`CType` requires `#[repr(C)]` or an integer `repr`")
+                        .to_compile_error().into(),
+                };
+                (c_name.clone(), format!("typedef {int} {c_name};\nenum {{\n"))
+            };
+            let define = quote! {
+                if defs.start(#c_name) {
+                    let mut def = ::std::string::String::from(#head);
+                    #(def += &::std::format!("    {} = {},\n", #labels, #name::#variants as i64);)*
+                    def += "};";
+                    defs.add(def);
+                }
+            };
+            (quote!(::std::format!("{} {}", #decl, declarator)), define)
+        },
+    };
+
+    let check = version_check();
+    quote! {
+        #check
+        impl global_static::CType for #name {
+            fn c_decl(declarator: &str) -> ::std::string::String {
+                #decl
+            }
+            fn c_define(defs: &mut global_static::CDefinitions) {
+                #define
+            }
+        }
+    }.into()
+}
@@ -0,0 +1,124 @@
+//!Debug checks that globals are only accessed from the threads they are meant for.
+//!
+//!A global restricted with [`Global::restrict_to`](crate::Global::restrict_to) checks the tag of
+//!the current thread on every access in debug builds, and panics if it doesn't match, so misuse
+//!such as a blocking database client being reached from an async executor shows up in tests
+//!rather than as a stalled runtime in production. Nothing is checked in release builds.
+//!
+//!The crate can't tell executor threads apart by itself, so they are tagged by the application,
+//!usually from the executor's thread start hook, with [`mark_thread`], or for the duration of a
+//!scope with [`enter`].
+//!```rust
+//!# use global_static::{Global, access::{self, Access, Tag}};
+//!static DB: Global<Vec<&str>> = Global::<Vec<&str>>::new(|| vec!["users"])
+//!    .restrict_to(Access::NotAsync);
+//!
+//!std::thread::spawn(|| {
+//!    access::mark_thread(Tag::Async);
+//!    let blocking = access::enter(Tag::Blocking);
+//!    assert_eq!(DB[0], "users");
+//!    drop(blocking);
+//!    //accessing `DB` here panics in debug builds
+//!    assert_eq!(access::current_tag(), Some(Tag::Async));
+//!}).join().unwrap();
+//!```
+use std::{cell::Cell, fmt::{self, Display}, marker::PhantomData};
+
+thread_local! {
+    static TAG: Cell<Option<Tag>> = const { Cell::new(None) };
+}
+
+///What a thread is used for, as set with [`mark_thread`] or [`enter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tag {
+    ///The main thread of the application. Threads named `main` by the standard library are
+    ///treated as main without being tagged.
+    Main,
+    ///A worker thread of an async executor, which must not block.
+    Async,
+    ///A thread of a blocking pool, such as the one behind tokio's `spawn_blocking`.
+    Blocking,
+}
+
+///The threads a global may be accessed from, set with
+///[`Global::restrict_to`](crate::Global::restrict_to).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    ///Only the main thread.
+    MainThread,
+    ///Only threads tagged [`Tag::Blocking`].
+    Blocking,
+    ///Any thread, unless it is tagged [`Tag::Async`].
+    NotAsync,
+}
+
+impl Access {
+    ///Whether a thread with `tag` may access the global.
+    pub fn allows(self, tag: Option<Tag>) -> bool {
+        match self {
+            Access::MainThread => tag == Some(Tag::Main),
+            Access::Blocking => tag == Some(Tag::Blocking),
+            Access::NotAsync => tag != Some(Tag::Async),
+        }
+    }
+}
+
+impl Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Access::MainThread => "the main thread",
+            Access::Blocking => "blocking threads",
+            Access::NotAsync => "threads outside of async executors",
+        })
+    }
+}
+
+///Tags the current thread for the rest of its life, such as from an executor's thread start
+///hook.
+pub fn mark_thread(tag: Tag) {
+    TAG.with(|t| t.set(Some(tag)));
+}
+
+///Tags the current thread until the returned guard is dropped, such as inside a closure passed
+///to a blocking pool that runs on executor threads.
+pub fn enter(tag: Tag) -> TagGuard {
+    let previous = TAG.with(|t| t.replace(Some(tag)));
+    TagGuard { previous, _thread: PhantomData }
+}
+
+///Restores the previous tag of the thread when dropped. Returned by [`enter`].
+#[must_use = "the thread is only tagged while the guard is alive"]
+pub struct TagGuard {
+    previous: Option<Tag>,
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for TagGuard {
+    fn drop(&mut self) {
+        TAG.with(|t| t.set(self.previous));
+    }
+}
+
+///Returns the tag of the current thread, if any.
+pub fn current_tag() -> Option<Tag> {
+    //inside thread-local destructors the tag is gone, which is treated as untagged
+    TAG.try_with(Cell::get).ok().flatten().or_else(|| {
+        (std::thread::current().name() == Some("main")).then_some(Tag::Main)
+    })
+}
+
+#[cfg(debug_assertions)]
+pub(crate) fn check<T>(access: Access) {
+    let tag = current_tag();
+    assert!(
+        access.allows(tag),
+        "`{}` is restricted to {access}, but was accessed from {}",
+        std::any::type_name::<T>(),
+        match tag {
+            Some(Tag::Main) => "the main thread",
+            Some(Tag::Async) => "an async executor thread",
+            Some(Tag::Blocking) => "a blocking thread",
+            None => "an untagged thread",
+        },
+    );
+}
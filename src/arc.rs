@@ -0,0 +1,75 @@
+use std::{
+    fmt::{Debug, Display},
+    ops::Deref,
+    sync::{Arc, OnceLock},
+};
+
+///Lazily evaluated static that hands out [`Arc`] handles to its value.
+///
+///This behaves like [`Global`](crate::Global), but [`shared`](GlobalArc::shared) returns an owned
+///`Arc<T>` that can be moved into spawned threads or tasks without wrapping the value in a
+///`Global<Arc<T>>` yourself.
+///```rust
+///# use global_static::GlobalArc;
+///static NAMES: GlobalArc<Vec<&str>> = GlobalArc::new(|| vec!["a", "b"]);
+///
+///let names = NAMES.shared();
+///std::thread::spawn(move || assert_eq!(names.len(), 2)).join().unwrap();
+///```
+pub struct GlobalArc<T> {
+    f: fn() -> T,
+    data: OnceLock<Arc<T>>,
+}
+
+impl<T> GlobalArc<T> {
+    ///Constructs a new global from a function that produces its value.
+    pub const fn new(f: fn() -> T) -> Self {
+        Self { f, data: OnceLock::new() }
+    }
+
+    ///Initializes the contents of a global. Does nothing if already initialized.
+    pub fn init(&self) {
+        self.arc();
+    }
+
+    ///Retrieves a reference to the value inside the global without initializing it.
+    pub fn get(&self) -> Option<&T> {
+        self.data.get().map(Arc::as_ref)
+    }
+
+    ///Returns a new handle to the value, initializing it if needed.
+    pub fn shared(&self) -> Arc<T> {
+        self.arc().clone()
+    }
+
+    fn arc(&self) -> &Arc<T> {
+        self.data.get_or_init(|| Arc::new((self.f)()))
+    }
+}
+
+impl<T: Default> GlobalArc<T> {
+    ///Constructs a new global, using the [`Default`] implementation for `T` as the initializer.
+    #[allow(clippy::should_implement_trait)]
+    pub const fn default() -> Self {
+        Self::new(T::default)
+    }
+}
+
+impl<T> Deref for GlobalArc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.arc()
+    }
+}
+
+impl<T: Debug> Debug for GlobalArc<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.deref())
+    }
+}
+impl<T: Display> Display for GlobalArc<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.deref())
+    }
+}
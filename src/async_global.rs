@@ -0,0 +1,255 @@
+use std::{
+    fmt::Display,
+    future::{poll_fn, Future},
+    pin::Pin,
+    sync::{Arc, Mutex, OnceLock, PoisonError},
+    task::{Context, Poll, Wake, Waker},
+};
+
+///A boxed future, as returned by the initializer of an [`AsyncGlobal`].
+pub type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+///Wakes every task waiting on the global. Used as the waker for the initializer, so no matter
+///which task is polling it, all of them notice progress.
+#[derive(Default)]
+struct Waiters(Mutex<Vec<Waker>>);
+
+impl Waiters {
+    fn register(&self, waker: &Waker) {
+        let mut waiters = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if !waiters.iter().any(|w| w.will_wake(waker)) {
+            waiters.push(waker.clone());
+        }
+    }
+}
+
+impl Wake for Waiters {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+    fn wake_by_ref(self: &Arc<Self>) {
+        let waiters = std::mem::take(&mut *self.0.lock().unwrap_or_else(PoisonError::into_inner));
+        waiters.into_iter().for_each(Waker::wake);
+    }
+}
+
+struct State<T> {
+    started: bool,
+    //taken out while a task is polling it
+    fut: Option<BoxFuture<T>>,
+    //bumped by every cancellation, so a task polling the old future knows to drop it
+    generation: u64,
+    cancelled: bool,
+}
+
+///Error returned when waiting on an [`AsyncGlobal`] whose initialization was cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("global initialization was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+///Lazily evaluated static with an asynchronous initializer.
+///
+///The initializer runs once, driven by whichever tasks are awaiting [`get`](AsyncGlobal::get).
+///If the task polling it is dropped, another waiting task takes over, so this works with any
+///executor.
+///
+///An initialization in progress can be [cancelled](AsyncGlobal::cancel), for example on
+///shutdown. This drops its future, and the next access starts a new one.
+///```rust
+///# use global_static::AsyncGlobal;
+///async fn connect() -> String { "connection".to_owned() }
+///
+///static CONN: AsyncGlobal<String> = AsyncGlobal::new(|| Box::pin(connect()));
+///
+///async fn handler() {
+///    let conn: &String = CONN.get().await;
+///}
+///```
+pub struct AsyncGlobal<T> {
+    f: fn() -> BoxFuture<T>,
+    state: Mutex<State<T>>,
+    waiters: OnceLock<Arc<Waiters>>,
+    data: OnceLock<T>,
+}
+
+//resets the global if polling the initializer panics, so the next access starts over
+struct Restart<'a, T>(&'a Mutex<State<T>>);
+impl<T> Drop for Restart<'_, T> {
+    fn drop(&mut self) {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).started = false;
+    }
+}
+
+impl<T> AsyncGlobal<T> {
+    ///Constructs a new global from a function that produces a future of its value.
+    pub const fn new(f: fn() -> BoxFuture<T>) -> Self {
+        Self { 
+            f, 
+            state: Mutex::new(State { started: false, fut: None, generation: 0, cancelled: false }), 
+            waiters: OnceLock::new(),
+            data: OnceLock::new(),
+        }
+    }
+
+    ///Retrieves the value without waiting or initializing it.
+    pub fn try_get(&self) -> Option<&T> {
+        self.data.get()
+    }
+
+    ///Retrieves the value, initializing it if needed. If the initialization is cancelled while
+    ///waiting, this starts a new one.
+    pub async fn get(&self) -> &T {
+        poll_fn(|cx| self.poll_get(cx, None).map(|res| res.unwrap_or_else(|_| unreachable!()))).await
+    }
+
+    ///Retrieves the value, initializing it if needed. Returns an error if the initialization
+    ///this is waiting on is cancelled, instead of starting a new one.
+    ///```rust
+    ///# use global_static::{AsyncGlobal, Cancelled};
+    ///# use std::{future::Future, pin::pin, task::{Context, Poll, Waker}};
+    ///static NEVER: AsyncGlobal<u8> = AsyncGlobal::new(|| Box::pin(std::future::pending()));
+    ///
+    ///let mut cx = Context::from_waker(Waker::noop());
+    ///let mut waiting = pin!(NEVER.get_cancellable());
+    ///assert!(waiting.as_mut().poll(&mut cx).is_pending());
+    ///
+    ///assert!(NEVER.cancel());
+    ///assert!(NEVER.is_cancelled());
+    ///assert_eq!(waiting.poll(&mut cx), Poll::Ready(Err(Cancelled)));
+    ///```
+    pub async fn get_cancellable(&self) -> Result<&T, Cancelled> {
+        let mut generation = None;
+        poll_fn(|cx| self.poll_get(cx, Some(&mut generation))).await
+    }
+
+    ///Cancels an initialization in progress by dropping its future, and wakes every task waiting
+    ///on it. Tasks in [`get_cancellable`](AsyncGlobal::get_cancellable) return an error, and the
+    ///next access starts a new initialization. Returns whether there was one to cancel.
+    ///
+    ///If another task is polling the future right now, it is dropped once that poll returns.
+    pub fn cancel(&self) -> bool {
+        let fut = {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            if !state.started || self.data.get().is_some() {
+                return false;
+            }
+            state.started = false;
+            state.cancelled = true;
+            state.generation += 1;
+            state.fut.take()
+        };
+        //dropped outside the lock, in case the future's destructor accesses the global
+        drop(fut);
+        if let Some(waiters) = self.waiters.get() {
+            waiters.wake_by_ref();
+        }
+        true
+    }
+
+    ///Whether the last initialization was cancelled, and no new one has started since.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner).cancelled
+    }
+
+    //`generation` is the initialization a cancellable access is waiting on, recorded on its
+    //first poll
+    fn poll_get(&self, cx: &mut Context<'_>, generation: Option<&mut Option<u64>>) -> Poll<Result<&T, Cancelled>> {
+        if let Some(v) = self.data.get() {
+            return Poll::Ready(Ok(v));
+        }
+        let waiters = self.waiters.get_or_init(Default::default);
+        waiters.register(cx.waker());
+
+        let (mut fut, current) = {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            if let Some(generation) = generation {
+                match *generation {
+                    Some(g) if g != state.generation => return Poll::Ready(Err(Cancelled)),
+                    Some(_) => {},
+                    None => *generation = Some(state.generation),
+                }
+            }
+            if !state.started {
+                state.started = true;
+                state.cancelled = false;
+                state.fut = Some((self.f)());
+            }
+            match state.fut.take() {
+                Some(fut) => (fut, state.generation),
+                //another task is polling, it will wake us
+                None => return self.data.get().map_or(Poll::Pending, |v| Poll::Ready(Ok(v))),
+            }
+        };
+
+        let restart = Restart(&self.state);
+        let waker = Waker::from(waiters.clone());
+        let poll = fut.as_mut().poll(&mut Context::from_waker(&waker));
+        std::mem::forget(restart);
+        match poll {
+            Poll::Ready(value) => {
+                //checked under the lock, so `cancel` can't slip in before the value is stored
+                let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+                if state.generation != current {
+                    //cancelled while polling, so the result is discarded
+                    drop(state);
+                    drop(value);
+                    return Poll::Pending;
+                }
+                let rejected = self.data.set(value);
+                drop(state);
+                drop(rejected);
+                waiters.wake_by_ref();
+                Poll::Ready(Ok(self.data.get().unwrap()))
+            },
+            Poll::Pending => {
+                let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+                if state.generation == current {
+                    state.fut = Some(fut);
+                    return Poll::Pending;
+                }
+                //cancelled while polling
+                drop(state);
+                drop(fut);
+                Poll::Pending
+            },
+        }
+    }
+}
+
+#[macro_export]
+///Generate statics with asynchronous initializers.
+///
+///```rust
+///# use global_static::async_static;
+///async fn connect() -> String { "connection".to_owned() }
+///
+///async_static! {
+///    pub CONN: String = connect();
+///    GREETING: String = async { "hello".to_owned() };
+///};
+///```
+///This code will expand to the following:
+///```rust
+///# use global_static::AsyncGlobal;
+///# async fn connect() -> String { "connection".to_owned() }
+///pub static CONN: AsyncGlobal<String> = AsyncGlobal::new(|| Box::pin(connect()));
+///static GREETING: AsyncGlobal<String> = AsyncGlobal::new(|| Box::pin(async { "hello".to_owned() }));
+///```
+macro_rules! async_static {
+    () => {};
+    ($name:ident: $type:ty = $init:expr; $($tail:tt)*) => {
+        static $name: $crate::AsyncGlobal<$type> = $crate::AsyncGlobal::new(|| ::std::boxed::Box::pin($init));
+        $crate::async_static!($($tail)*);
+    };
+    (pub $name:ident: $type:ty = $init:expr; $($tail:tt)*) => {
+        pub static $name: $crate::AsyncGlobal<$type> = $crate::AsyncGlobal::new(|| ::std::boxed::Box::pin($init));
+        $crate::async_static!($($tail)*);
+    };
+}
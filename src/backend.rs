@@ -0,0 +1,344 @@
+use core::{cell::UnsafeCell, mem::MaybeUninit};
+#[cfg(feature = "std")]
+use std::sync::Once;
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(feature = "std"))]
+use core::sync::atomic::{AtomicU8, Ordering};
+
+///A once-cell that a [`Global`](crate::Global) can store its value in.
+///
+///The default backend is built on [`Once`](std::sync::Once), or a spin lock without the `std`
+///feature, but environments with their own synchronization
+///primitives (interrupt-safe cells, GC-integrated hosts) can supply their own.
+///```rust
+///# use global_static::{Global, OnceBackend};
+///# use std::sync::OnceLock;
+///struct MyCell<T>(OnceLock<T>);
+///
+///impl<T> OnceBackend<T> for MyCell<T> {
+///    #[allow(clippy::declare_interior_mutable_const)]
+///    const EMPTY: Self = MyCell(OnceLock::new());
+///
+///    fn get(&self) -> Option<&T> { self.0.get() }
+///    fn set(&self, value: T) -> Result<(), T> { self.0.set(value) }
+///    fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &T { self.0.get_or_init(f) }
+///}
+///
+///static MY_NUM: Global<i32, MyCell<i32>> = Global::new(|| 5);
+///assert_eq!(*MY_NUM, 5);
+///```
+pub trait OnceBackend<T> {
+    ///An empty, uninitialized cell. This is a constant so that globals can be constructed in
+    ///const contexts.
+    const EMPTY: Self;
+
+    ///Retrieves the value if the cell has been initialized.
+    fn get(&self) -> Option<&T>;
+
+    ///Sets the value of the cell, returning it back if the cell was already initialized.
+    fn set(&self, value: T) -> Result<(), T>;
+
+    ///Retrieves the value of the cell, initializing it with `f` if it is empty. Implementations
+    ///must ensure `f` runs at most once, even under concurrent access.
+    fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &T;
+
+    ///Like [`get_or_init`](OnceBackend::get_or_init), but `f` writes the value into the cell's
+    ///storage directly. The default implementation initializes a value on the stack and moves
+    ///it into the cell.
+    ///
+    ///# Safety
+    ///`f` must fully initialize the value it is given.
+    unsafe fn get_or_init_in_place<F: FnOnce(&mut MaybeUninit<T>)>(&self, f: F) -> &T {
+        self.get_or_init(|| {
+            let mut slot = MaybeUninit::uninit();
+            f(&mut slot);
+            slot.assume_init()
+        })
+    }
+
+    ///Drops the value in place if the cell is initialized. Backends that can't do this, such as
+    ///ones built on `OnceLock`, keep the default implementation, which leaves the value alone.
+    ///
+    ///# Safety
+    ///No references to the value may be alive, and the cell must not be accessed, deinitialized
+    ///again, or dropped afterwards.
+    unsafe fn deinit(&self) {}
+
+    ///Moves the value out if the cell is initialized, leaving the cell to be treated as empty by
+    ///[`deinit`](OnceBackend::deinit) and drop. Returns `None` if it is empty, has already been
+    ///taken or deinitialized, or if the backend can't do this, which is the default.
+    ///
+    ///# Safety
+    ///No references to the value may be alive, and the cell must not be accessed afterwards.
+    unsafe fn take(&self) -> Option<T> {
+        None
+    }
+}
+
+///The backend used by [`Global`](crate::Global) unless another is specified. Stores the value
+///inline, and supports initializing it in place.
+#[cfg(feature = "std")]
+pub struct DefaultBackend<T> {
+    once: Once,
+    //set for cells constructed with a value, since a `Once` can't be completed in const
+    preset: bool,
+    //set once the value has been taken or dropped, so it is never dropped again
+    emptied: AtomicBool,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+//same bounds as OnceLock
+#[cfg(feature = "std")]
+unsafe impl<T: Send + Sync> Sync for DefaultBackend<T> {}
+#[cfg(feature = "std")]
+unsafe impl<T: Send> Send for DefaultBackend<T> {}
+
+#[cfg(feature = "std")]
+impl<T> DefaultBackend<T> {
+    //a cell that is already initialized
+    pub(crate) const fn with_value(value: T) -> Self {
+        DefaultBackend { once: Once::new(), preset: true, emptied: AtomicBool::new(false), value: UnsafeCell::new(MaybeUninit::new(value)) }
+    }
+
+    //an emptied value reads as unset, so a stray access after `take_at_exit` sees `None`
+    #[inline]
+    fn is_set(&self) -> bool {
+        (self.preset || self.once.is_completed()) && !self.emptied.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> OnceBackend<T> for DefaultBackend<T> {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const EMPTY: Self = DefaultBackend { once: Once::new(), preset: false, emptied: AtomicBool::new(false), value: UnsafeCell::new(MaybeUninit::uninit()) };
+
+    #[inline]
+    fn get(&self) -> Option<&T> {
+        //safety: once completed, the value is initialized and never written again
+        self.is_set().then(|| unsafe { (*self.value.get()).assume_init_ref() })
+    }
+
+    fn set(&self, value: T) -> Result<(), T> {
+        if self.preset {
+            return Err(value);
+        }
+        let mut value = Some(value);
+        //safety: call_once_force gives us exclusive access to the value
+        self.once.call_once_force(|_| unsafe {
+            (*self.value.get()).write(value.take().unwrap());
+        });
+        match value {
+            None => Ok(()),
+            Some(value) => Err(value),
+        }
+    }
+
+    fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &T {
+        //a panicking initializer leaves the once incomplete, so the next access tries again
+        if !self.preset {
+            self.once.call_once_force(|_| unsafe {
+                (*self.value.get()).write(f());
+            });
+        }
+        assert!(!self.emptied.load(Ordering::Acquire), "global accessed after its value was taken");
+        unsafe { (*self.value.get()).assume_init_ref() }
+    }
+
+    unsafe fn get_or_init_in_place<F: FnOnce(&mut MaybeUninit<T>)>(&self, f: F) -> &T {
+        if !self.preset {
+            self.once.call_once_force(|_| f(&mut *self.value.get()));
+        }
+        assert!(!self.emptied.load(Ordering::Acquire), "global accessed after its value was taken");
+        (*self.value.get()).assume_init_ref()
+    }
+
+    unsafe fn deinit(&self) {
+        if self.is_set() && !self.emptied.swap(true, Ordering::AcqRel) {
+            (*self.value.get()).assume_init_drop();
+        }
+    }
+
+    unsafe fn take(&self) -> Option<T> {
+        (self.is_set() && !self.emptied.swap(true, Ordering::AcqRel))
+            .then(|| (*self.value.get()).assume_init_read())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Drop for DefaultBackend<T> {
+    fn drop(&mut self) {
+        if self.is_set() {
+            unsafe { self.value.get_mut().assume_init_drop() }
+        }
+    }
+}
+
+
+#[cfg(not(feature = "std"))]
+const EMPTY: u8 = 0;
+#[cfg(not(feature = "std"))]
+const RUNNING: u8 = 1;
+#[cfg(not(feature = "std"))]
+const DONE: u8 = 2;
+//the value was taken or dropped
+#[cfg(not(feature = "std"))]
+const EMPTIED: u8 = 3;
+
+///The backend used by [`Global`](crate::Global) unless another is specified. Stores the value
+///inline, and supports initializing it in place.
+///
+///Without the `std` feature this is a spin lock, so threads that access the global while it is
+///being initialized busy-wait until it is done.
+#[cfg(not(feature = "std"))]
+pub struct DefaultBackend<T> {
+    state: AtomicU8,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+//same bounds as OnceLock
+#[cfg(not(feature = "std"))]
+unsafe impl<T: Send + Sync> Sync for DefaultBackend<T> {}
+#[cfg(not(feature = "std"))]
+unsafe impl<T: Send> Send for DefaultBackend<T> {}
+
+//resets the state if the initializer panics, so the next access tries again
+#[cfg(not(feature = "std"))]
+struct Reset<'a>(&'a AtomicU8);
+#[cfg(not(feature = "std"))]
+impl Drop for Reset<'_> {
+    fn drop(&mut self) {
+        self.0.store(EMPTY, Ordering::Release);
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<T> DefaultBackend<T> {
+    //a cell that is already initialized
+    pub(crate) const fn with_value(value: T) -> Self {
+        DefaultBackend { state: AtomicU8::new(DONE), value: UnsafeCell::new(MaybeUninit::new(value)) }
+    }
+
+    ///Runs `f` with exclusive access to the value if the cell is empty, and waits for it to be
+    ///initialized otherwise. Does nothing if the value was taken.
+    fn once(&self, f: impl FnOnce(&mut MaybeUninit<T>)) {
+        loop {
+            match self.state.compare_exchange_weak(EMPTY, RUNNING, Ordering::Acquire, Ordering::Acquire) {
+                Ok(_) => {
+                    let reset = Reset(&self.state);
+                    //safety: the RUNNING state gives us exclusive access
+                    f(unsafe { &mut *self.value.get() });
+                    core::mem::forget(reset);
+                    self.state.store(DONE, Ordering::Release);
+                    return;
+                },
+                Err(DONE | EMPTIED) => return,
+                Err(_) => core::hint::spin_loop(),
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<T> OnceBackend<T> for DefaultBackend<T> {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const EMPTY: Self = DefaultBackend { state: AtomicU8::new(EMPTY), value: UnsafeCell::new(MaybeUninit::uninit()) };
+
+    #[inline]
+    fn get(&self) -> Option<&T> {
+        //safety: once done, the value is initialized and never written again
+        (self.state.load(Ordering::Acquire) == DONE).then(|| unsafe { (*self.value.get()).assume_init_ref() })
+    }
+
+    fn set(&self, value: T) -> Result<(), T> {
+        let mut value = Some(value);
+        self.once(|slot| { slot.write(value.take().unwrap()); });
+        match value {
+            None => Ok(()),
+            Some(value) => Err(value),
+        }
+    }
+
+    fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &T {
+        self.once(|slot| { slot.write(f()); });
+        assert!(self.state.load(Ordering::Acquire) != EMPTIED, "global accessed after its value was taken");
+        unsafe { (*self.value.get()).assume_init_ref() }
+    }
+
+    unsafe fn get_or_init_in_place<F: FnOnce(&mut MaybeUninit<T>)>(&self, f: F) -> &T {
+        self.once(f);
+        assert!(self.state.load(Ordering::Acquire) != EMPTIED, "global accessed after its value was taken");
+        (*self.value.get()).assume_init_ref()
+    }
+
+    unsafe fn deinit(&self) {
+        if self.state.compare_exchange(DONE, EMPTIED, Ordering::AcqRel, Ordering::Acquire).is_ok() {
+            (*self.value.get()).assume_init_drop();
+        }
+    }
+
+    unsafe fn take(&self) -> Option<T> {
+        self.state.compare_exchange(DONE, EMPTIED, Ordering::AcqRel, Ordering::Acquire).ok()
+            .map(|_| (*self.value.get()).assume_init_read())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<T> Drop for DefaultBackend<T> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() == DONE {
+            unsafe { self.value.get_mut().assume_init_drop() }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_after_panic() {
+        static CELL: DefaultBackend<u8> = DefaultBackend::EMPTY;
+        let res = std::panic::catch_unwind(|| CELL.get_or_init(|| panic!("boom")));
+        assert!(res.is_err());
+        assert_eq!(CELL.get(), None);
+        assert_eq!(CELL.get_or_init(|| 1), &1);
+        assert_eq!(CELL.set(2), Err(2));
+    }
+
+    #[test]
+    fn concurrent_init_runs_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+        static CELL: DefaultBackend<usize> = DefaultBackend::EMPTY;
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| CELL.get_or_init(|| RUNS.fetch_add(1, Ordering::SeqCst)));
+            }
+        });
+        assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn take_once() {
+        let cell: DefaultBackend<String> = DefaultBackend::EMPTY;
+        cell.set("log".to_owned()).unwrap();
+        unsafe {
+            assert_eq!(cell.take().as_deref(), Some("log"));
+            assert_eq!(cell.take(), None);
+            cell.deinit();
+        }
+        //accesses after the value was taken see nothing, rather than the moved-out value
+        assert_eq!(cell.get(), None);
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cell.get_or_init(String::new).len()));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn preset_value() {
+        static CELL: DefaultBackend<u8> = DefaultBackend::with_value(1);
+        assert_eq!(CELL.get(), Some(&1));
+        assert_eq!(CELL.get_or_init(|| unreachable!()), &1);
+        assert_eq!(CELL.set(2), Err(2));
+    }
+}
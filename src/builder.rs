@@ -0,0 +1,153 @@
+use core::{marker::PhantomData, mem::MaybeUninit, time::Duration};
+
+#[cfg(debug_assertions)]
+use crate::Validator;
+#[cfg(feature = "std")]
+use crate::Offload;
+use crate::{DefaultBackend, Global, Init, OnceBackend};
+
+///Builder for a [`Global`] with several options, from [`Global::builder`].
+///
+///Every method is `const`, so the global can be built in a `static`. The initializer is the only
+///required option, and building without one fails to compile when the builder is evaluated in
+///a `static`. Each option does the same as the method on [`Global`] with the matching name.
+///```rust
+///# use global_static::Global;
+///# use std::time::Duration;
+///static PRIMES: Global<Vec<u32>> = Global::builder()
+///    .name("PRIMES")
+///    .init(|| vec![2, 3, 5, 7])
+///    .budget(Duration::from_millis(5))
+///    .ensure("sorted", |v| v.windows(2).all(|w| w[0] < w[1]))
+///    .build();
+///
+///assert_eq!(PRIMES[3], 7);
+///```
+#[must_use = "the global is only created by `build`"]
+pub struct GlobalBuilder<T, B: OnceBackend<T> = DefaultBackend<T>> {
+    //the builder holds the options rather than a global, so it has no destructor to run in
+    //const contexts
+    init: Option<Init<T>>,
+    #[cfg(feature = "instrumented")]
+    budget: Option<Duration>,
+    #[cfg(debug_assertions)]
+    validator: Option<Validator<T>>,
+    #[cfg(feature = "std")]
+    offload: Option<Offload<T>>,
+    #[cfg(all(feature = "std", debug_assertions))]
+    access: Option<crate::access::Access>,
+    #[cfg(feature = "tracing")]
+    name: Option<&'static str>,
+    backend: PhantomData<fn() -> B>,
+}
+
+impl<T, B: OnceBackend<T>> Global<T, B> {
+    ///Starts building a global with a [`GlobalBuilder`], for globals that set several options.
+    pub const fn builder() -> GlobalBuilder<T, B> {
+        GlobalBuilder {
+            init: None,
+            #[cfg(feature = "instrumented")]
+            budget: None,
+            #[cfg(debug_assertions)]
+            validator: None,
+            #[cfg(feature = "std")]
+            offload: None,
+            #[cfg(all(feature = "std", debug_assertions))]
+            access: None,
+            #[cfg(feature = "tracing")]
+            name: None,
+            backend: PhantomData,
+        }
+    }
+}
+
+impl<T, B: OnceBackend<T>> GlobalBuilder<T, B> {
+    ///Sets the function that produces the value. See [`Global::new`].
+    pub const fn init(mut self, f: fn() -> T) -> Self {
+        self.init = Some(Init::Value(f));
+        self
+    }
+
+    ///Sets a function that initializes the value in place. See [`Global::new_in_place`].
+    ///
+    ///# Safety
+    ///The initializer must fully initialize the value it is given.
+    pub const unsafe fn init_in_place(mut self, f: unsafe fn(&mut MaybeUninit<T>)) -> Self {
+        self.init = Some(Init::InPlace(f));
+        self
+    }
+
+    ///Sets the name of the global. See [`Global::named`].
+    #[allow(unused_mut)]
+    pub const fn name(mut self, name: &'static str) -> Self {
+        #[cfg(feature = "tracing")]
+        { self.name = Some(name); }
+        let _ = name;
+        self
+    }
+
+    ///Sets how long the initializer is expected to take. See [`Global::with_budget`].
+    #[allow(unused_mut)]
+    pub const fn budget(mut self, budget: Duration) -> Self {
+        #[cfg(feature = "instrumented")]
+        { self.budget = Some(budget); }
+        let _ = budget;
+        self
+    }
+
+    ///Checks an invariant of the value on every access in debug builds. See
+    ///[`Global::ensure_with`].
+    #[allow(unused_mut)]
+    pub const fn ensure(mut self, name: &'static str, check: fn(&T) -> bool) -> Self {
+        #[cfg(debug_assertions)]
+        { self.validator = Some((name, check)); }
+        let _ = (name, check);
+        self
+    }
+
+    ///Builds the global.
+    ///
+    ///# Panics
+    ///Panics if no initializer was set, which is a compile error in a `static`.
+    pub const fn build(self) -> Global<T, B> {
+        let Some(init) = self.init else {
+            panic!("`GlobalBuilder::build` called without an initializer");
+        };
+        #[allow(unused_mut)]
+        let mut global = Global::from_init(init, B::EMPTY);
+        #[cfg(feature = "instrumented")]
+        { global.budget = self.budget; }
+        #[cfg(debug_assertions)]
+        { global.validator = self.validator; }
+        #[cfg(feature = "std")]
+        { global.offload = self.offload; }
+        #[cfg(all(feature = "std", debug_assertions))]
+        { global.access = self.access; }
+        #[cfg(feature = "tracing")]
+        { global.name = self.name; }
+        global
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Send + 'static, B: OnceBackend<T>> GlobalBuilder<T, B> {
+    ///Lets the global be initialized on the init executor. See [`Global::offload`].
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub const fn offload(mut self) -> Self {
+        self.offload = Some(crate::executor::run::<T>);
+        self
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, B: OnceBackend<T>> GlobalBuilder<T, B> {
+    ///Restricts the threads the global may be accessed from. See [`Global::restrict_to`].
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[allow(unused_mut)]
+    pub const fn restrict_to(mut self, access: crate::access::Access) -> Self {
+        #[cfg(debug_assertions)]
+        { self.access = Some(access); }
+        let _ = access;
+        self
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    fmt::Debug,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+///Lazily evaluated static whose value is cached on disk between runs.
+///
+///Globals that build big derived tables (lookup tables, indexes, parsed grammars) can take a
+///long time to initialize on every start. The first run stores the value in a cache file, and
+///later runs read it back instead of running the initializer. The file is keyed by the
+///global's name, the given version, and the type of the value, so bumping the version
+///invalidates old caches.
+///
+///Caches are stored in the directory named by the `GLOBAL_STATIC_CACHE_DIR` environment
+///variable, or a `global-static` directory in the system temporary directory otherwise. Caching
+///is best effort: if the file can't be read, written, or decoded, the initializer runs as usual.
+///```rust
+///# use global_static::GlobalCached;
+///fn squares() -> Vec<u64> {
+///    (0..1000).map(|n| n * n).collect()
+///}
+///static SQUARES: GlobalCached<Vec<u64>> = GlobalCached::new("squares", "1", squares);
+///
+///assert_eq!(SQUARES[12], 144);
+///```
+pub struct GlobalCached<T> {
+    name: &'static str,
+    version: &'static str,
+    f: fn() -> T,
+    data: OnceLock<T>,
+    from_cache: AtomicBool,
+}
+
+impl<T: Serialize + DeserializeOwned> GlobalCached<T> {
+    ///Constructs a new cached global. `name` must be unique among cached globals, and `version`
+    ///should change whenever the initializer produces a different value.
+    pub const fn new(name: &'static str, version: &'static str, f: fn() -> T) -> Self {
+        Self { name, version, f, data: OnceLock::new(), from_cache: AtomicBool::new(false) }
+    }
+
+    ///Initializes the contents of a global. Does nothing if already initialized.
+    pub fn init(&self) {
+        self.force();
+    }
+
+    ///Retrieves a reference to the value inside the global without initializing it.
+    pub fn get(&self) -> Option<&T> {
+        self.data.get()
+    }
+
+    ///Retrieves a reference to the value inside the global, initializing it if needed.
+    pub fn force(&self) -> &T {
+        self.data.get_or_init(|| {
+            let path = self.path();
+            if let Some(value) = std::fs::read(&path).ok().and_then(|b| bincode::deserialize(&b).ok()) {
+                self.from_cache.store(true, Ordering::Relaxed);
+                return value;
+            }
+            let value = (self.f)();
+            if let Ok(bytes) = bincode::serialize(&value) {
+                //write to a temporary file first, so other processes never read a partial cache
+                let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+                let written = path.parent().is_some_and(|dir| std::fs::create_dir_all(dir).is_ok())
+                    && std::fs::write(&tmp, bytes).is_ok();
+                if !written || std::fs::rename(&tmp, &path).is_err() {
+                    let _ = std::fs::remove_file(&tmp);
+                }
+            }
+            value
+        })
+    }
+
+    ///Whether the value was read from the cache rather than produced by the initializer.
+    pub fn loaded_from_cache(&self) -> bool {
+        self.from_cache.load(Ordering::Relaxed)
+    }
+
+    ///The cache file for this global.
+    pub fn path(&self) -> PathBuf {
+        let dir = std::env::var_os("GLOBAL_STATIC_CACHE_DIR")
+            .map_or_else(|| std::env::temp_dir().join("global-static"), PathBuf::from);
+        let key = fnv1a([self.version, std::any::type_name::<T>()]);
+        dir.join(format!("{}-{key:016x}.bin", self.name))
+    }
+
+    ///Removes the cache file, so the next run initializes the global from scratch.
+    pub fn invalidate(&self) -> std::io::Result<()> {
+        match std::fs::remove_file(self.path()) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            res => res,
+        }
+    }
+}
+
+//stable across runs and compiler versions, unlike the std hasher
+fn fnv1a<'a>(parts: impl IntoIterator<Item = &'a str>) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for part in parts {
+        for byte in part.bytes().chain([0]) {
+            hash = (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+#[cfg(not(feature = "no-panic"))]
+impl<T: Serialize + DeserializeOwned> std::ops::Deref for GlobalCached<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.force()
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + Debug> Debug for GlobalCached<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.force())
+    }
+}
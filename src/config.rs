@@ -0,0 +1,86 @@
+use core::{fmt::Display, str::FromStr};
+
+use crate::{Global, Init, OnceBackend};
+
+impl<T: FromStr, B: OnceBackend<T>> Global<T, B>
+where
+    T::Err: Display,
+{
+    ///Constructs a new global that parses the environment variable `var` on first access.
+    ///
+    ///# Panics
+    ///Accessing the global panics if the variable is not set, or can't be parsed. The global is
+    ///then poisoned, and can still be [`set`](Global::set) by hand.
+    ///```rust
+    ///# use global_static::Global;
+    ///static WORKERS: Global<usize> = Global::from_env("APP_WORKERS");
+    ///
+    ///std::env::set_var("APP_WORKERS", "8");
+    ///assert_eq!(*WORKERS, 8);
+    ///```
+    pub const fn from_env(var: &'static str) -> Self {
+        Self::from_init(Init::With(env_var::<T>, var), B::EMPTY)
+    }
+}
+
+fn env_var<T: FromStr>(var: &'static str) -> T
+where
+    T::Err: Display,
+{
+    parse_var(var, std::env::var(var))
+}
+
+//split from reading the variable so tests don't need to change the environment of the process
+pub(crate) fn parse_var<T: FromStr>(var: &'static str, value: Result<String, std::env::VarError>) -> T
+where
+    T::Err: Display,
+{
+    let value = match value {
+        Ok(v) => v,
+        Err(e) => panic!("environment variable `{var}` for `{}`: {e}", core::any::type_name::<T>()),
+    };
+    match value.parse() {
+        Ok(v) => v,
+        Err(e) => panic!("environment variable `{var}` is not a valid `{}`: {e}", core::any::type_name::<T>()),
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::de::DeserializeOwned, B: OnceBackend<T>> Global<T, B> {
+    ///Constructs a new global that reads and deserializes the JSON file at `path` on first
+    ///access. Relative paths are resolved against the working directory at that point.
+    ///
+    ///# Panics
+    ///Accessing the global panics if the file can't be read or deserialized. The global is then
+    ///poisoned, and can still be [`set`](Global::set) by hand.
+    ///```rust,no_run
+    ///# use global_static::Global;
+    ///# use std::collections::HashMap;
+    ///static ROUTES: Global<HashMap<String, String>> = Global::from_config("routes.json");
+    ///```
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    pub const fn from_config(path: &'static str) -> Self {
+        Self::from_init(Init::With(config_file::<T>, path), B::EMPTY)
+    }
+}
+
+#[cfg(feature = "serde")]
+fn config_file<T: serde::de::DeserializeOwned>(path: &'static str) -> T {
+    let file = match std::fs::read(path) {
+        Ok(f) => f,
+        Err(e) => panic!("config file `{path}` for `{}`: {e}", core::any::type_name::<T>()),
+    };
+    match serde_json::from_slice(&file) {
+        Ok(v) => v,
+        Err(e) => panic!("config file `{path}` is not a valid `{}`: {e}", core::any::type_name::<T>()),
+    }
+}
+
+///Serializes the value of the global, initializing it if needed.
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<T: serde::Serialize, B: OnceBackend<T>> serde::Serialize for Global<T, B> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.force().serialize(serializer)
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    panic::Location,
+    sync::Mutex,
+};
+
+use crate::{AlreadyInit, Global, Init, OnceBackend};
+
+struct Contribution {
+    file: &'static str,
+    line: u32,
+    //a `fn() -> Vec<Item>` for the global's item type
+    items: Box<dyn Any + Send>,
+}
+
+//contributions by address of the global, or `None` once it has collected them
+static CONTRIBUTIONS: Mutex<Option<HashMap<usize, Option<Vec<Contribution>>>>> = Mutex::new(None);
+
+impl<T, B> Global<T, B>
+where
+    T: FromIterator<T::Item> + IntoIterator,
+    T::Item: 'static,
+    B: OnceBackend<T>,
+{
+    ///Constructs a new global that collects the items given to it with
+    ///[`contribute`](Global::contribute) or `contribute_to!`, for tables that several crates
+    ///add to, such as routes or codecs.
+    ///
+    ///Contributions are collected when the global is initialized, ordered by the source file and
+    ///line they were made from, so the order doesn't depend on link or ctor order.
+    ///```rust
+    ///# use global_static::Global;
+    ///static CODECS: Global<Vec<&str>> = Global::new_from_iter();
+    ///
+    ///CODECS.contribute(|| vec!["json"]).unwrap();
+    ///CODECS.contribute(|| vec!["cbor", "msgpack"]).unwrap();
+    ///assert_eq!(*CODECS, ["json", "cbor", "msgpack"]);
+    ///assert!(CODECS.contribute(|| vec!["toml"]).is_err());
+    ///```
+    pub const fn new_from_iter() -> Self {
+        Self::from_init(Init::Collect(collect::<T>), B::EMPTY)
+    }
+
+    ///Adds items to a global constructed with [`new_from_iter`](Global::new_from_iter). `items`
+    ///is run when the global is initialized. Returns an error if the global has already
+    ///collected its contributions.
+    #[track_caller]
+    pub fn contribute(&self, items: fn() -> Vec<T::Item>) -> Result<(), AlreadyInit> {
+        let location = Location::caller();
+        let contribution = Contribution { file: location.file(), line: location.line(), items: Box::new(items) };
+        let mut lock = CONTRIBUTIONS.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = lock.get_or_insert_with(HashMap::new).entry(self as *const Self as usize).or_insert_with(|| Some(Vec::new()));
+        match entry {
+            Some(list) if self.get().is_none() => {
+                list.push(contribution);
+                Ok(())
+            },
+            _ => Err(AlreadyInit),
+        }
+    }
+}
+
+fn collect<T>(addr: usize) -> T
+where
+    T: FromIterator<T::Item> + IntoIterator,
+    T::Item: 'static,
+{
+    let mut list = {
+        let mut lock = CONTRIBUTIONS.lock().unwrap_or_else(|e| e.into_inner());
+        lock.get_or_insert_with(HashMap::new).insert(addr, None).flatten().unwrap_or_default()
+    };
+    list.sort_by_key(|c| (c.file, c.line));
+    //run outside the lock, so contributors can access other globals
+    list.into_iter()
+        .flat_map(|c| match c.items.downcast::<fn() -> Vec<T::Item>>() {
+            Ok(items) => items(),
+            Err(_) => unreachable!("contributions are added with the global's item type"),
+        })
+        .collect()
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "ctor")))]
+#[cfg(all(feature = "ctor", not(any(feature = "ctor-fallback", target_family = "wasm"))))]
+#[macro_export]
+///Contribute items to a global constructed with
+///[`Global::new_from_iter`](crate::Global::new_from_iter) at startup, from any crate that can
+///name it. The closure returns anything that can be iterated over, and is run when the global
+///is initialized. This is unavailable on platforms without life-before-main, where
+///[`contribute`](crate::Global::contribute) should be called in `main` instead.
+///```rust
+///# use global_static::Global;
+///pub static ROUTES: Global<Vec<&str>> = Global::new_from_iter();
+///
+///mod users {
+///    global_static::contribute_to!(super::ROUTES, || ["/users", "/users/{id}"]);
+///}
+///mod health {
+///    global_static::contribute_to!(super::ROUTES, || ["/health"]);
+///}
+///
+///# fn main() {
+///assert_eq!(*ROUTES, ["/users", "/users/{id}", "/health"]);
+///# }
+///```
+macro_rules! contribute_to {
+    ($global:path, $items:expr $(,)?) => {
+        const _: () = {
+            #[$crate::ctor::ctor]
+            fn _global_contribute() {
+                if $global.contribute(|| ::std::iter::IntoIterator::into_iter(($items)()).collect()).is_err() {
+                    panic!(concat!("`", stringify!($global), "` was initialized before a contribution to it"));
+                }
+            }
+        };
+    };
+}
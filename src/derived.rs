@@ -0,0 +1,158 @@
+use core::fmt::Debug;
+
+use crate::{DefaultBackend, Global, OnceBackend};
+
+///A global that other globals can be derived from with `map` and `zip`.
+pub trait Source<T>: Sync {
+    ///Retrieves the value, initializing it if needed.
+    fn value(&'static self) -> &'static T;
+}
+
+impl<T, B: OnceBackend<T>> Source<T> for Global<T, B> where Self: Sync {
+    fn value(&'static self) -> &'static T {
+        self.force()
+    }
+}
+
+///Lazily evaluated static computed from another global.
+///
+///The source is initialized first if needed, and the mapping runs once, on first access. Since
+///the source is always initialized before the derived value, this also holds for globals
+///declared with `ctor_static!`.
+///```rust
+///# use global_static::{Global, MappedGlobal};
+///static CONFIG: Global<Vec<&str>> = Global::new(|| vec!["/", "/users"]);
+///static ROUTES: MappedGlobal<Vec<&str>, usize> = CONFIG.map(|c| c.len());
+///
+///assert_eq!(*ROUTES, 2);
+///```
+pub struct MappedGlobal<S: 'static, T> {
+    source: &'static dyn Source<S>,
+    f: fn(&S) -> T,
+    data: DefaultBackend<T>,
+}
+
+///Lazily evaluated static pairing two globals, to derive a value from both with
+///[`map`](ZipGlobal::map).
+///```rust
+///# use global_static::{Global, MappedGlobal, ZipGlobal};
+///static HOST: Global<&str> = Global::new(|| "localhost");
+///static PORT: Global<u16> = Global::new(|| 8080);
+///static BOTH: ZipGlobal<&str, u16> = HOST.zip(&PORT);
+///static ADDR: MappedGlobal<(&&str, &u16), String> = BOTH.map(|(h, p)| format!("{h}:{p}"));
+///
+///assert_eq!(*ADDR, "localhost:8080");
+///```
+pub struct ZipGlobal<A: 'static, B: 'static> {
+    a: &'static dyn Source<A>,
+    b: &'static dyn Source<B>,
+    data: DefaultBackend<(&'static A, &'static B)>,
+}
+
+impl<T, B: OnceBackend<T>> Global<T, B> {
+    ///Derives a global from this one. See [`MappedGlobal`].
+    pub const fn map<U>(&'static self, f: fn(&T) -> U) -> MappedGlobal<T, U> where Self: Sync {
+        MappedGlobal { source: self, f, data: DefaultBackend::EMPTY }
+    }
+
+    ///Pairs this global with another. See [`ZipGlobal`].
+    pub const fn zip<U>(&'static self, other: &'static dyn Source<U>) -> ZipGlobal<T, U> where Self: Sync {
+        ZipGlobal { a: self, b: other, data: DefaultBackend::EMPTY }
+    }
+}
+
+impl<S, T> MappedGlobal<S, T> {
+    ///Initializes the contents of a global, and its source. Does nothing if already initialized.
+    pub fn init(&self) {
+        self.force();
+    }
+
+    ///Retrieves a reference to the value inside the global without initializing it.
+    pub fn get(&self) -> Option<&T> {
+        self.data.get()
+    }
+
+    ///Retrieves a reference to the value inside the global, initializing it and its source if
+    ///needed.
+    pub fn force(&self) -> &T {
+        match self.data.get() {
+            Some(v) => v,
+            None => self.data.get_or_init(|| (self.f)(self.source.value())),
+        }
+    }
+
+    ///Derives a global from this one.
+    pub const fn map<U>(&'static self, f: fn(&T) -> U) -> MappedGlobal<T, U> where Self: Sync {
+        MappedGlobal { source: self, f, data: DefaultBackend::EMPTY }
+    }
+
+    ///Pairs this global with another.
+    pub const fn zip<U>(&'static self, other: &'static dyn Source<U>) -> ZipGlobal<T, U> where Self: Sync {
+        ZipGlobal { a: self, b: other, data: DefaultBackend::EMPTY }
+    }
+}
+
+impl<A, B> ZipGlobal<A, B> {
+    ///Initializes both sources. Does nothing if already initialized.
+    pub fn init(&self) {
+        self.force();
+    }
+
+    ///Retrieves both values, initializing them if needed.
+    pub fn force(&self) -> &(&'static A, &'static B) {
+        match self.data.get() {
+            Some(v) => v,
+            None => self.data.get_or_init(|| (self.a.value(), self.b.value())),
+        }
+    }
+
+    ///Derives a global from both values.
+    pub const fn map<U>(&'static self, f: fn(&(&'static A, &'static B)) -> U) -> MappedGlobal<(&'static A, &'static B), U>
+    where
+        Self: Sync,
+    {
+        MappedGlobal { source: self, f, data: DefaultBackend::EMPTY }
+    }
+}
+
+impl<S, T> Source<T> for MappedGlobal<S, T> where Self: Sync {
+    fn value(&'static self) -> &'static T {
+        self.force()
+    }
+}
+
+impl<A, B> Source<(&'static A, &'static B)> for ZipGlobal<A, B> where Self: Sync {
+    fn value(&'static self) -> &'static (&'static A, &'static B) {
+        self.force()
+    }
+}
+
+#[cfg(not(feature = "no-panic"))]
+impl<S, T> core::ops::Deref for MappedGlobal<S, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.force()
+    }
+}
+
+#[cfg(not(feature = "no-panic"))]
+impl<A, B> core::ops::Deref for ZipGlobal<A, B> {
+    type Target = (&'static A, &'static B);
+
+    fn deref(&self) -> &Self::Target {
+        self.force()
+    }
+}
+
+impl<S, T: Debug> Debug for MappedGlobal<S, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.force())
+    }
+}
+
+impl<A: Debug, B: Debug> Debug for ZipGlobal<A, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.force())
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    fmt::Debug,
+    sync::{Mutex, OnceLock},
+};
+
+use crate::{Error, ErrorKind};
+
+type Init<T> = Box<dyn FnOnce() -> T + Send>;
+
+///Lazily evaluated static with a capturing initializer.
+///
+///[`Global`](crate::Global) only takes a plain `fn() -> T`, so its initializer can't use values
+///computed at runtime. `GlobalDyn` can be declared empty and given a closure later, such as from
+///`main` once configuration has been parsed.
+///```rust
+///# use global_static::GlobalDyn;
+///static GREETING: GlobalDyn<String> = GlobalDyn::empty();
+///
+///fn main() {
+///    let name = std::env::args().count().to_string();
+///    GREETING.set_init(move || format!("hello {name}")).ok();
+///
+///    assert!(GREETING.starts_with("hello"));
+///}
+///```
+pub struct GlobalDyn<T> {
+    init: Mutex<Option<Init<T>>>,
+    data: OnceLock<T>,
+}
+
+impl<T> GlobalDyn<T> {
+    ///Constructs a new global without an initializer. One must be set with
+    ///[`set_init`](GlobalDyn::set_init) before the global is first accessed.
+    pub const fn empty() -> Self {
+        Self { init: Mutex::new(None), data: OnceLock::new() }
+    }
+
+    ///Constructs a new global with a capturing initializer.
+    pub fn new(f: impl FnOnce() -> T + Send + 'static) -> Self {
+        Self { init: Mutex::new(Some(Box::new(f))), data: OnceLock::new() }
+    }
+
+    ///Sets the initializer, replacing any previous one. Returns the initializer back if the
+    ///global has already been initialized.
+    pub fn set_init<F: FnOnce() -> T + Send + 'static>(&self, f: F) -> Result<(), F> {
+        let mut init = self.init.lock().unwrap_or_else(|e| e.into_inner());
+        if self.data.get().is_some() {
+            return Err(f);
+        }
+        *init = Some(Box::new(f));
+        Ok(())
+    }
+
+    ///Initializes the contents of a global. Does nothing if already initialized.
+    ///
+    ///Panics if no initializer has been set.
+    #[cfg(not(feature = "no-panic"))]
+    pub fn init(&self) {
+        self.force();
+    }
+
+    ///Initializes the contents of a global, returning an error if no initializer has been set.
+    ///Does nothing if already initialized.
+    pub fn try_init(&self) -> Result<(), Error> {
+        self.try_get().map(|_| ())
+    }
+
+    ///Retrieves the value, initializing the global if needed. Returns an error if no initializer
+    ///has been set.
+    pub fn try_get(&self) -> Result<&T, Error> {
+        if let Some(v) = self.data.get() {
+            return Ok(v);
+        }
+        //held while initializing so concurrent callers wait for the value instead of finding
+        //the initializer already taken
+        let mut init = self.init.lock().unwrap_or_else(|e| e.into_inner());
+        match init.take() {
+            Some(f) => Ok(self.data.get_or_init(f)),
+            None => self.data.get().ok_or_else(|| {
+                Error::new(std::any::type_name::<T>(), ErrorKind::NoInitializer)
+            }),
+        }
+    }
+
+    ///Retrieves a reference to the value inside the global without initializing it.
+    pub fn get(&self) -> Option<&T> {
+        self.data.get()
+    }
+
+    ///Whether the global holds no initializer. The initializer is consumed when the global is
+    ///initialized, so anything it captured (buffers, file handles) is released right away
+    ///rather than kept for the lifetime of the global.
+    pub fn initializer_dropped(&self) -> bool {
+        self.init.lock().unwrap_or_else(|e| e.into_inner()).is_none()
+    }
+
+    #[cfg(not(feature = "no-panic"))]
+    fn force(&self) -> &T {
+        match self.try_get() {
+            Ok(v) => v,
+            Err(e) => panic!("{e}"),
+        }
+    }
+}
+
+#[cfg(not(feature = "no-panic"))]
+impl<T> std::ops::Deref for GlobalDyn<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.force()
+    }
+}
+
+impl<T: Debug> Debug for GlobalDyn<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.try_get())
+    }
+}
+#[cfg(not(feature = "no-panic"))]
+impl<T: std::fmt::Display> std::fmt::Display for GlobalDyn<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.force())
+    }
+}
@@ -0,0 +1,88 @@
+use std::{
+    fmt::{self, Display},
+    time::Duration,
+};
+
+///Why a global could not be initialized.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    ///The initializer panicked, with the panic message if it was a string.
+    InitPanic(Option<String>),
+    ///Initialization did not finish in time.
+    Timeout(Duration),
+    ///Reading the value from a file or other source failed.
+    Io(std::io::Error),
+    ///The value was read but could not be parsed.
+    Parse(Box<dyn std::error::Error + Send + Sync>),
+    ///An environment variable was missing or not unicode.
+    Env(std::env::VarError),
+    ///The value was produced but rejected by a validator.
+    Validation(String),
+    ///The global was accessed before an initializer was set.
+    NoInitializer,
+}
+
+///Error produced when initializing a global fails.
+///
+///Every error carries the name of the global it came from, which is shown in its [`Display`]
+///output. The cause can be matched on with [`kind`](Error::kind), and underlying errors are
+///available through [`source`](std::error::Error::source).
+#[derive(Debug)]
+pub struct Error {
+    global: &'static str,
+    kind: ErrorKind,
+}
+
+impl Error {
+    ///Constructs a new error for the named global.
+    pub fn new(global: &'static str, kind: ErrorKind) -> Self {
+        Self { global, kind }
+    }
+
+    ///The name of the global that failed. This is the most descriptive name available, such as
+    ///the environment variable or file read, or the type of the global otherwise.
+    pub fn global(&self) -> &'static str {
+        self.global
+    }
+
+    ///What went wrong.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to initialize `{}`: ", self.global)?;
+        match &self.kind {
+            ErrorKind::InitPanic(Some(msg)) => write!(f, "initializer panicked: {msg}"),
+            ErrorKind::InitPanic(None) => write!(f, "initializer panicked"),
+            ErrorKind::Timeout(d) => write!(f, "timed out after {d:?}"),
+            ErrorKind::Io(e) => write!(f, "{e}"),
+            ErrorKind::Parse(e) => write!(f, "could not parse value: {e}"),
+            ErrorKind::Env(e) => write!(f, "{e}"),
+            ErrorKind::Validation(msg) => write!(f, "invalid value: {msg}"),
+            ErrorKind::NoInitializer => write!(f, "accessed before an initializer was set"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Io(e) => Some(e),
+            ErrorKind::Parse(e) => Some(&**e),
+            ErrorKind::Env(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+//the message of a caught panic, for reporting it elsewhere
+#[cfg(any(feature = "observability", feature = "async"))]
+pub(crate) fn message(panic: &(dyn std::any::Any + Send)) -> String {
+    panic.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "initializer panicked".to_owned())
+}
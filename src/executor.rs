@@ -0,0 +1,103 @@
+//!Running initializers on a designated thread.
+//!
+//!Lazy initialization runs on whichever thread first touches a global, which can be a
+//!latency-sensitive one, such as an audio or render thread. Globals built with
+//![`Global::offload`](crate::Global::offload) are instead initialized on the init executor when
+//!they are first accessed from a thread marked with [`latency_sensitive`]. The accessing thread
+//!still blocks until the value is ready, but the work and its stack usage happen elsewhere.
+//!```rust
+//!# use global_static::{Global, executor};
+//!# use std::time::Duration;
+//!fn table() -> Vec<f32> {
+//!    (0..4096).map(|i| i as f32).collect()
+//!}
+//!static TABLE: Global<Vec<f32>> = Global::<Vec<f32>>::new(table).offload();
+//!
+//!executor::spawn_init_thread();
+//!
+//!let _rt = executor::latency_sensitive(Duration::from_millis(100));
+//!assert_eq!(TABLE[1], 1.0);
+//!```
+use std::{
+    cell::Cell,
+    marker::PhantomData,
+    sync::{mpsc, Mutex, RwLock},
+    time::Duration,
+};
+
+use crate::{Error, ErrorKind};
+
+///An initialization to run on the init executor.
+pub type InitJob = Box<dyn FnOnce() + Send>;
+
+static EXECUTOR: RwLock<Option<fn(InitJob)>> = RwLock::new(None);
+static INIT_THREAD: Mutex<Option<mpsc::Sender<InitJob>>> = Mutex::new(None);
+
+thread_local! {
+    static DEADLINE: Cell<Option<Duration>> = const { Cell::new(None) };
+}
+
+///Sets the function used to run offloaded initializations, such as one that submits them to a
+///thread pool. The function must run the job eventually, on any thread other than the caller.
+pub fn set_init_executor(spawn: fn(InitJob)) {
+    *EXECUTOR.write().unwrap_or_else(|e| e.into_inner()) = Some(spawn);
+}
+
+///Starts a dedicated background thread and makes it the init executor. Calling this again
+///does nothing.
+pub fn spawn_init_thread() {
+    let mut sender = INIT_THREAD.lock().unwrap_or_else(|e| e.into_inner());
+    if sender.is_some() {
+        return;
+    }
+    let (tx, rx) = mpsc::channel::<InitJob>();
+    std::thread::Builder::new()
+        .name("global-static-init".to_owned())
+        .spawn(move || rx.into_iter().for_each(|job| job()))
+        .expect("failed to spawn the init thread");
+    *sender = Some(tx);
+    set_init_executor(|job| {
+        if let Some(tx) = &*INIT_THREAD.lock().unwrap_or_else(|e| e.into_inner()) {
+            let _ = tx.send(job);
+        }
+    });
+}
+
+///Marks the current thread as latency-sensitive until the returned guard is dropped.
+///
+///Offloadable globals first accessed on this thread are initialized on the init executor, and
+///the access panics with an [`ErrorKind::Timeout`] error if that takes longer than `deadline`.
+///Without an executor, they are initialized on this thread as usual.
+pub fn latency_sensitive(deadline: Duration) -> LatencyGuard {
+    let previous = DEADLINE.with(|d| d.replace(Some(deadline)));
+    LatencyGuard { previous, _thread: PhantomData }
+}
+
+///Restores the previous state of the thread when dropped. Returned by [`latency_sensitive`].
+#[must_use = "the thread is only marked as latency-sensitive while the guard is alive"]
+pub struct LatencyGuard {
+    previous: Option<Duration>,
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for LatencyGuard {
+    fn drop(&mut self) {
+        DEADLINE.with(|d| d.set(self.previous));
+    }
+}
+
+//runs `f` on the executor if the current thread is latency-sensitive and one is set
+pub(crate) fn run<T: Send + 'static>(f: fn() -> T) -> Option<T> {
+    let deadline = DEADLINE.try_with(Cell::get).ok().flatten()?;
+    let spawn = (*EXECUTOR.read().unwrap_or_else(|e| e.into_inner()))?;
+
+    let (tx, rx) = mpsc::sync_channel(1);
+    spawn(Box::new(move || {
+        let _ = tx.send(std::panic::catch_unwind(f));
+    }));
+    match rx.recv_timeout(deadline) {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(panic)) => std::panic::resume_unwind(panic),
+        Err(_) => panic!("{}", Error::new(std::any::type_name::<T>(), ErrorKind::Timeout(deadline))),
+    }
+}
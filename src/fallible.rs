@@ -0,0 +1,70 @@
+use std::{fmt::Debug, sync::OnceLock};
+
+use crate::Error;
+
+///Lazily evaluated static with a fallible initializer.
+///
+///The initializer runs once. If it fails, the error is stored and returned by every later
+///access instead of being retried.
+///```rust
+///# use global_static::TryGlobal;
+///static PORT: TryGlobal<u16, std::num::ParseIntError> = TryGlobal::new(|| "8080".parse());
+///static BAD: TryGlobal<u16, std::num::ParseIntError> = TryGlobal::new(|| "eighty".parse());
+///
+///assert_eq!(PORT.try_get(), Ok(&8080));
+///assert!(BAD.try_init().is_err());
+///assert_eq!(BAD.get(), None);
+///```
+pub struct TryGlobal<T, E = Error> {
+    f: fn() -> Result<T, E>,
+    data: OnceLock<Result<T, E>>,
+}
+
+impl<T, E> TryGlobal<T, E> {
+    ///Constructs a new global from a function that produces its value or an error.
+    pub const fn new(f: fn() -> Result<T, E>) -> Self {
+        Self { f, data: OnceLock::new() }
+    }
+
+    ///Initializes the contents of a global, returning the error if initialization failed. Does
+    ///nothing if already initialized.
+    pub fn try_init(&self) -> Result<(), &E> {
+        self.try_get().map(|_| ())
+    }
+
+    ///Retrieves the value or the error, initializing the global if needed.
+    pub fn try_get(&self) -> Result<&T, &E> {
+        self.data.get_or_init(self.f).as_ref()
+    }
+
+    ///Retrieves the value without initializing the global. Returns `None` if it has not been
+    ///initialized or initialization failed.
+    pub fn get(&self) -> Option<&T> {
+        self.data.get()?.as_ref().ok()
+    }
+
+    ///Retrieves the error without initializing the global. Returns `None` if it has not been
+    ///initialized or initialization succeeded.
+    pub fn get_err(&self) -> Option<&E> {
+        self.data.get()?.as_ref().err()
+    }
+}
+
+#[cfg(not(feature = "no-panic"))]
+impl<T, E: std::fmt::Display> std::ops::Deref for TryGlobal<T, E> {
+    type Target = T;
+
+    ///Retrieves the value, initializing the global if needed. Panics if initialization failed.
+    fn deref(&self) -> &Self::Target {
+        match self.try_get() {
+            Ok(v) => v,
+            Err(e) => panic!("`TryGlobal<{}>` failed to initialize: {e}", std::any::type_name::<T>()),
+        }
+    }
+}
+
+impl<T: Debug, E: Debug> Debug for TryGlobal<T, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.try_get())
+    }
+}
@@ -0,0 +1,161 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, OnceLock,
+    },
+};
+
+//initialized outside the lock, so initializers for different keys run concurrently
+type Slot<V> = Arc<OnceLock<Arc<V>>>;
+
+struct Entry<V> {
+    slot: Slot<V>,
+    used: u64,
+}
+
+struct State<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    //least recently used first
+    order: BTreeMap<u64, K>,
+    tick: u64,
+}
+
+///Counters describing how a [`GlobalFamily`] has been used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FamilyMetrics {
+    ///Lookups of keys that were already present.
+    pub hits: u64,
+    ///Lookups that had to initialize a new key.
+    pub misses: u64,
+    ///Keys removed to stay within the capacity.
+    pub evictions: u64,
+    ///Number of keys present.
+    pub len: usize,
+    ///The maximum number of keys, if bounded.
+    pub capacity: Option<usize>,
+}
+
+///A family of lazily evaluated globals, one per key.
+///
+///Each key is initialized the first time it is looked up, and later lookups return the same
+///value. Values are reference counted, so a [`capacity`](GlobalFamily::with_capacity) can be set
+///to bound memory use when keys come from user input: once it is reached, the least recently
+///used key is evicted, and its value is freed when the last handle to it is dropped.
+///```rust
+///# use global_static::GlobalFamily;
+///static GREETINGS: GlobalFamily<String, String> =
+///    GlobalFamily::<String, String>::new(|name| format!("hello {name}")).with_capacity(1024);
+///
+///assert_eq!(*GREETINGS.get(&"alice".to_owned()), "hello alice");
+///```
+pub struct GlobalFamily<K, V> {
+    f: fn(&K) -> V,
+    capacity: Option<usize>,
+    data: Mutex<Option<State<K, V>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl<K: Hash + Eq + Clone, V> GlobalFamily<K, V> {
+    ///Constructs a new, unbounded family from a function that produces the value for a key.
+    pub const fn new(f: fn(&K) -> V) -> Self {
+        Self {
+            f,
+            capacity: None,
+            data: Mutex::new(None),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    ///Bounds the number of keys, evicting the least recently used key when a new one would
+    ///exceed it. A capacity of zero is treated as one.
+    pub const fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(if capacity == 0 { 1 } else { capacity });
+        self
+    }
+
+    ///Retrieves the value for a key, initializing it if needed.
+    pub fn get(&self, key: &K) -> Arc<V> {
+        let slot = {
+            let mut lock = self.lock();
+            let state = lock.get_or_insert_with(|| {
+                State { entries: HashMap::new(), order: BTreeMap::new(), tick: 0 }
+            });
+            state.tick += 1;
+            let tick = state.tick;
+            match state.entries.get_mut(key) {
+                Some(entry) => {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    state.order.remove(&entry.used);
+                    state.order.insert(tick, key.clone());
+                    entry.used = tick;
+                    entry.slot.clone()
+                },
+                None => {
+                    self.misses.fetch_add(1, Ordering::Relaxed);
+                    if self.capacity.is_some_and(|c| state.entries.len() >= c) {
+                        if let Some((_, oldest)) = state.order.pop_first() {
+                            state.entries.remove(&oldest);
+                            self.evictions.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                    let slot = Slot::default();
+                    state.order.insert(tick, key.clone());
+                    state.entries.insert(key.clone(), Entry { slot: slot.clone(), used: tick });
+                    slot
+                },
+            }
+        };
+        slot.get_or_init(|| Arc::new((self.f)(key))).clone()
+    }
+
+    ///Retrieves the value for a key without initializing it or marking it as used.
+    pub fn peek(&self, key: &K) -> Option<Arc<V>> {
+        self.lock().as_ref()?.entries.get(key)?.slot.get().cloned()
+    }
+
+    ///Whether the key is present.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.lock().as_ref().is_some_and(|s| s.entries.contains_key(key))
+    }
+
+    ///Removes a key, returning its value if it was initialized. Handles to the value stay
+    ///valid.
+    pub fn remove(&self, key: &K) -> Option<Arc<V>> {
+        let mut lock = self.lock();
+        let state = lock.as_mut()?;
+        let entry = state.entries.remove(key)?;
+        state.order.remove(&entry.used);
+        entry.slot.get().cloned()
+    }
+
+    ///Number of keys present.
+    pub fn len(&self) -> usize {
+        self.lock().as_ref().map_or(0, |s| s.entries.len())
+    }
+
+    ///Whether no keys are present.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///Counters for this family since it was created.
+    pub fn metrics(&self) -> FamilyMetrics {
+        FamilyMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            len: self.len(),
+            capacity: self.capacity,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<State<K, V>>> {
+        self.data.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
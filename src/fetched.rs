@@ -0,0 +1,94 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+struct Entry<T> {
+    value: Arc<T>,
+    fetched_at: Instant,
+}
+
+///Lazily fetched static that refreshes itself in the background once it goes stale.
+///
+///The first access fetches the value and blocks. Afterwards, accesses always return the current
+///value immediately; if it is older than the TTL, a background thread is spawned to fetch a new
+///one (stale-while-revalidate). This fits tokens, key sets, and feature flags fetched from
+///elsewhere.
+///```rust
+///# use global_static::GlobalFetched;
+///# use std::time::Duration;
+///fn fetch_flags() -> Vec<String> { vec!["new-ui".to_owned()] }
+///
+///static FLAGS: GlobalFetched<Vec<String>> = GlobalFetched::new(fetch_flags, Duration::from_secs(60));
+///
+///assert_eq!(FLAGS.get()[0], "new-ui");
+///assert!(!FLAGS.is_stale());
+///```
+pub struct GlobalFetched<T> {
+    fetch: fn() -> T,
+    ttl: Duration,
+    data: RwLock<Option<Entry<T>>>,
+    refreshing: AtomicBool,
+}
+
+struct Refreshing<'a>(&'a AtomicBool);
+impl Drop for Refreshing<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+impl<T> GlobalFetched<T> {
+    ///Constructs a new global from a fetching function and the duration fetched values stay
+    ///fresh for.
+    pub const fn new(fetch: fn() -> T, ttl: Duration) -> Self {
+        Self { fetch, ttl, data: RwLock::new(None), refreshing: AtomicBool::new(false) }
+    }
+
+    ///Time since the current value was fetched, or `None` if it has not been fetched yet.
+    pub fn age(&self) -> Option<Duration> {
+        self.read(|e| e.fetched_at.elapsed())
+    }
+
+    ///Whether the current value is older than the TTL. An unfetched global is not stale.
+    pub fn is_stale(&self) -> bool {
+        self.age().is_some_and(|age| age > self.ttl)
+    }
+
+    ///Whether a background refresh is currently running.
+    pub fn is_refreshing(&self) -> bool {
+        self.refreshing.load(Ordering::Acquire)
+    }
+
+    ///Fetches a new value now, blocking until it is available.
+    pub fn refresh(&self) -> Arc<T> {
+        let value = Arc::new((self.fetch)());
+        let entry = Entry { value: value.clone(), fetched_at: Instant::now() };
+        *self.data.write().unwrap_or_else(|e| e.into_inner()) = Some(entry);
+        value
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&Entry<T>) -> R) -> Option<R> {
+        self.data.read().unwrap_or_else(|e| e.into_inner()).as_ref().map(f)
+    }
+}
+
+impl<T: Send + Sync + 'static> GlobalFetched<T> {
+    ///Returns the current value. Fetches and blocks if there is none yet, and starts a
+    ///background refresh if the value is stale.
+    pub fn get(&'static self) -> Arc<T> {
+        let Some((value, stale)) = self.read(|e| (e.value.clone(), e.fetched_at.elapsed() > self.ttl)) else {
+            return self.refresh();
+        };
+        if stale && !self.refreshing.swap(true, Ordering::AcqRel) {
+            std::thread::spawn(move || {
+                let _guard = Refreshing(&self.refreshing);
+                self.refresh();
+            });
+        }
+        value
+    }
+}
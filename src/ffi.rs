@@ -0,0 +1,104 @@
+use crate::{Global, OnceBackend};
+
+///Marker for types with a layout that C code can rely on.
+///
+///This is required by [`Global::freeze_layout`] and [`export_global!`](crate::export_global), so
+///that exporting a type with an unspecified Rust layout fails at compile time instead of handing
+///C callers an unusable pointer. With the `singleton` feature, it can be derived for `#[repr(C)]`
+///and `#[repr(transparent)]` types.
+///```rust,compile_fail
+///# use global_static::{Global, export_global};
+///struct Opaque(u32); //not repr(C)
+///static OPAQUE: Global<Opaque> = Global::new(|| Opaque(1));
+///export_global! { opaque_get: Opaque = OPAQUE; }
+///```
+///
+///# Safety
+///The type must be `#[repr(C)]`, `#[repr(transparent)]`, or a primitive with a C equivalent, and
+///all of its fields must be `FfiSafe` as well.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not have a C-compatible layout",
+    note = "add `#[repr(C)]` to the type and implement or derive `FfiSafe` for it"
+)]
+pub unsafe trait FfiSafe {}
+
+macro_rules! impl_ffi_safe {
+    ($($ty:ty),*) => { $(unsafe impl FfiSafe for $ty {})* };
+}
+
+impl_ffi_safe!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64, bool, ());
+
+unsafe impl<T> FfiSafe for *const T {}
+unsafe impl<T> FfiSafe for *mut T {}
+unsafe impl<T: FfiSafe, const N: usize> FfiSafe for [T; N] {}
+
+impl<T: FfiSafe, B: OnceBackend<T>> Global<T, B> {
+    ///Initializes the global and returns a pointer to its value for use across FFI. The pointer
+    ///stays valid for as long as the global does, so it is valid forever for statics.
+    ///```rust
+    ///# use global_static::Global;
+    ///static LIMIT: Global<u32> = Global::new(|| 64);
+    ///let ptr = LIMIT.freeze_layout();
+    ///assert_eq!(unsafe { *ptr }, 64);
+    ///```
+    pub fn freeze_layout(&self) -> *const T {
+        self.force() as *const T
+    }
+}
+
+#[macro_export]
+///Generate `extern "C"` accessors for globals. Each accessor initializes the global and returns a
+///pointer to it. The type must implement [`FfiSafe`](crate::FfiSafe).
+///
+///```rust
+///# use global_static::{Global, export_global};
+///#[repr(C)]
+///pub struct Limits { max: u32 }
+///unsafe impl global_static::FfiSafe for Limits {}
+///
+///static LIMITS: Global<Limits> = Global::new(|| Limits { max: 8 });
+///
+///export_global! {
+///    limits_get: Limits = LIMITS;
+///}
+///```
+///This generates:
+///```rust,ignore
+///#[no_mangle]
+///pub extern "C" fn limits_get() -> *const Limits {
+///    LIMITS.freeze_layout()
+///}
+///```
+///
+///With a `header` line first, a function returning a C header that declares the accessors is
+///generated too, so embedding applications don't have to maintain the prototypes by hand. The
+///types must implement [`CType`](crate::CType) as well, which can be derived with the
+///`singleton` feature. Writing the header from a test that fails when it changes keeps the
+///checked-in copy up to date. See [`CType`](crate::CType) for a full example.
+///```rust,ignore
+///export_global! {
+///    header limits_header = "LIMITS_H";
+///    limits_get: Limits = LIMITS;
+///}
+///
+///std::fs::write("include/limits.h", limits_header()).unwrap();
+///```
+macro_rules! export_global {
+    () => {};
+    (header $header:ident = $guard:literal; $($fn_name:ident: $type:ty = $global:path;)*) => {
+        ///Returns a C header declaring the accessors generated by `export_global!`.
+        pub fn $header() -> ::std::string::String {
+            $crate::CHeader::new($guard)
+                $(.accessor::<$type>(stringify!($fn_name)))*
+                .render()
+        }
+        $crate::export_global!($($fn_name: $type = $global;)*);
+    };
+    ($fn_name:ident: $type:ty = $global:path; $($tail:tt)*) => {
+        #[no_mangle]
+        pub extern "C" fn $fn_name() -> *const $type {
+            $global.freeze_layout()
+        }
+        $crate::export_global!($($tail)*);
+    };
+}
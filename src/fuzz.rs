@@ -0,0 +1,227 @@
+use std::{
+    fmt::Display,
+    io::{BufRead, BufReader, Write},
+    panic::AssertUnwindSafe,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use crate::{error, registered};
+
+//set in the child processes, to the seed of the order they should use
+const SEED_VAR: &str = "GLOBAL_STATIC_FUZZ_SEED";
+const ORDER_LINE: &str = "global-static-fuzz-order: ";
+const PANIC_LINE: &str = "global-static-fuzz-panic: ";
+
+///Soak test that initializes every registered global in random orders, to flush out hidden
+///dependencies between them, such as an initializer that assumes another global is already
+///initialized, or two initializers that deadlock when run at the same time.
+///
+///Globals can only be initialized once per process, so each iteration re-runs the calling test
+///in a new process, which shuffles the registered globals and initializes them from several
+///threads. [`run`](InitOrderFuzz::run) reports the first order that panicked or didn't finish
+///in time. Globals initialized by generated ctors are initialized before the order can be
+///chosen, unless the `deterministic` feature is enabled, so this is most useful together with
+///it, or with globals registered by the test itself.
+///```rust,no_run
+///# use global_static::{Global, InitOrderFuzz, register};
+///static PORT: Global<u16> = Global::new(|| 8080);
+///static ADDR: Global<String> = Global::new(|| format!("0.0.0.0:{}", PORT.get().unwrap()));
+///
+///#[test]
+///fn init_order() {
+///    register(&PORT);
+///    register(&ADDR);
+///    if let Err(failure) = InitOrderFuzz::new("init_order").iterations(20).run() {
+///        panic!("{failure}");
+///    }
+///}
+///```
+#[derive(Debug, Clone, Copy)]
+pub struct InitOrderFuzz {
+    test: &'static str,
+    iterations: usize,
+    threads: usize,
+    timeout: Duration,
+    seed: Option<u64>,
+}
+
+impl InitOrderFuzz {
+    ///Constructs a new fuzzer, run from the test named `test`, with the path the test harness
+    ///shows for it, such as `tests::init_order`.
+    pub const fn new(test: &'static str) -> Self {
+        Self { test, iterations: 10, threads: 4, timeout: Duration::from_secs(10), seed: None }
+    }
+
+    ///Sets how many orders are tried. Defaults to 10.
+    pub const fn iterations(mut self, n: usize) -> Self {
+        self.iterations = n;
+        self
+    }
+
+    ///Sets how many threads initialize the globals in each order. Defaults to 4.
+    pub const fn threads(mut self, n: usize) -> Self {
+        self.threads = if n == 0 { 1 } else { n };
+        self
+    }
+
+    ///Sets how long an order may take before it is reported as a deadlock. Defaults to 10
+    ///seconds.
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    ///Sets the seed the orders are derived from, to reproduce a failure. Defaults to
+    ///[`seed`](crate::seed).
+    pub const fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    ///Tries every order, returning the first one that failed.
+    ///
+    ///In the processes started for each order, this initializes the globals instead and exits
+    ///the process.
+    pub fn run(&self) -> Result<(), OrderFailure> {
+        if let Some(seed) = std::env::var(SEED_VAR).ok().and_then(|s| s.parse().ok()) {
+            self.run_child(seed);
+        }
+        let base = self.seed.unwrap_or_else(crate::seed);
+        for i in 0..self.iterations as u64 {
+            self.run_parent(base.wrapping_add(i))?;
+        }
+        Ok(())
+    }
+
+    fn run_child(&self, seed: u64) -> ! {
+        let mut globals = registered();
+        shuffle(&mut globals, seed);
+        let mut out = std::io::stdout().lock();
+        for global in &globals {
+            let _ = writeln!(out, "{ORDER_LINE}{}", global.type_name());
+        }
+        let _ = out.flush();
+        drop(out);
+
+        let threads = self.threads.min(globals.len()).max(1);
+        let panic = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..threads).map(|offset| {
+                let globals = &globals;
+                s.spawn(move || {
+                    globals.iter().skip(offset).step_by(threads).find_map(|g| {
+                        std::panic::catch_unwind(AssertUnwindSafe(|| g.init())).err().map(|p| error::message(&*p))
+                    })
+                })
+            }).collect();
+            handles.into_iter().find_map(|h| h.join().ok().flatten())
+        });
+        match panic {
+            Some(msg) => {
+                println!("{PANIC_LINE}{}", msg.replace('\n', " "));
+                std::process::exit(101);
+            },
+            None => std::process::exit(0),
+        }
+    }
+
+    fn run_parent(&self, seed: u64) -> Result<(), OrderFailure> {
+        let fail = |order, kind| OrderFailure { seed, threads: self.threads, order, kind };
+        let spawned = std::env::current_exe().and_then(|exe| {
+            Command::new(exe)
+                .args([self.test, "--exact", "--nocapture", "--test-threads=1"])
+                .env(SEED_VAR, seed.to_string())
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+        });
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => return Err(fail(Vec::new(), OrderFailureKind::Spawn(e.to_string()))),
+        };
+        //read on another thread, so a child blocked on a full pipe isn't taken for a deadlock
+        let stdout = child.stdout.take();
+        let reader = std::thread::spawn(move || {
+            stdout.map_or_else(Vec::new, |s| BufReader::new(s).lines().map_while(Result::ok).collect::<Vec<_>>())
+        });
+
+        let start = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Some(status),
+                Ok(None) if start.elapsed() < self.timeout => std::thread::sleep(Duration::from_millis(5)),
+                _ => break None,
+            }
+        };
+        if status.is_none() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        let lines = reader.join().unwrap_or_default();
+        //the harness may have printed the test's name on the same line
+        let order = lines.iter().filter_map(|l| l.split_once(ORDER_LINE)).map(|(_, ty)| ty.to_owned()).collect();
+        let panic = lines.iter().find_map(|l| l.split_once(PANIC_LINE)).map(|(_, msg)| msg);
+        match (status, panic) {
+            (None, _) => Err(fail(order, OrderFailureKind::Deadlock)),
+            (Some(_), Some(msg)) => Err(fail(order, OrderFailureKind::Panic(msg.to_owned()))),
+            (Some(status), None) if !status.success() => {
+                Err(fail(order, OrderFailureKind::Panic(format!("the test process failed with {status}"))))
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+//splitmix64, which is plenty for picking orders
+fn shuffle<T>(items: &mut [T], mut seed: u64) {
+    let mut next = || {
+        seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    for i in (1..items.len()).rev() {
+        items.swap(i, (next() % (i as u64 + 1)) as usize);
+    }
+}
+
+///How an initialization order failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderFailureKind {
+    ///An initializer panicked, with its message.
+    Panic(String),
+    ///The globals weren't initialized before the timeout.
+    Deadlock,
+    ///The test process couldn't be started.
+    Spawn(String),
+}
+
+///An initialization order that failed, as reported by [`InitOrderFuzz::run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderFailure {
+    ///The seed of the order, which reproduces it when given to
+    ///[`seed`](InitOrderFuzz::seed) with `iterations(1)`.
+    pub seed: u64,
+    ///How many threads initialized the globals. Thread `i` initialized every global whose
+    ///position in the order is `i` modulo this.
+    pub threads: usize,
+    ///The types of the globals, in the order they were initialized in.
+    pub order: Vec<String>,
+    ///What went wrong.
+    pub kind: OrderFailureKind,
+}
+
+impl Display for OrderFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            OrderFailureKind::Panic(msg) => write!(f, "initializing globals panicked with `{msg}`")?,
+            OrderFailureKind::Deadlock => f.write_str("initializing globals didn't finish in time")?,
+            OrderFailureKind::Spawn(e) => return write!(f, "couldn't start the test process: {e}"),
+        }
+        write!(f, " (seed {}, {} threads), in the order: {}", self.seed, self.threads, self.order.join(", "))
+    }
+}
+
+impl std::error::Error for OrderFailure {}
@@ -0,0 +1,189 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    fs::File,
+    io::{self, Read},
+    path::PathBuf,
+    process::Command,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex, OnceLock, PoisonError,
+    },
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Error, ErrorKind};
+
+///The environment variable the state handed off by the previous process is passed in. It holds
+///either the path of a file, which is deleted once read, or `fd:N` for a file descriptor the
+///process inherited, such as a memfd passed by a supervisor. The variable is removed once read,
+///so processes started afterwards don't inherit it.
+pub const HANDOFF_VAR: &str = "GLOBAL_STATIC_HANDOFF";
+
+//read when the first handoff global is initialized after the variable is set, with entries
+//removed as they are used so the encoded values don't stay around
+static INHERITED: Mutex<Option<HashMap<String, Vec<u8>>>> = Mutex::new(None);
+
+fn take_inherited(key: &str) -> Option<Vec<u8>> {
+    let mut inherited = INHERITED.lock().unwrap_or_else(PoisonError::into_inner);
+    if inherited.is_none() {
+        let var = std::env::var_os(HANDOFF_VAR)?;
+        //so processes started later don't read a deleted file or someone else's descriptor
+        std::env::remove_var(HANDOFF_VAR);
+        //an unreadable handoff just means starting cold
+        *inherited = Some(read_handoff(var.into()).unwrap_or_default());
+    }
+    inherited.as_mut()?.remove(key)
+}
+
+fn read_handoff(var: PathBuf) -> io::Result<HashMap<String, Vec<u8>>> {
+    let mut bytes = Vec::new();
+    match var.to_str().and_then(|v| v.strip_prefix("fd:")) {
+        #[cfg(unix)]
+        Some(fd) => {
+            use std::{io::Seek, os::fd::FromRawFd};
+            let fd = fd.parse().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            //safety: the supervisor passed the descriptor for this process to read, and nothing
+            //else uses it
+            let mut file = unsafe { File::from_raw_fd(fd) };
+            //a memfd is usually left at the end by whoever wrote to it
+            let _ = file.rewind();
+            file.read_to_end(&mut bytes)?;
+        },
+        #[cfg(not(unix))]
+        Some(_) => return Err(io::ErrorKind::Unsupported.into()),
+        None => {
+            File::open(&var)?.read_to_end(&mut bytes)?;
+            let _ = std::fs::remove_file(&var);
+        },
+    }
+    bincode::deserialize(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+///Lazily evaluated static that can inherit its value from the process that started this one.
+///
+///For zero-downtime restarts, the old process collects the values of its handoff globals in a
+///[`Handoff`] and passes them to the new one, which reads them back on first access instead of
+///running the initializers, so it starts with warm caches and state. The state is read from the
+///location in the [`HANDOFF_VAR`] environment variable. Values are matched by the global's name,
+///the given version, and the type of the value, so bumping the version makes the new process
+///start cold. Like `GlobalCached`, inheriting is best effort: if
+///no value was handed off or it can't be decoded, the initializer runs as usual.
+///```rust
+///# use global_static::{GlobalHandoff, Handoff};
+///static ROUTES: GlobalHandoff<Vec<String>> = GlobalHandoff::new("routes", "1", || vec!["/".to_owned()]);
+///
+///fn restart() -> std::io::Result<()> {
+///    let mut next = std::process::Command::new(std::env::current_exe()?);
+///    Handoff::new().add(&ROUTES).unwrap().pass_to(&mut next)?;
+///    next.spawn()?;
+///    Ok(())
+///}
+///```
+pub struct GlobalHandoff<T> {
+    name: &'static str,
+    version: &'static str,
+    f: fn() -> T,
+    data: OnceLock<T>,
+    inherited: AtomicBool,
+}
+
+impl<T: Serialize + DeserializeOwned> GlobalHandoff<T> {
+    ///Constructs a new global that can be handed off. `name` must be unique among handoff
+    ///globals, and `version` should change whenever the value's encoding does.
+    pub const fn new(name: &'static str, version: &'static str, f: fn() -> T) -> Self {
+        Self { name, version, f, data: OnceLock::new(), inherited: AtomicBool::new(false) }
+    }
+
+    ///Initializes the contents of a global. Does nothing if already initialized.
+    pub fn init(&self) {
+        self.force();
+    }
+
+    ///Retrieves a reference to the value inside the global without initializing it.
+    pub fn get(&self) -> Option<&T> {
+        self.data.get()
+    }
+
+    ///Retrieves a reference to the value inside the global, initializing it if needed.
+    pub fn force(&self) -> &T {
+        self.data.get_or_init(|| {
+            if let Some(value) = take_inherited(&self.key()).and_then(|b| bincode::deserialize(&b).ok()) {
+                self.inherited.store(true, Ordering::Relaxed);
+                return value;
+            }
+            (self.f)()
+        })
+    }
+
+    ///Whether the value was handed off by the previous process rather than produced by the
+    ///initializer.
+    pub fn inherited(&self) -> bool {
+        self.inherited.load(Ordering::Relaxed)
+    }
+
+    fn key(&self) -> String {
+        format!("{}\0{}\0{}", self.name, self.version, std::any::type_name::<T>())
+    }
+}
+
+#[cfg(not(feature = "no-panic"))]
+impl<T: Serialize + DeserializeOwned> std::ops::Deref for GlobalHandoff<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.force()
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + Debug> Debug for GlobalHandoff<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.force())
+    }
+}
+
+///The values of [`GlobalHandoff`]s, collected to be passed to the next process.
+#[derive(Debug, Default)]
+pub struct Handoff {
+    entries: HashMap<String, Vec<u8>>,
+}
+
+impl Handoff {
+    ///Constructs a new, empty handoff.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Adds the value of `global`, if it is initialized, so the next process doesn't have to
+    ///produce it again. Returns an error if the value can't be serialized.
+    pub fn add<T: Serialize + DeserializeOwned>(&mut self, global: &GlobalHandoff<T>) -> Result<&mut Self, Error> {
+        if let Some(value) = global.get() {
+            let bytes = bincode::serialize(value).map_err(|e| Error::new(global.name, ErrorKind::Parse(e)))?;
+            self.entries.insert(global.key(), bytes);
+        }
+        Ok(self)
+    }
+
+    ///Encodes the handoff, for supervisors that pass it on themselves, such as by writing it to
+    ///a memfd and setting [`HANDOFF_VAR`] to `fd:N` in the new process.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        //encoding a map of strings and bytes can't fail
+        bincode::serialize(&self.entries).unwrap_or_default()
+    }
+
+    ///Writes the handoff to a private file in the temporary directory, and sets [`HANDOFF_VAR`]
+    ///on `cmd` so the process it starts reads it. The new process deletes the file once read.
+    pub fn pass_to(&self, cmd: &mut Command) -> io::Result<()> {
+        static PASSED: AtomicUsize = AtomicUsize::new(0);
+        let n = PASSED.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("global-static-handoff-{}-{n}", std::process::id()));
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        io::Write::write_all(&mut options.open(&path)?, &self.to_bytes())?;
+        cmd.env(HANDOFF_VAR, path);
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt::Write;
+
+use crate::FfiSafe;
+
+///A type that can be declared in a C header generated by
+///[`export_global!`](crate::export_global). With the `singleton` feature, it can be derived for
+///`#[repr(C)]` structs, unions and enums, and `#[repr(transparent)]` types, along with
+///[`FfiSafe`].
+///
+///Structs and unions are declared as `struct Name` and `union Name`, so they can point to
+///themselves. Enums with a `#[repr(C)]` layout become a C `enum`, and ones with an integer
+///representation become a `typedef` of that integer, since a C `enum` may be larger.
+///```rust
+///# use global_static::{Global, export_global, CType, FfiSafe};
+///#[repr(C)]
+///pub struct Limits { max: u32, ports: [u16; 2] }
+///unsafe impl FfiSafe for Limits {}
+///impl CType for Limits {
+///    fn c_decl(declarator: &str) -> String {
+///        format!("struct Limits {declarator}")
+///    }
+///    fn c_define(defs: &mut global_static::CDefinitions) {
+///        if defs.start("Limits") {
+///            defs.add(format!("struct Limits {{\n    {};\n    {};\n}};", u32::c_decl("max"), <[u16; 2]>::c_decl("ports")));
+///        }
+///    }
+///}
+///
+///static LIMITS: Global<Limits> = Global::new(|| Limits { max: 8, ports: [80, 443] });
+///
+///export_global! {
+///    header limits_header = "LIMITS_H";
+///    limits_get: Limits = LIMITS;
+///}
+///
+///let header = limits_header();
+///assert!(header.contains("struct Limits {\n    uint32_t max;\n    uint16_t ports[2];\n};"));
+///assert!(header.contains("struct Limits const *limits_get(void);"));
+///```
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be declared in a C header",
+    note = "derive `CType` for the type along with `FfiSafe`"
+)]
+pub trait CType: FfiSafe {
+    ///Declares `declarator` as a value of this type, such as `uint32_t max` for `u32` and `max`.
+    fn c_decl(declarator: &str) -> String;
+
+    ///Adds the definitions this type needs to `defs`, after those of the types it uses. Does
+    ///nothing by default, for types built into C.
+    fn c_define(defs: &mut CDefinitions) {
+        let _ = defs;
+    }
+}
+
+macro_rules! impl_c_type {
+    ($($ty:ty => $c:literal),*) => {$(
+        impl CType for $ty {
+            fn c_decl(declarator: &str) -> String {
+                format!(concat!($c, " {}"), declarator)
+            }
+        }
+    )*};
+}
+
+impl_c_type!(
+    u8 => "uint8_t", u16 => "uint16_t", u32 => "uint32_t", u64 => "uint64_t", usize => "uintptr_t",
+    i8 => "int8_t", i16 => "int16_t", i32 => "int32_t", i64 => "int64_t", isize => "intptr_t",
+    f32 => "float", f64 => "double", bool => "bool", () => "void"
+);
+
+//the qualifier goes after the pointee, as in `uint8_t const *p`, so it composes with nested
+//pointers and arrays
+impl<T: CType> CType for *const T {
+    fn c_decl(declarator: &str) -> String {
+        T::c_decl(&format!("const *{declarator}"))
+    }
+    fn c_define(defs: &mut CDefinitions) {
+        T::c_define(defs)
+    }
+}
+
+impl<T: CType> CType for *mut T {
+    fn c_decl(declarator: &str) -> String {
+        T::c_decl(&format!("*{declarator}"))
+    }
+    fn c_define(defs: &mut CDefinitions) {
+        T::c_define(defs)
+    }
+}
+
+impl<T: CType, const N: usize> CType for [T; N] {
+    fn c_decl(declarator: &str) -> String {
+        let (qualifier, rest) = match declarator.strip_prefix("const ") {
+            Some(rest) => ("const ", rest),
+            None => ("", declarator),
+        };
+        if rest.starts_with('*') {
+            T::c_decl(&format!("{qualifier}({rest})[{N}]"))
+        } else {
+            T::c_decl(&format!("{qualifier}{rest}[{N}]"))
+        }
+    }
+    fn c_define(defs: &mut CDefinitions) {
+        T::c_define(defs)
+    }
+}
+
+///Definitions of the types used by a C header, in the order they have to appear in.
+#[derive(Debug, Default)]
+pub struct CDefinitions {
+    started: Vec<&'static str>,
+    defs: Vec<String>,
+}
+
+impl CDefinitions {
+    ///Marks the type `name` as being defined. Returns `false` if it already is, so types used
+    ///in several places, or that point to themselves, are only defined once.
+    pub fn start(&mut self, name: &'static str) -> bool {
+        if self.started.contains(&name) {
+            return false;
+        }
+        self.started.push(name);
+        true
+    }
+
+    ///Adds a definition, after those added so far.
+    pub fn add(&mut self, def: String) {
+        self.defs.push(def);
+    }
+}
+
+///Builder for a C header declaring exported globals, as generated by
+///[`export_global!`](crate::export_global).
+///```rust
+///# use global_static::CHeader;
+///let header = CHeader::new("LIMITS_H").accessor::<[u16; 4]>("ports_get").render();
+///assert!(header.contains("uint16_t const (*ports_get(void))[4];"));
+///```
+#[derive(Debug)]
+pub struct CHeader {
+    guard: String,
+    defs: CDefinitions,
+    accessors: Vec<String>,
+}
+
+impl CHeader {
+    ///Constructs a new header with the include guard `guard`.
+    pub fn new(guard: &str) -> Self {
+        Self { guard: guard.to_owned(), defs: CDefinitions::default(), accessors: Vec::new() }
+    }
+
+    ///Declares an accessor generated by `export_global!`, which returns a pointer to a `T`.
+    pub fn accessor<T: CType>(mut self, name: &str) -> Self {
+        T::c_define(&mut self.defs);
+        self.accessors.push(T::c_decl(&format!("const *{name}(void)")));
+        self
+    }
+
+    ///Renders the header.
+    pub fn render(&self) -> String {
+        let guard = &self.guard;
+        let mut out = format!("/* generated by global-static, do not edit */\n#ifndef {guard}\n#define {guard}\n\n");
+        out += "#include <stdbool.h>\n#include <stdint.h>\n\n#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n";
+        for def in &self.defs.defs {
+            let _ = write!(out, "{def}\n\n");
+        }
+        for accessor in &self.accessors {
+            let _ = writeln!(out, "{accessor};");
+        }
+        out += "\n#ifdef __cplusplus\n}\n#endif\n\n";
+        let _ = writeln!(out, "#endif /* {guard} */");
+        out
+    }
+}
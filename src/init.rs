@@ -0,0 +1,382 @@
+#[cfg(feature = "observability")]
+use std::{sync::Mutex, time::Instant};
+
+#[cfg(feature = "observability")]
+use crate::startup;
+use crate::{Global, GlobalArc, GlobalDyn, GlobalMap, GlobalMut, GlobalOpt, MappedGlobal, OnceBackend, SwapGlobal, TryGlobal};
+
+///A global that can be initialized without knowing its type, so that it can be part of
+///[`init_all`].
+pub trait AnyGlobal: Sync {
+    ///Initializes the global. Does nothing if already initialized.
+    fn init(&self);
+    ///Whether the global has been initialized.
+    fn is_initialized(&self) -> bool;
+    ///The type of the value inside the global.
+    fn type_name(&self) -> &'static str;
+    ///The memory the value is expected to take once initialized, or 0 if it already is. Only
+    ///[`Global`] reports it, for [`forecast`](crate::forecast).
+    fn uninit_size(&self) -> usize {
+        0
+    }
+    ///Drops the value inside the global, if the global supports it. Only [`Global`] does.
+    ///
+    ///# Safety
+    ///No references to the value may be alive, and the global must not be accessed or
+    ///deinitialized again afterwards.
+    unsafe fn deinit(&'static self) {}
+}
+
+impl<T, B: OnceBackend<T> + Sync> AnyGlobal for Global<T, B> {
+    fn init(&self) {
+        Global::init_unchecked(self)
+    }
+    unsafe fn deinit(&'static self) {
+        Global::deinit(self)
+    }
+    fn is_initialized(&self) -> bool {
+        self.peek().is_some()
+    }
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+    fn uninit_size(&self) -> usize {
+        Global::uninit_size(self)
+    }
+}
+
+impl<T: Send + Sync> AnyGlobal for GlobalOpt<T> {
+    fn init(&self) {
+        GlobalOpt::init(self)
+    }
+    fn is_initialized(&self) -> bool {
+        GlobalOpt::is_initialized(self)
+    }
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
+
+impl<S, T: Send + Sync> AnyGlobal for MappedGlobal<S, T> {
+    fn init(&self) {
+        MappedGlobal::init(self)
+    }
+    fn is_initialized(&self) -> bool {
+        self.get().is_some()
+    }
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
+
+impl<T: Send + Sync> AnyGlobal for GlobalArc<T> {
+    fn init(&self) {
+        GlobalArc::init(self)
+    }
+    fn is_initialized(&self) -> bool {
+        self.get().is_some()
+    }
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
+
+impl<T: Send + Sync> AnyGlobal for GlobalDyn<T> {
+    fn init(&self) {
+        #[cfg(not(feature = "no-panic"))]
+        GlobalDyn::init(self);
+        #[cfg(feature = "no-panic")]
+        let _ = self.try_init();
+    }
+    fn is_initialized(&self) -> bool {
+        self.get().is_some()
+    }
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
+
+impl<T: Send + Sync, E: Send + Sync> AnyGlobal for TryGlobal<T, E> {
+    fn init(&self) {
+        let _ = self.try_init();
+    }
+    fn is_initialized(&self) -> bool {
+        self.get().is_some() || self.get_err().is_some()
+    }
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
+
+impl<T: Send + Sync> AnyGlobal for GlobalMut<T> {
+    fn init(&self) {
+        GlobalMut::init(self)
+    }
+    fn is_initialized(&self) -> bool {
+        GlobalMut::is_initialized(self)
+    }
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
+
+impl<T: Send + Sync> AnyGlobal for SwapGlobal<T> {
+    fn init(&self) {
+        SwapGlobal::init(self)
+    }
+    fn is_initialized(&self) -> bool {
+        SwapGlobal::is_initialized(self)
+    }
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
+
+impl<K: Send + Sync, V: Send + Sync> AnyGlobal for GlobalMap<K, V> {
+    fn init(&self) {
+        GlobalMap::init(self)
+    }
+    fn is_initialized(&self) -> bool {
+        GlobalMap::is_initialized(self)
+    }
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<GlobalMap<K, V>>()
+    }
+}
+
+#[cfg(feature = "init-cache")]
+impl<T> AnyGlobal for crate::GlobalCached<T>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + Send + Sync,
+{
+    fn init(&self) {
+        crate::GlobalCached::init(self)
+    }
+    fn is_initialized(&self) -> bool {
+        self.get().is_some()
+    }
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
+
+#[cfg(feature = "observability")]
+static REGISTERED: Mutex<Vec<&'static dyn AnyGlobal>> = Mutex::new(Vec::new());
+
+///Registers a global to be initialized by [`init_all`]. Registering the same global twice does
+///nothing.
+#[cfg_attr(docsrs, doc(cfg(feature = "observability")))]
+#[cfg(feature = "observability")]
+pub fn register(global: &'static dyn AnyGlobal) {
+    let mut registered = REGISTERED.lock().unwrap_or_else(|e| e.into_inner());
+    //compare addresses only, vtables for the same type can differ between codegen units
+    let addr = global as *const dyn AnyGlobal as *const ();
+    if !registered.iter().any(|g| std::ptr::eq(*g as *const dyn AnyGlobal as *const (), addr)) {
+        registered.push(global);
+    }
+}
+
+///All registered globals, in registration order.
+#[cfg_attr(docsrs, doc(cfg(feature = "observability")))]
+#[cfg(feature = "observability")]
+pub fn registered() -> Vec<&'static dyn AnyGlobal> {
+    REGISTERED.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+//names and doc comments of registered globals, by address
+#[cfg(feature = "runtime-docs")]
+static DOCS: Mutex<Vec<(usize, &'static str, &'static str)>> = Mutex::new(Vec::new());
+
+///A registered global, with the name and doc comment it was declared with, as listed by
+///[`registered_docs`].
+#[cfg(feature = "runtime-docs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "runtime-docs")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobalDoc {
+    ///The path of the static, such as `app::config::SETTINGS`, if it was documented.
+    pub name: Option<&'static str>,
+    ///The type of the value inside the global.
+    pub type_name: &'static str,
+    ///The doc comment on the static, without the leading space of each line. Empty if it has
+    ///none.
+    pub doc: String,
+    ///Whether the global has been initialized.
+    pub initialized: bool,
+}
+
+///Registers a global like [`register`], along with its name and doc comment, so they show up in
+///[`registered_docs`]. `ctor_static!` and `#[singleton]` do this for the globals they declare,
+///with the doc comments written on them.
+#[cfg(feature = "runtime-docs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "runtime-docs")))]
+pub fn document(global: &'static dyn AnyGlobal, name: &'static str, doc: &'static str) {
+    register(global);
+    let addr = global as *const dyn AnyGlobal as *const () as usize;
+    let mut docs = DOCS.lock().unwrap_or_else(|e| e.into_inner());
+    match docs.iter_mut().find(|(a, ..)| *a == addr) {
+        Some(entry) => *entry = (addr, name, doc),
+        None => docs.push((addr, name, doc)),
+    }
+}
+
+///Every registered global, in registration order, with the names and doc comments given to
+///[`document`]. This is meant for admin pages and diagnostics that list what each global is for.
+///```rust
+///# use global_static::{Global, document, registered_docs};
+///static UPLOAD_LIMIT: Global<usize> = Global::new(|| 4);
+///document(&UPLOAD_LIMIT, "app::UPLOAD_LIMIT", "Maximum number of concurrent uploads.");
+///
+///let docs = registered_docs();
+///let limit = docs.iter().find(|g| g.name == Some("app::UPLOAD_LIMIT")).unwrap();
+///assert_eq!((limit.type_name, limit.doc.as_str()), ("usize", "Maximum number of concurrent uploads."));
+///```
+#[cfg(feature = "runtime-docs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "runtime-docs")))]
+pub fn registered_docs() -> Vec<GlobalDoc> {
+    let docs = DOCS.lock().unwrap_or_else(|e| e.into_inner());
+    registered().into_iter().map(|global| {
+        let addr = global as *const dyn AnyGlobal as *const () as usize;
+        let (name, doc) = docs.iter().find(|(a, ..)| *a == addr).map_or((None, ""), |(_, n, d)| (Some(*n), *d));
+        GlobalDoc {
+            name,
+            type_name: global.type_name(),
+            doc: doc.lines().map(|l| l.strip_prefix(' ').unwrap_or(l)).collect::<Vec<_>>().join("\n").trim_end().to_owned(),
+            initialized: global.is_initialized(),
+        }
+    }).collect()
+}
+
+///Initializes every registered global.
+///
+///Globals registered by `ctor_static!` and `#[singleton]` are included automatically. Globals
+///are initialized concurrently on a few threads, unless the `deterministic` feature is enabled,
+///in which case they are initialized one by one on the calling thread, in registration order.
+///
+///If an initializer panics, the remaining globals are still initialized, and the first panic is
+///resumed once they are done. Each run is added to the [`startup_summary`](crate::startup_summary).
+#[cfg_attr(docsrs, doc(cfg(feature = "observability")))]
+#[cfg(feature = "observability")]
+pub fn init_all() {
+    let start = Instant::now();
+    let globals = registered();
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get()).min(globals.len());
+    let panics: Vec<_> = if cfg!(feature = "deterministic") || threads <= 1 {
+        globals.iter().filter_map(|g| startup::init_recorded(*g).err()).collect()
+    } else {
+        std::thread::scope(|s| {
+            let handles: Vec<_> = (0..threads).map(|offset| {
+                let globals = &globals;
+                s.spawn(move || {
+                    globals.iter().skip(offset).step_by(threads)
+                        .filter_map(|g| startup::init_recorded(*g).err())
+                        .collect::<Vec<_>>()
+                })
+            }).collect();
+            handles.into_iter().flat_map(|h| h.join().unwrap_or_default()).collect()
+        })
+    };
+    startup::add_time(start);
+    startup::log();
+    if let Some(panic) = panics.into_iter().next() {
+        std::panic::resume_unwind(panic);
+    }
+}
+
+///Drops every registered global that supports it, most recently registered first, and clears
+///the registry. With the `dtor` feature this runs automatically when the process exits.
+///
+///# Safety
+///No references into the globals may be alive, and they must not be accessed afterwards.
+#[cfg_attr(docsrs, doc(cfg(feature = "observability")))]
+#[cfg(feature = "observability")]
+pub unsafe fn deinit_all() {
+    let globals = std::mem::take(&mut *REGISTERED.lock().unwrap_or_else(|e| e.into_inner()));
+    for global in globals.into_iter().rev() {
+        global.deinit();
+    }
+}
+
+#[cfg(all(feature = "dtor", not(any(feature = "ctor-fallback", target_family = "wasm"))))]
+#[ctor::dtor]
+fn deinit_at_exit() {
+    //safety: enabling the feature opts into globals not being used by other dtors or by threads
+    //still running at exit
+    unsafe { deinit_all() }
+}
+
+///A seed for globals holding random number generators.
+///
+///With the `deterministic` feature, this is always the same: the value of the
+///`GLOBAL_STATIC_SEED` environment variable if set, or a fixed constant otherwise. Without it,
+///a new random seed is produced on every call.
+///```rust
+///# use global_static::{Global, seed};
+///static JITTER: Global<u64> = Global::new(|| seed() % 100);
+///```
+pub fn seed() -> u64 {
+    if cfg!(feature = "deterministic") {
+        return std::env::var("GLOBAL_STATIC_SEED").ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0x5eed_5eed_5eed_5eed);
+    }
+    use std::hash::{BuildHasher, Hasher};
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos()));
+    hasher.finish()
+}
+
+///Internal function used by generated ctors. Do not use.
+#[doc(hidden)]
+pub fn __exempt(global: &'static dyn AnyGlobal) {
+    #[cfg(feature = "instrumented")]
+    crate::instrument::exempt_type(global.type_name());
+    #[cfg(not(feature = "instrumented"))]
+    let _ = global;
+}
+
+///Internal function used by generated ctors. Do not use.
+#[doc(hidden)]
+#[inline]
+pub fn __document(global: &'static dyn AnyGlobal, name: &'static str, doc: &'static str) {
+    #[cfg(feature = "runtime-docs")]
+    document(global, name, doc);
+    #[cfg(not(feature = "runtime-docs"))]
+    let _ = (global, name, doc);
+}
+
+///Internal function used by generated ctors. Do not use.
+#[doc(hidden)]
+#[cfg(feature = "observability")]
+pub fn __ctor_init(global: &'static dyn AnyGlobal) {
+    register(global);
+    //with deterministic initialization, nothing happens until init_all
+    if !cfg!(feature = "deterministic") {
+        let start = Instant::now();
+        let res = startup::init_recorded(global);
+        startup::add_time(start);
+        if let Err(panic) = res {
+            #[cfg(feature = "panic-safe-ctor")]
+            startup::defer(global, &*panic);
+            #[cfg(not(feature = "panic-safe-ctor"))]
+            std::panic::resume_unwind(panic);
+        }
+    }
+}
+
+#[cfg(feature = "handoff")]
+impl<T> AnyGlobal for crate::GlobalHandoff<T>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + Send + Sync,
+{
+    fn init(&self) {
+        crate::GlobalHandoff::init(self)
+    }
+    fn is_initialized(&self) -> bool {
+        self.get().is_some()
+    }
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
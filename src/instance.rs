@@ -0,0 +1,30 @@
+use crate::Poisoned;
+
+///A type with a single, lazily initialized instance.
+///
+///With the `singleton` feature, `#[singleton(instance)]` implements this for the annotated type
+///and keeps the static holding the instance private, so the type is only accessed through
+///[`instance`](Singleton::instance). Generic code can be written over `S: Singleton`.
+///```rust
+///# use global_static::{Global, Poisoned, Singleton};
+///struct Config { port: u16 }
+///
+///static CONFIG: Global<Config> = Global::new(|| Config { port: 8080 });
+///impl Singleton for Config {
+///    fn instance() -> &'static Self { CONFIG.force() }
+///    fn try_instance() -> Result<&'static Self, Poisoned> { CONFIG.try_get() }
+///}
+///
+///fn describe<S: Singleton>(f: fn(&S) -> String) -> String {
+///    f(S::instance())
+///}
+///assert_eq!(describe::<Config>(|c| c.port.to_string()), "8080");
+///```
+pub trait Singleton: Sized + 'static {
+    ///Retrieves the instance, initializing it if needed. See [`Global::force`](crate::Global::force).
+    fn instance() -> &'static Self;
+
+    ///Retrieves the instance, initializing it if needed, or returns an error if the last attempt
+    ///to initialize it panicked. See [`Global::try_get`](crate::Global::try_get).
+    fn try_instance() -> Result<&'static Self, Poisoned>;
+}
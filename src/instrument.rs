@@ -0,0 +1,386 @@
+//!Debug checks for globals that are initialized from contexts where allocating or locking is
+//!risky.
+//!
+//!When the `instrumented` feature is enabled and debug assertions are on, every lazy
+//!initialization first checks whether it is running during unwinding, inside a thread-local
+//!destructor, or inside a signal handler marked with [`SignalGuard`]. These are reported on
+//!stderr along with the type of the global and, outside of signal handlers, a backtrace, rather
+//!than crashing sometime later.
+//!
+//!Initializers are also timed, so startup latency can be checked against per-global budgets
+//!(see [`Global::with_budget`](crate::Global::with_budget)) and a total [`set_startup_budget`].
+//!
+//![`check_foreign_globals`] helps teams that keep all their globals in this crate notice lazy
+//!statics from other crates creeping in.
+use std::{
+    backtrace::Backtrace,
+    cell::Cell,
+    fmt::{self, Display},
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+static RECORDS: Mutex<Vec<InitRecord>> = Mutex::new(Vec::new());
+static TOTAL_BUDGET: Mutex<Option<Duration>> = Mutex::new(None);
+static POLICY: AtomicU8 = AtomicU8::new(BudgetPolicy::Log as u8);
+static EXEMPT: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+//type name prefixes of lazy statics from other crates
+const FOREIGN: &[(&str, &str)] = &[
+    ("lazy_static::", "lazy_static"),
+    ("once_cell::", "once_cell"),
+    ("std::sync::lazy_lock::LazyLock<", "std::sync::LazyLock"),
+    ("std::sync::once_lock::OnceLock<", "std::sync::OnceLock"),
+    ("core::cell::lazy::LazyCell<", "std::cell::LazyCell"),
+    ("core::cell::once::OnceCell<", "std::cell::OnceCell"),
+];
+
+struct Alive;
+impl Drop for Alive {
+    fn drop(&mut self) {}
+}
+
+thread_local! {
+    static ALIVE: Alive = const { Alive };
+    //signals are delivered to a single thread, so only that thread is in the handler. const and
+    //without a destructor, so it is never lazily registered from inside a handler
+    static IN_SIGNAL_HANDLER: Cell<u32> = const { Cell::new(0) };
+}
+
+///A context in which lazy initialization is considered dangerous.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    ///The current thread is panicking.
+    Unwinding,
+    ///Thread-local storage of the current thread is being torn down.
+    TlsDestructor,
+    ///A [`SignalGuard`] is alive on the current thread.
+    SignalHandler,
+}
+
+impl Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Context::Unwinding => "during unwinding",
+            Context::TlsDestructor => "inside a thread-local destructor",
+            Context::SignalHandler => "inside a signal handler",
+        })
+    }
+}
+
+///Marks the current scope as a signal handler until dropped. Any global initialized while a
+///guard is alive is reported.
+///
+///This only touches a thread-local counter, so it is safe to create from within a signal
+///handler.
+///```rust
+///# use global_static::instrument::SignalGuard;
+///extern "C" fn on_sigint(_: i32) {
+///    let _guard = SignalGuard::enter();
+///    /* handler body */
+///}
+///```
+pub struct SignalGuard(PhantomData<*const ()>);
+
+impl SignalGuard {
+    ///Enters a signal handler context.
+    pub fn enter() -> Self {
+        IN_SIGNAL_HANDLER.with(|n| n.set(n.get() + 1));
+        SignalGuard(PhantomData)
+    }
+}
+
+impl Drop for SignalGuard {
+    fn drop(&mut self) {
+        IN_SIGNAL_HANDLER.with(|n| n.set(n.get() - 1));
+    }
+}
+
+///Returns the dangerous context the current thread is in, if any.
+///
+///Thread-local destructors are detected on a best-effort basis: this can only notice teardown
+///once this module's own thread-local has been destroyed.
+pub fn current_context() -> Option<Context> {
+    if IN_SIGNAL_HANDLER.with(Cell::get) > 0 {
+        return Some(Context::SignalHandler);
+    }
+    if std::thread::panicking() {
+        return Some(Context::Unwinding);
+    }
+    if ALIVE.try_with(|_| ()).is_err() {
+        return Some(Context::TlsDestructor);
+    }
+    None
+}
+
+pub(crate) fn check_context<T>() {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    match current_context() {
+        //formatting, allocating and locking stderr aren't safe in a signal handler, so the
+        //message is written out in pieces
+        Some(Context::SignalHandler) => {
+            write_stderr("global_static: `Global<");
+            write_stderr(std::any::type_name::<T>());
+            write_stderr(">` was initialized inside a signal handler\n");
+        },
+        Some(ctx) => eprintln!(
+            "global_static: `Global<{}>` was initialized {ctx}\n{}",
+            std::any::type_name::<T>(),
+            Backtrace::force_capture(),
+        ),
+        None => {},
+    }
+}
+
+fn write_stderr(msg: &str) {
+    #[cfg(unix)]
+    //safety: the buffer is valid for its length. a failed or short write only loses the report
+    unsafe {
+        libc::write(libc::STDERR_FILENO, msg.as_ptr().cast(), msg.len());
+    }
+    #[cfg(not(unix))]
+    {
+        use std::io::Write;
+        let _ = std::io::stderr().write_all(msg.as_bytes());
+    }
+}
+
+///The time taken by a single initializer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitRecord {
+    ///The type of the global's value.
+    pub type_name: &'static str,
+    ///How long the initializer took.
+    pub duration: Duration,
+    ///The budget set with [`Global::with_budget`](crate::Global::with_budget), if any.
+    pub budget: Option<Duration>,
+}
+
+impl InitRecord {
+    ///Whether this initializer took longer than its budget.
+    pub fn over_budget(&self) -> bool {
+        self.budget.is_some_and(|b| self.duration > b)
+    }
+}
+
+///What to do when an initializer exceeds its budget, or all initializers together exceed the
+///startup budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum BudgetPolicy {
+    ///Print the breakdown to stderr.
+    Log,
+    ///Panic with the breakdown.
+    Panic,
+}
+
+///Sets what happens when a budget is exceeded. Defaults to [`BudgetPolicy::Log`].
+pub fn set_budget_policy(policy: BudgetPolicy) {
+    POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+///Sets the total time all initializers together are expected to take.
+pub fn set_startup_budget(budget: Duration) {
+    *TOTAL_BUDGET.lock().unwrap_or_else(|e| e.into_inner()) = Some(budget);
+}
+
+///All initializers that have run so far, in the order they finished.
+pub fn records() -> Vec<InitRecord> {
+    RECORDS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+///A breakdown of initializers that did not fit in their budget.
+#[derive(Debug, Clone)]
+pub struct BudgetExceeded {
+    ///The total budget, if it was exceeded.
+    pub total_budget: Option<Duration>,
+    ///Time taken by all initializers.
+    pub total: Duration,
+    ///All initializers, slowest first.
+    pub records: Vec<InitRecord>,
+}
+
+impl Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.total_budget {
+            Some(budget) => writeln!(f, "globals took {:?} to initialize, over the {budget:?} startup budget:", self.total)?,
+            None => writeln!(f, "globals exceeded their initialization budget:")?,
+        }
+        for r in &self.records {
+            write!(f, "    {}: {:?}", r.type_name, r.duration)?;
+            match r.budget {
+                Some(b) if r.over_budget() => writeln!(f, " (over budget of {b:?})")?,
+                Some(b) => writeln!(f, " (budget {b:?})")?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+///Checks the initializers that have run so far against their budgets and the total startup
+///budget. Call this at the end of startup, or in a test, to turn latency regressions into
+///failures.
+pub fn check_startup_budget() -> Result<(), BudgetExceeded> {
+    let mut records = records();
+    let total = records.iter().map(|r| r.duration).sum();
+    let total_budget = TOTAL_BUDGET.lock().unwrap_or_else(|e| e.into_inner())
+        .filter(|b| total > *b);
+    if total_budget.is_none() && !records.iter().any(InitRecord::over_budget) {
+        return Ok(());
+    }
+    records.sort_by_key(|r| std::cmp::Reverse(r.duration));
+    Err(BudgetExceeded { total_budget, total, records })
+}
+
+pub(crate) fn timed<T, R>(budget: Option<Duration>, f: impl FnOnce() -> R) -> R {
+    //timing is disabled in deterministic mode so reports are reproducible
+    if cfg!(feature = "deterministic") {
+        let value = f();
+        let record = InitRecord { type_name: std::any::type_name::<T>(), duration: Duration::ZERO, budget };
+        RECORDS.lock().unwrap_or_else(|e| e.into_inner()).push(record);
+        return value;
+    }
+    let start = Instant::now();
+    let value = f();
+    let record = InitRecord { type_name: std::any::type_name::<T>(), duration: start.elapsed(), budget };
+    let over = record.over_budget();
+    RECORDS.lock().unwrap_or_else(|e| e.into_inner()).push(record.clone());
+
+    if over {
+        let report = BudgetExceeded { total_budget: None, total: record.duration, records: vec![record] };
+        match POLICY.load(Ordering::Relaxed) {
+            p if p == BudgetPolicy::Panic as u8 => panic!("{report}"),
+            _ => eprintln!("global_static: {report}"),
+        }
+    }
+    value
+}
+
+///A global whose value is a lazy static from another crate, found by
+///[`check_foreign_globals`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignGlobal {
+    ///The type of the global's value.
+    pub type_name: &'static str,
+    ///The crate or type it comes from, such as `once_cell`.
+    pub kind: &'static str,
+}
+
+impl Display for ForeignGlobal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "global of type `{}` wraps a {} static", self.type_name, self.kind)
+    }
+}
+
+///Exempts globals whose value is a `T` from [`check_foreign_globals`]. `ctor_static!` does the
+///same for globals marked with `#[exempt]`.
+pub fn exempt<T>() {
+    exempt_type(std::any::type_name::<T>());
+}
+
+pub(crate) fn exempt_type(type_name: &'static str) {
+    let mut exempt = EXEMPT.lock().unwrap_or_else(|e| e.into_inner());
+    if !exempt.contains(&type_name) {
+        exempt.push(type_name);
+    }
+}
+
+///Scans the globals known to this crate, those [registered](crate::register) for
+///[`init_all`](crate::init_all) and those in the process-wide
+///[`GlobalRegistry`](crate::GlobalRegistry), for values that are themselves lazy statics from
+///`lazy_static`, `once_cell` or the standard library. Each one that isn't [exempt] is reported
+///on stderr, like a lint, and returned.
+///
+///Statics declared directly with other crates can't be found at runtime, so this only catches
+///ones that have been wrapped in, or registered through, this crate.
+///```rust
+///# use global_static::{Global, register, instrument};
+///# use std::sync::OnceLock;
+///static PATHS: Global<OnceLock<Vec<String>>> = Global::new(OnceLock::new);
+///register(&PATHS);
+///
+///assert_eq!(instrument::check_foreign_globals()[0].kind, "std::sync::OnceLock");
+///instrument::exempt::<OnceLock<Vec<String>>>();
+///assert!(instrument::check_foreign_globals().is_empty());
+///```
+pub fn check_foreign_globals() -> Vec<ForeignGlobal> {
+    let types = crate::registered().into_iter().map(|g| g.type_name())
+        .chain(crate::GlobalRegistry::global().type_names());
+    let exempt = EXEMPT.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let found: Vec<_> = types
+        .filter(|t| !exempt.contains(t))
+        .filter_map(|type_name| {
+            let (_, kind) = FOREIGN.iter().find(|(prefix, _)| type_name.starts_with(prefix))?;
+            Some(ForeignGlobal { type_name, kind })
+        })
+        .collect();
+    for global in &found {
+        eprintln!("global_static: {global}; declare it with this crate, or exempt it");
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_contexts() {
+        assert_eq!(current_context(), None);
+        {
+            let _guard = SignalGuard::enter();
+            assert_eq!(current_context(), Some(Context::SignalHandler));
+            //only the thread running the handler is in it
+            std::thread::spawn(|| assert_eq!(current_context(), None)).join().unwrap();
+        }
+        assert_eq!(current_context(), None);
+
+        struct Check;
+        impl Drop for Check {
+            fn drop(&mut self) {
+                assert_eq!(current_context(), Some(Context::Unwinding));
+            }
+        }
+        let res = std::panic::catch_unwind(|| {
+            let _check = Check;
+            panic!("boom");
+        });
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn foreign_globals() {
+        use std::sync::OnceLock;
+        let registry = crate::GlobalRegistry::global();
+        registry.register_named("instrument::foreign", OnceLock::<i16>::new);
+        let found = |kind| check_foreign_globals().iter().any(|g| g.type_name.contains("i16") && g.kind == kind);
+
+        assert!(found("std::sync::OnceLock"));
+        exempt::<OnceLock<i16>>();
+        assert!(!found("std::sync::OnceLock"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "deterministic"))]
+    fn budgets() {
+        static SLOW: crate::Global<u8> = crate::Global::<u8>::new(|| {
+            std::thread::sleep(Duration::from_millis(5));
+            1
+        }).with_budget(Duration::from_millis(1));
+
+        SLOW.init();
+        let record = records().into_iter().find(|r| r.budget == Some(Duration::from_millis(1))).unwrap();
+        assert!(record.over_budget());
+        let report = check_startup_budget().unwrap_err();
+        assert!(report.to_string().contains("u8"));
+    }
+}
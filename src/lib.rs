@@ -1,7 +1,14 @@
 #![doc = include_str!("../README.md")]
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
-use std::{ops::Deref, sync::OnceLock, fmt::{Debug, Display}};
+#![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
+
+#[cfg(all(feature = "cdylib-safe", feature = "ctor"))]
+compile_error!(
+    "the `cdylib-safe` feature cannot be combined with `ctor` or `singleton`, \
+    since life-before-main is not reliable in dynamically loaded libraries"
+);
+use core::{fmt::{Debug, Display}, time::Duration, task::Poll, mem::MaybeUninit, sync::atomic::{AtomicBool, AtomicPtr, Ordering}};
 
 
 #[cfg_attr(docsrs, doc(cfg(feature = "ctor")))]
@@ -11,11 +18,61 @@ pub use ctor;
 
 #[cfg_attr(docsrs, doc(cfg(feature = "singleton")))]
 #[cfg(feature = "singleton")]
-pub use singleton::{singleton, singleton_fn};
+pub use singleton::{singleton, singleton_fn, FfiSafe, CType};
+#[cfg(feature = "singleton")]
+singleton::__check_runtime_abi!(1);
+
+//version of the interface the code generated by global-static-singleton relies on, which is also
+//the `1` passed above. bumped together with `RUNTIME_ABI` there whenever generated code starts
+//relying on something new, so partial upgrades fail with an error naming both versions rather
+//than with missing items
+const MACRO_ABI: u32 = 1;
+
+#[doc(hidden)]
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+///Internal function used by generated code. Do not use.
+///
+///Fails to compile when called in a const context with another interface version.
+///```rust,compile_fail
+///const _: () = global_static::__check_macro_version("0.1.0", 0);
+///```
+#[doc(hidden)]
+pub const fn __check_macro_version(macro_version: &str, abi: u32) {
+    if abi != MACRO_ABI {
+        let (msg, len) = version_mismatch(macro_version);
+        match core::str::from_utf8(msg.split_at(len).0) {
+            Ok(msg) => panic!("{}", msg),
+            Err(_) => panic!("global-static-singleton is not compatible with this version of global-static"),
+        }
+    }
+}
+
+//const panics can't format, so the message is put together by hand
+const fn version_mismatch(macro_version: &str) -> ([u8; 256], usize) {
+    let parts = [
+        "global-static-singleton ", macro_version, " is not compatible with global-static ", VERSION,
+        "; update both crates to matching versions",
+    ];
+    let mut msg = [0; 256];
+    let mut len = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        let part = parts[i].as_bytes();
+        let mut j = 0;
+        while j < part.len() && len < msg.len() {
+            msg[len] = part[j];
+            len += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    (msg, len)
+}
 
 
 #[cfg_attr(docsrs, doc(cfg(feature = "ctor")))]
-#[cfg(feature = "ctor")]
+#[cfg(all(feature = "ctor", not(any(feature = "ctor-fallback", target_family = "wasm"))))]
 #[macro_export]
 /// Generate a static with a ctor procedure.
 /// 
@@ -27,6 +84,7 @@ pub use singleton::{singleton, singleton_fn};
 ///    pub MY_NUM: i32 = { 5 };
 ///    MY_OTHER_NUM: i32 = spit_a_number;
 ///    pub default DEFAULT_NUM: i32;
+///    mut COUNTER: i32 = { 0 };
 ///};
 ///```
 ///This code will expand to the following:
@@ -36,14 +94,85 @@ pub use singleton::{singleton, singleton_fn};
 ///pub static MY_NUM: Global<i32> = Global::new(|| { 5 });
 ///static MY_OTHER_NUM: Global<i32> = Global::new(spit_a_number);
 ///pub static DEFAULT_NUM: Global<i32> = Global::default();
+///static COUNTER: GlobalMut<i32> = GlobalMut::new(|| { 0 });
 ///
 ///#[global_static::ctor::ctor]
 ///fn _global_init() {
-///    MY_NUM.init();
-///    MY_OTHER_NUM.init();
-///    DEFAULT_NUM.init();
+///    global_static::__ctor_init(&MY_NUM);
+///    global_static::__ctor_init(&MY_OTHER_NUM);
+///    global_static::__ctor_init(&DEFAULT_NUM);
+///    global_static::__ctor_init(&COUNTER);
 ///}
 ///```
+///
+///Each entry can have doc comments and other attributes, which are put on its static, and any
+///visibility. `cfg` attributes also apply to the entry's initialization. An initializer can be
+///a block or any other expression, which is evaluated when the global is initialized, or a
+///path to a function or a closure, which is called to produce the value. Qualified paths such as
+///`<T as Default>::default` are taken as expressions, so they need to be called.
+///```rust
+///# use global_static::ctor_static;
+///fn base() -> u32 { 40 }
+///
+///ctor_static! {
+///    ///The answer.
+///    pub(crate) ANSWER: u32 = base() + 2;
+///    #[cfg(unix)]
+///    HOME: String = std::env::var("HOME").unwrap_or_default();
+///};
+///assert_eq!(*ANSWER, 42);
+///```
+///
+///Globals in one invocation are initialized in the order they are declared, but the order of
+///separate invocations depends on the linker. A global that needs others to be initialized
+///first can list them with `#[after(..)]`, which initializes them right before it, wherever
+///they are declared:
+///```rust
+///# use global_static::ctor_static;
+///# mod config { global_static::ctor_static! { pub CONFIG: u32 = { 3 }; } }
+///ctor_static! {
+///    #[after(config::CONFIG)]
+///    LOG_LEVEL: u32 = { *config::CONFIG };
+///};
+///```
+///There is no `before`, since an invocation can't delay ctors that have already run; put
+///`after` on the dependent global instead.
+///
+///With the `instrumented` feature, `#[exempt]` excludes a global from
+///`instrument::check_foreign_globals`, for the few that deliberately wrap a lazy static from
+///another crate.
+///
+///`thread_local` generates a [`LocalGlobal`] with a separate value for each thread. These are
+///initialized on first use in each thread rather than at startup.
+///```rust
+///# use global_static::ctor_static;
+///# use std::cell::RefCell;
+///ctor_static! {
+///    thread_local SCRATCH: RefCell<Vec<u8>> = { RefCell::new(Vec::with_capacity(1024)) };
+///};
+///SCRATCH.with(|s| s.borrow_mut().push(1));
+///```
+///
+///`value` stores a const value in the global directly, so it never runs an initializer.
+///```rust
+///# use global_static::ctor_static;
+///ctor_static! {
+///    pub value RETRIES: u32 = 3;
+///};
+///assert_eq!(*RETRIES, 3);
+///```
+///
+///`stub(..)` takes a `cfg` predicate, and generates a [stub](Global::stub) in place of the
+///global when it doesn't hold, so call sites don't need `cfg` attributes of their own. The stub
+///panics when accessed, or uses the initializer given after the predicate instead.
+///```rust
+///# use global_static::ctor_static;
+///ctor_static! {
+///    pub stub(feature = "metrics") EXPORTER: String = { "prometheus".to_owned() };
+///    stub(feature = "metrics", || 0) INTERVAL: u64 = { 15 };
+///};
+///assert_eq!(*INTERVAL, 0);
+///```
 macro_rules! ctor_static {
     () => {};
     ($($body:tt)*) => {
@@ -55,39 +184,324 @@ macro_rules! ctor_static {
     };
 }
 
+#[cfg(all(feature = "ctor", any(feature = "ctor-fallback", target_family = "wasm")))]
+#[macro_export]
+///Generate statics with the same syntax as the `ctor` version of this macro. On platforms
+///without life-before-main, such as wasm, no ctor is generated. The globals are initialized
+///lazily on first use, or eagerly by the function generated with [`init_all_globals!`].
+///
+///```rust
+///# use global_static::ctor_static;
+///ctor_static! {
+///    pub MY_NUM: i32 = { 5 };
+///};
+///assert_eq!(*MY_NUM, 5);
+///```
+macro_rules! ctor_static {
+    () => {};
+    ($($body:tt)*) => {
+        $crate::ctor_gen_defs!($($body)*);
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        pub fn __global_static_init() {
+            $crate::ctor_gen_inits!($($body)*);
+        }
+    };
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "ctor")))]
+#[cfg(feature = "ctor")]
+#[macro_export]
+///Generate a function that initializes the listed globals, for platforms without
+///life-before-main, where ctors never run. `mod` entries initialize every global declared by the
+///`ctor_static!` invocation in that module, and other entries initialize a single global, such
+///as one generated by `#[singleton]`.
+///
+///On wasm, or with the `ctor-fallback` feature, `ctor_static!` and `#[singleton]` don't
+///generate ctors, so globals are only initialized on first use unless this function is called
+///at the start of `main`. Other platforms keep running ctors as usual, and the function does
+///nothing for globals that are already initialized, so the same code works everywhere. With the
+///`deterministic` feature, the globals are only registered, like in a ctor.
+///```rust
+///mod config {
+///    global_static::ctor_static! {
+///        pub PORT: u16 = { 8080 };
+///    }
+///}
+///static NAME: global_static::Global<String> = global_static::Global::new(|| "app".to_owned());
+///
+///global_static::init_all_globals! {
+///    mod config;
+///    NAME;
+///}
+///
+///# fn main() {
+///init_all_globals();
+///assert_eq!(*config::PORT, 8080);
+///# }
+///```
+macro_rules! init_all_globals {
+    ($($body:tt)*) => {
+        ///Initializes every global listed in `init_all_globals!`.
+        pub fn init_all_globals() {
+            $crate::init_gen_all!($($body)*);
+        }
+    };
+}
+
+///Internal macro. Do not use.
+#[cfg(all(feature = "ctor", any(feature = "ctor-fallback", target_family = "wasm")))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! init_gen_all {
+    () => {};
+    (mod $($module:ident)::+; $($tail:tt)*) => {
+        $($module)::+::__global_static_init();
+        $crate::init_gen_all!($($tail)*);
+    };
+    ($global:path; $($tail:tt)*) => {
+        $crate::__ctor_init(&$global);
+        $crate::init_gen_all!($($tail)*);
+    };
+}
+
+///Internal macro. Do not use.
+#[cfg(all(feature = "ctor", not(any(feature = "ctor-fallback", target_family = "wasm"))))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! init_gen_all {
+    () => {};
+    //ctors already ran, but make sure the module exists
+    (mod $($module:ident)::+; $($tail:tt)*) => {
+        #[allow(unused_imports)]
+        use $($module)::+ as _;
+        $crate::init_gen_all!($($tail)*);
+    };
+    ($global:path; $($tail:tt)*) => {
+        $crate::__ctor_init(&$global);
+        $crate::init_gen_all!($($tail)*);
+    };
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "ctor")))]
+#[cfg(all(feature = "ctor", not(any(feature = "ctor-fallback", target_family = "wasm"))))]
+#[macro_export]
+///Register entries in the process-wide [`GlobalRegistry`] at startup. Entries are registered
+///before `main`, but their values are still produced on first lookup. This is unavailable on
+///platforms without life-before-main, where entries should be registered in `main` instead.
+///
+///```rust
+///# use global_static::{register_globals, GlobalRegistry};
+///struct Theme(&'static str);
+///
+///register_globals! {
+///    Theme = || Theme("dark");
+///    "motd": String = || "welcome".to_owned();
+///}
+///
+///# fn main() {
+///assert_eq!(GlobalRegistry::global().get::<Theme>().unwrap().0, "dark");
+///assert_eq!(GlobalRegistry::global().get_named::<String>("motd").unwrap(), "welcome");
+///# }
+///```
+macro_rules! register_globals {
+    ($($body:tt)*) => {
+        const _: () = {
+            #[$crate::ctor::ctor]
+            fn _global_register() {
+                $crate::register_gen!($($body)*);
+            }
+        };
+    };
+}
+
+///Internal macro. Do not use.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! register_gen {
+    () => {};
+    ($name:literal: $type:ty = $init:expr; $($tail:tt)*) => {
+        $crate::GlobalRegistry::global().register_named::<$type>($name, $init);
+        $crate::register_gen!($($tail)*);
+    };
+    ($type:ty = $init:expr; $($tail:tt)*) => {
+        $crate::GlobalRegistry::global().register::<$type>($init);
+        $crate::register_gen!($($tail)*);
+    };
+}
+
+#[macro_export]
+///Borrow several globals at once, initializing them in the order they are listed.
+///
+///```rust
+///# use global_static::{Global, globals};
+///static NAME: Global<String> = Global::new(|| "app".to_owned());
+///static PORT: Global<u16> = Global::new(|| 8080);
+///
+///let (name, port) = globals!(NAME, PORT);
+///assert_eq!(format!("{name}:{port}"), "app:8080");
+///```
+macro_rules! globals {
+    ($($global:expr),+ $(,)?) => {
+        ($(&*$global,)+)
+    };
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "cdylib-safe")))]
+#[cfg(all(feature = "cdylib-safe", not(feature = "ctor")))]
+#[macro_export]
+///Generate statics with the same syntax as the `ctor` version of this macro. In `cdylib-safe`
+///mode no ctor is generated, so globals are initialized lazily on first use.
+///
+///```rust
+///# use global_static::ctor_static;
+///ctor_static! {
+///    pub MY_NUM: i32 = { 5 };
+///};
+///assert_eq!(*MY_NUM, 5);
+///```
+macro_rules! ctor_static {
+    ($($body:tt)*) => {
+        $crate::ctor_gen_defs!($($body)*);
+    };
+}
+
 ///Internal macro. Do not use.
 #[macro_export]
 #[doc(hidden)]
 macro_rules! ctor_gen_defs {
     () => {};
 
-    ($name:ident: $type: ty = $init:block; $($tail:tt)*) => {
-        static $name: $crate::Global<$type> = $crate::Global::new(|| $init);
+    //attributes other than `after` and `exempt` are put on the generated statics
+    (@attrs [$($attrs:tt)*] #[after($($dep:path),+ $(,)?)] $($tail:tt)*) => {
+        $crate::ctor_gen_defs!(@attrs [$($attrs)*] $($tail)*);
+    };
+    (@attrs [$($attrs:tt)*] #[exempt] $($tail:tt)*) => {
+        $crate::ctor_gen_defs!(@attrs [$($attrs)*] $($tail)*);
+    };
+    (@attrs [$($attrs:tt)*] #[$attr:meta] $($tail:tt)*) => {
+        $crate::ctor_gen_defs!(@attrs [$($attrs)* #[$attr]] $($tail)*);
+    };
+
+    (@attrs [$($attrs:tt)*] $vis:vis value $name:ident: $type:ty = $value:expr; $($tail:tt)*) => {
+        $($attrs)*
+        $vis static $name: $crate::Global<$type> = <$crate::Global<$type>>::with_value($value)
+            .named(concat!(module_path!(), "::", stringify!($name)));
+        $crate::ctor_gen_defs!($($tail)*);
+    };
+    (@attrs [$($attrs:tt)*] $vis:vis default mut $name:ident: $type:ty; $($tail:tt)*) => {
+        $($attrs)*
+        $vis static $name: $crate::GlobalMut<$type> = $crate::GlobalMut::default();
         $crate::ctor_gen_defs!($($tail)*);
     };
-    (pub $name:ident: $type: ty = $init:block; $($tail:tt)*) => {
-        pub static $name: $crate::Global<$type> = $crate::Global::new(|| $init);
+    (@attrs [$($attrs:tt)*] $vis:vis default $name:ident: $type:ty; $($tail:tt)*) => {
+        $($attrs)*
+        $vis static $name: $crate::Global<$type> = <$crate::Global<$type>>::default()
+            .named(concat!(module_path!(), "::", stringify!($name)));
         $crate::ctor_gen_defs!($($tail)*);
     };
+    (@attrs [$($attrs:tt)*] $vis:vis mut $($tail:tt)*) => {
+        $crate::ctor_gen_defs!(@init [$($attrs)*] ($vis) (mut) $($tail)*);
+    };
+    (@attrs [$($attrs:tt)*] $vis:vis thread_local $($tail:tt)*) => {
+        $crate::ctor_gen_defs!(@init [$($attrs)*] ($vis) (thread_local) $($tail)*);
+    };
+    (@attrs [$($attrs:tt)*] $vis:vis stub($cfg:meta $(, $stub:expr)?) $($tail:tt)*) => {
+        $crate::ctor_gen_defs!(@init [$($attrs)*] ($vis) (stub ($cfg) ($($stub)?)) $($tail)*);
+    };
+    (@attrs [$($attrs:tt)*] $vis:vis $name:ident: $($tail:tt)*) => {
+        $crate::ctor_gen_defs!(@init [$($attrs)*] ($vis) () $name: $($tail)*);
+    };
+    (@attrs [$($attrs:tt)*] $($tail:tt)*) => {
+        compile_error!(concat!("invalid `ctor_static!` entry: `", stringify!($($tail)*), "`"));
+    };
+
+    //blocks and other expressions are evaluated when the global is initialized, while paths and
+    //closures are initializer functions
+    (@init $attrs:tt $vis:tt $kind:tt $name:ident: $type:ty = $init:block; $($tail:tt)*) => {
+        $crate::ctor_gen_defs!(@def $attrs $vis $kind $name: $type = (|| $init); $($tail)*);
+    };
+    //matched token by token, since a `path` fragment would try to parse calls as `Fn(A)` sugar
+    (@init $attrs:tt $vis:tt $kind:tt $name:ident: $type:ty = $($seg:ident $(::<$($arg:ty),+>)?)::+; $($tail:tt)*) => {
+        $crate::ctor_gen_defs!(@def $attrs $vis $kind $name: $type = ($($seg $(::<$($arg),+>)?)::+); $($tail)*);
+    };
+    (@init $attrs:tt $vis:tt $kind:tt $name:ident: $type:ty = :: $($seg:ident $(::<$($arg:ty),+>)?)::+; $($tail:tt)*) => {
+        $crate::ctor_gen_defs!(@def $attrs $vis $kind $name: $type = (:: $($seg $(::<$($arg),+>)?)::+); $($tail)*);
+    };
+    (@init $attrs:tt $vis:tt $kind:tt $name:ident: $type:ty = || $init:expr; $($tail:tt)*) => {
+        $crate::ctor_gen_defs!(@def $attrs $vis $kind $name: $type = (|| $init); $($tail)*);
+    };
+    (@init $attrs:tt $vis:tt $kind:tt $name:ident: $type:ty = move || $init:expr; $($tail:tt)*) => {
+        $crate::ctor_gen_defs!(@def $attrs $vis $kind $name: $type = (move || $init); $($tail)*);
+    };
+    (@init $attrs:tt $vis:tt $kind:tt $name:ident: $type:ty = $init:expr; $($tail:tt)*) => {
+        $crate::ctor_gen_defs!(@def $attrs $vis $kind $name: $type = (|| $init); $($tail)*);
+    };
+    (@init $attrs:tt $vis:tt $kind:tt $($tail:tt)*) => {
+        compile_error!(concat!("invalid `ctor_static!` entry: `", stringify!($($tail)*), "`"));
+    };
 
-    ($name:ident: $type: ty = $init:expr; $($tail:tt)*) => {
-        static $name: $crate::Global<$type> = $crate::Global::new($init);
+    (@def [$($attrs:tt)*] ($vis:vis) () $name:ident: $type:ty = ($init:expr); $($tail:tt)*) => {
+        $($attrs)*
+        $vis static $name: $crate::Global<$type> = <$crate::Global<$type>>::new($init)
+            .named(concat!(module_path!(), "::", stringify!($name)));
         $crate::ctor_gen_defs!($($tail)*);
     };
-    (pub $name:ident: $type: ty = $init:expr; $($tail:tt)*) => {
-        pub static $name: $crate::Global<$type> = $crate::Global::new($init);
+    (@def [$($attrs:tt)*] ($vis:vis) (mut) $name:ident: $type:ty = ($init:expr); $($tail:tt)*) => {
+        $($attrs)*
+        $vis static $name: $crate::GlobalMut<$type> = $crate::GlobalMut::new($init);
         $crate::ctor_gen_defs!($($tail)*);
     };
-
-    (default $name:ident: $type: ty; $($tail:tt)*) => {
-        static $name: $crate::Global<$type> = $crate::Global::default();
+    (@def [$($attrs:tt)*] ($vis:vis) (thread_local) $name:ident: $type:ty = ($init:expr); $($tail:tt)*) => {
+        $($attrs)*
+        $vis static $name: $crate::LocalGlobal<$type> = {
+            ::std::thread_local! {
+                static KEY: ::std::cell::OnceCell<$type> = const { ::std::cell::OnceCell::new() };
+            }
+            $crate::LocalGlobal::new(&KEY, $init)
+        };
         $crate::ctor_gen_defs!($($tail)*);
     };
-    (pub default $name:ident: $type: ty; $($tail:tt)*) => {
-        pub static $name: $crate::Global<$type> = $crate::Global::default();
+    (@def [$($attrs:tt)*] ($vis:vis) (stub ($cfg:meta) ($($stub:expr)?)) $name:ident: $type:ty = ($init:expr); $($tail:tt)*) => {
+        $($attrs)*
+        #[cfg($cfg)]
+        $vis static $name: $crate::Global<$type> = <$crate::Global<$type>>::new($init)
+            .named(concat!(module_path!(), "::", stringify!($name)));
+        $($attrs)*
+        #[cfg(not($cfg))]
+        $vis static $name: $crate::Global<$type> = $crate::ctor_gen_stub!($type $(, $stub)?)
+            .named(concat!(module_path!(), "::", stringify!($name)));
         $crate::ctor_gen_defs!($($tail)*);
     };
 
+    ($($tail:tt)+) => {
+        $crate::ctor_gen_defs!(@attrs [] $($tail)+);
+    };
+}
+
+///Internal macro. Do not use.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! ctor_gen_stub {
+    ($type:ty) => { <$crate::Global<$type>>::stub() };
+    ($type:ty, $stub:expr) => { <$crate::Global<$type>>::new($stub) };
+}
+
+///Internal macro. Do not use.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! ctor_gen_exempt {
+    ($name:ident) => {};
+    (exempt $name:ident) => { $crate::__exempt(&$name); };
+}
+
+///Internal macro. Do not use.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! ctor_gen_document {
+    ($name:ident [$($doc:literal)*]) => {
+        $crate::__document(&$name, concat!(module_path!(), "::", stringify!($name)), concat!($($doc, "\n"),*));
+    };
 }
 
 ///Internal macro. Do not use.
@@ -95,142 +509,2161 @@ macro_rules! ctor_gen_defs {
 #[doc(hidden)]
 macro_rules! ctor_gen_inits {
     () => {};
-    ($name:ident: $type: ty = $init:block; $($tail:tt)*) => {
-        $name.init();
-        $crate::ctor_gen_inits!($($tail)*);
+
+    //`cfg` attributes apply to the initialization as well, `after` dependencies are initialized
+    //first, doc comments are recorded for `registered_docs`, and other attributes only apply to
+    //the statics
+    (@attrs [$($cfgs:tt)*] [$($deps:tt)*] [$($exempt:ident)?] $docs:tt #[cfg($($cfg:tt)*)] $($tail:tt)*) => {
+        $crate::ctor_gen_inits!(@attrs [$($cfgs)* #[cfg($($cfg)*)]] [$($deps)*] [$($exempt)?] $docs $($tail)*);
     };
-    (pub $name:ident: $type: ty = $init:block; $($tail:tt)*) => {
-        $name.init();
-        $crate::ctor_gen_inits!($($tail)*);
+    (@attrs $cfgs:tt [$($deps:tt)*] $exempt:tt $docs:tt #[after($($dep:path),+ $(,)?)] $($tail:tt)*) => {
+        $crate::ctor_gen_inits!(@attrs $cfgs [$($deps)* $($crate::__ctor_init(&$dep);)+] $exempt $docs $($tail)*);
+    };
+    (@attrs $cfgs:tt $deps:tt [$($exempt:ident)?] $docs:tt #[exempt] $($tail:tt)*) => {
+        $crate::ctor_gen_inits!(@attrs $cfgs $deps [exempt] $docs $($tail)*);
+    };
+    (@attrs $cfgs:tt $deps:tt $exempt:tt [$($docs:tt)*] #[doc = $doc:literal] $($tail:tt)*) => {
+        $crate::ctor_gen_inits!(@attrs $cfgs $deps $exempt [$($docs)* $doc] $($tail)*);
+    };
+    (@attrs $cfgs:tt $deps:tt $exempt:tt $docs:tt #[$attr:meta] $($tail:tt)*) => {
+        $crate::ctor_gen_inits!(@attrs $cfgs $deps $exempt $docs $($tail)*);
     };
 
-    ($name:ident: $type: ty = $init:expr; $($tail:tt)*) => {
-        $name.init();
+    //thread-local globals are initialized on first use in each thread, and never audited
+    (@attrs [$($cfgs:tt)*] [$($deps:tt)*] $exempt:tt $docs:tt $vis:vis thread_local $name:ident: $type:ty = $init:expr; $($tail:tt)*) => {
+        $($cfgs)* { $($deps)* }
         $crate::ctor_gen_inits!($($tail)*);
     };
-    (pub $name:ident: $type: ty = $init:expr; $($tail:tt)*) => {
-        $name.init();
+    //panicking stubs are left alone, and only fail if they are used
+    (@attrs [$($cfgs:tt)*] [$($deps:tt)*] [$($exempt:ident)?] $docs:tt $vis:vis stub($cfg:meta) $name:ident: $type:ty = $init:expr; $($tail:tt)*) => {
+        $($cfgs)* {
+            $($deps)*
+            $crate::ctor_gen_exempt!($($exempt)? $name);
+            #[cfg($cfg)]
+            $crate::ctor_gen_document!($name $docs);
+            #[cfg($cfg)]
+            $crate::__ctor_init(&$name);
+        }
         $crate::ctor_gen_inits!($($tail)*);
     };
+    (@attrs $cfgs:tt $deps:tt $exempt:tt $docs:tt $vis:vis stub($cfg:meta, $stub:expr) $name:ident: $type:ty = $init:expr; $($tail:tt)*) => {
+        $crate::ctor_gen_inits!(@init $cfgs $deps $exempt $docs $name; $($tail)*);
+    };
+    //values are already there, but are registered like the others
+    (@attrs $cfgs:tt $deps:tt $exempt:tt $docs:tt $vis:vis value $name:ident: $type:ty = $value:expr; $($tail:tt)*) => {
+        $crate::ctor_gen_inits!(@init $cfgs $deps $exempt $docs $name; $($tail)*);
+    };
+    (@attrs $cfgs:tt $deps:tt $exempt:tt $docs:tt $vis:vis default mut $name:ident: $type:ty; $($tail:tt)*) => {
+        $crate::ctor_gen_inits!(@init $cfgs $deps $exempt $docs $name; $($tail)*);
+    };
+    (@attrs $cfgs:tt $deps:tt $exempt:tt $docs:tt $vis:vis default $name:ident: $type:ty; $($tail:tt)*) => {
+        $crate::ctor_gen_inits!(@init $cfgs $deps $exempt $docs $name; $($tail)*);
+    };
+    (@attrs $cfgs:tt $deps:tt $exempt:tt $docs:tt $vis:vis mut $name:ident: $type:ty = $init:expr; $($tail:tt)*) => {
+        $crate::ctor_gen_inits!(@init $cfgs $deps $exempt $docs $name; $($tail)*);
+    };
+    (@attrs $cfgs:tt $deps:tt $exempt:tt $docs:tt $vis:vis $name:ident: $type:ty = $init:expr; $($tail:tt)*) => {
+        $crate::ctor_gen_inits!(@init $cfgs $deps $exempt $docs $name; $($tail)*);
+    };
+    (@attrs $cfgs:tt $deps:tt $exempt:tt $docs:tt $($tail:tt)*) => {
+        compile_error!(concat!("invalid `ctor_static!` entry: `", stringify!($($tail)*), "`"));
+    };
 
-    (default $name:ident: $type: ty; $($tail:tt)*) => {
-        $name.init();
+    (@init [$($cfgs:tt)*] [$($deps:tt)*] [$($exempt:ident)?] $docs:tt $name:ident; $($tail:tt)*) => {
+        $($cfgs)* {
+            $($deps)*
+            $crate::ctor_gen_document!($name $docs);
+            $crate::ctor_gen_exempt!($($exempt)? $name);
+            $crate::__ctor_init(&$name);
+        }
         $crate::ctor_gen_inits!($($tail)*);
     };
-    (pub default $name:ident: $type: ty; $($tail:tt)*) => {
-        $name.init();
-        $crate::ctor_gen_inits!($($tail)*);
+
+    ($($tail:tt)+) => {
+        $crate::ctor_gen_inits!(@attrs [] [] [] [] $($tail)+);
     };
 }
 
 
-///Lazily evaluated static allocation.
-pub struct Global<T> {
-    f: fn() -> T,
-    data: OnceLock<SendPtr<T>>
-}
+#[cfg_attr(docsrs, doc(cfg(feature = "instrumented")))]
+#[cfg(feature = "instrumented")]
+pub mod instrument;
 
-struct SendPtr<T>(pub *const T);
-unsafe impl<T> Send for SendPtr<T> {}
-unsafe impl<T> Sync for SendPtr<T> {}
 
-impl<T> Deref for SendPtr<T> {
-    type Target = *const T;
+mod backend;
+pub use backend::{OnceBackend, DefaultBackend};
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
-}
+mod builder;
+pub use builder::GlobalBuilder;
 
+mod ffi;
+pub use ffi::FfiSafe;
 
-impl<T> Global<T> {
-    ///Constructs a new global.
-    ///Rather than a value, this function takes a closure that produces a value.
-    ///```rust
-    ///# use global_static::Global;
-    ///
-    ///static MY_TABLE: Global<Vec<&str>> = Global::new(|| vec!["a", "b", "c"]);
-    pub const fn new(f: fn() -> T) -> Self {
-        Self { f, data: OnceLock::new() }
-    }
+#[cfg(feature = "std")]
+mod header;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use header::{CType, CDefinitions, CHeader};
 
-    ///Initializes the contents of a global. Does nothing if already initialized.
-    pub fn init(&self) {
-        if let None = self.data.get() { 
-            let _ = unsafe { self.alloc() }; 
-        }
-    }
+mod prefetch;
 
-    ///Retrieves a reference to the value inside the global without allocating.
-    ///This function will return `None` if the global has not been allocated.
-    pub fn get(&self) -> Option<&T> {
-        self.data.get().map(|ptr| {unsafe { &***ptr }})
-    }
+mod optional;
+pub use optional::GlobalOpt;
 
-    ///Retrieves a reference to the value inside the global without allocating. Calling this function on
-    ///an unallocated global is undefined behavior.
-    pub unsafe fn get_unchecked(&self) -> &T {
-        //lol
-        &***self.data.get().unwrap_unchecked()
-    } 
-    
-    ///Caller must ensure cell has not been already allocated
-    unsafe fn alloc(&self) -> *const T {
-        //box will panic if it cannot allocate
-        let ptr = Box::leak(
-            Box::new((self.f)())
-            ) as *const T;
-        self.data.set(SendPtr(ptr)).unwrap_unchecked();
-        **self.data.get().unwrap_unchecked()
-    }
-}
+mod instance;
+pub use instance::Singleton;
 
-impl<T: Default> Global<T> {
-    ///Constructs a new global, using the [`Default`] implementation for `T` as the initializer.
-    //cant use trait cus not const
-    pub const fn default() -> Self {
-        Self::new(T::default)
-    } 
-}
+mod derived;
+pub use derived::{Source, MappedGlobal, ZipGlobal};
 
-impl<T> Deref for Global<T> {
-    type Target = T;
+#[cfg(feature = "std")]
+mod error;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use error::{Error, ErrorKind};
 
-    fn deref(&self) -> &Self::Target {
-        match self.data.get() {
-            Some(v) => unsafe { &***v },
-            None => unsafe { &*self.alloc() },
-        }
-    }
-}
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod unload;
 
-impl<T: Debug> Debug for Global<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.deref())
-    }
-}
-impl<T: Display> Display for Global<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.deref())
-    }
-}
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod executor;
 
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub mod access;
 
-#[cfg(test)]
-mod tests {
-    use std::ops::Add;
+#[cfg(feature = "std")]
+mod init;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use init::{AnyGlobal, seed};
+#[cfg_attr(docsrs, doc(cfg(feature = "observability")))]
+#[cfg(feature = "observability")]
+pub use init::{register, registered, init_all, deinit_all};
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub use init::{__document, __exempt};
+#[cfg(feature = "observability")]
+#[doc(hidden)]
+pub use init::__ctor_init;
+#[cfg(feature = "runtime-docs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "runtime-docs")))]
+pub use init::{document, registered_docs, GlobalDoc};
 
-    use super::*;
-    static TEST: super::Global<u8> = super::Global::new(|| 5);
+#[cfg(feature = "observability")]
+mod startup;
+#[cfg_attr(docsrs, doc(cfg(feature = "observability")))]
+#[cfg(feature = "observability")]
+pub use startup::{StartupRecord, StartupSummary, startup_summary, set_startup_log, check_startup, InitMode, Initialized};
+#[cfg_attr(docsrs, doc(cfg(feature = "observability")))]
+#[cfg(feature = "observability")]
+pub use startup::{forecast, Forecast, PendingGlobal};
+#[cfg_attr(docsrs, doc(cfg(feature = "ctor")))]
+#[cfg(feature = "ctor")]
+pub use startup::{ctor_ran, set_ctor_policy, CtorPolicy};
 
-    #[test]
-    fn it_works() {
-        assert_eq!(TEST.add(1), 6);
-        assert_eq!(*TEST, 5);
-    }
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[cfg(feature = "async")]
+mod wait;
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[cfg(feature = "async")]
+pub use wait::{BindError, WaitReady};
 
-    #[test]
-    #[cfg(feature = "ctor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[cfg(feature = "async")]
+mod async_global;
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[cfg(feature = "async")]
+pub use async_global::{AsyncGlobal, BoxFuture, Cancelled};
+
+#[cfg(feature = "std")]
+mod arc;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use arc::GlobalArc;
+
+#[cfg(feature = "std")]
+mod dynamic;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use dynamic::GlobalDyn;
+
+#[cfg(feature = "std")]
+mod mutable;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use mutable::GlobalMut;
+
+#[cfg(feature = "std")]
+mod map;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use map::{GlobalMap, MapMetrics};
+
+#[cfg(feature = "std")]
+mod fallible;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use fallible::TryGlobal;
+
+#[cfg(feature = "std")]
+mod string;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use string::GlobalStr;
+
+#[cfg(feature = "std")]
+mod units;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use units::{GlobalDuration, GlobalSize, ParseUnitError};
+
+#[cfg(feature = "std")]
+mod scope;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use scope::{GlobalScope, ScopedGlobal};
+
+#[cfg(feature = "std")]
+mod staged;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use staged::GlobalStaged;
+
+#[cfg(feature = "std")]
+mod library;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use library::GlobalLibrary;
+
+#[cfg(feature = "paths")]
+mod path;
+#[cfg_attr(docsrs, doc(cfg(feature = "paths")))]
+#[cfg(feature = "paths")]
+pub use path::GlobalPath;
+
+#[cfg(feature = "std")]
+mod swap;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use swap::{SwapGlobal, SwapHook};
+
+#[cfg(feature = "std")]
+mod striped;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use striped::GlobalStripedCounter;
+
+#[cfg(feature = "test-support")]
+mod overrides;
+#[cfg_attr(docsrs, doc(cfg(feature = "test-support")))]
+#[cfg(feature = "test-support")]
+pub use overrides::OverrideGuard;
+
+#[cfg(feature = "test-support")]
+mod fuzz;
+#[cfg_attr(docsrs, doc(cfg(feature = "test-support")))]
+#[cfg(feature = "test-support")]
+pub use fuzz::{InitOrderFuzz, OrderFailure, OrderFailureKind};
+
+#[cfg(feature = "init-cache")]
+mod cached;
+#[cfg_attr(docsrs, doc(cfg(feature = "init-cache")))]
+#[cfg(feature = "init-cache")]
+pub use cached::GlobalCached;
+
+#[cfg(feature = "handoff")]
+mod handoff;
+#[cfg_attr(docsrs, doc(cfg(feature = "handoff")))]
+#[cfg(feature = "handoff")]
+pub use handoff::{GlobalHandoff, Handoff, HANDOFF_VAR};
+
+#[cfg(feature = "std")]
+mod local;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use local::LocalGlobal;
+
+#[cfg(feature = "std")]
+mod registry;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use registry::GlobalRegistry;
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub use registry::__inject;
+
+#[cfg(feature = "std")]
+mod family;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use family::{GlobalFamily, FamilyMetrics};
+
+#[cfg(feature = "std")]
+mod config;
+
+#[cfg(feature = "std")]
+mod contrib;
+
+#[cfg(feature = "wit")]
+mod wit;
+#[cfg_attr(docsrs, doc(cfg(feature = "wit")))]
+#[cfg(feature = "wit")]
+pub use wit::{WitValue, ReturnArea};
+#[cfg(feature = "wit")]
+#[doc(hidden)]
+pub use wit::__wit_interface;
+
+#[cfg(feature = "std")]
+mod fetched;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use fetched::GlobalFetched;
+
+
+enum Init<T> {
+    Value(fn() -> T),
+    InPlace(unsafe fn(&mut MaybeUninit<T>)),
+    //no initializer, for globals constructed with a value, which are never empty
+    Preset,
+    //an initializer and its argument, such as the variable read by from_env
+    #[cfg(feature = "std")]
+    With(fn(&'static str) -> T, &'static str),
+    //an initializer given the address of the global, which keys its contributions or the
+    //failure of its bound future
+    #[cfg(feature = "std")]
+    Collect(fn(usize) -> T),
+}
+
+//derive would require T: Clone
+impl<T> Clone for Init<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for Init<T> {}
+
+//name and check set with ensure_with
+#[cfg(debug_assertions)]
+type Validator<T> = (&'static str, fn(&T) -> bool);
+
+//runs an initializer on the init executor, monomorphized where `T: Send` is known
+#[cfg(feature = "std")]
+type Offload<T> = fn(fn() -> T) -> Option<T>;
+
+///Lazily evaluated static allocation.
+///
+///# Panics during initialization
+///If the initializer panics, the panic propagates to the thread that triggered initialization and
+///the global is left empty and marked as poisoned. Threads that were waiting for the value try
+///to initialize it themselves, as does the next access through [`Deref`](core::ops::Deref), so a
+///transient failure can recover. [`try_get`](Global::try_get) returns an error instead of
+///retrying, and [`is_poisoned`](Global::is_poisoned) reports the failure until an attempt
+///succeeds.
+///
+///# Concurrent first access
+///The initializer runs at most once at a time, no matter how many threads access the global
+///before it is initialized. One of them runs it, and the others block until it is done and then
+///share its value, so nothing is produced and thrown away. Once initialized, an access is a
+///single acquire load and never takes a lock, which compiles to a plain load on x86.
+pub struct Global<T, B: OnceBackend<T> = DefaultBackend<T>> {
+    f: Init<T>,
+    //replacement `fn() -> T` set by set_initializer, null if there is none
+    replaced: AtomicPtr<()>,
+    //set when the last initialization attempt panicked
+    poisoned: AtomicBool,
+    data: B,
+    #[cfg(feature = "instrumented")]
+    budget: Option<Duration>,
+    #[cfg(debug_assertions)]
+    validator: Option<Validator<T>>,
+    #[cfg(feature = "std")]
+    offload: Option<Offload<T>>,
+    #[cfg(all(feature = "std", debug_assertions))]
+    access: Option<access::Access>,
+    #[cfg(feature = "observability")]
+    state: startup::InitState,
+    #[cfg(feature = "tracing")]
+    name: Option<&'static str>,
+}
+
+
+impl<T, B: OnceBackend<T>> Global<T, B> {
+    ///Constructs a new global.
+    ///Rather than a value, this function takes a closure that produces a value.
+    ///```rust
+    ///# use global_static::Global;
+    ///
+    ///static MY_TABLE: Global<Vec<&str>> = Global::new(|| vec!["a", "b", "c"]);
+    pub const fn new(f: fn() -> T) -> Self {
+        Self::from_init(Init::Value(f), B::EMPTY)
+    }
+
+    ///Constructs a new global that is initialized in place. Rather than returning a value, the
+    ///initializer writes it directly into the global's storage, so very large values are never
+    ///built on the stack. Custom backends that don't support this fall back to initializing
+    ///on the stack.
+    ///```rust
+    ///# use global_static::Global;
+    ///# use std::mem::MaybeUninit;
+    ///const LEN: usize = 4 * 1024 * 1024;
+    ///
+    ///unsafe fn fill(slot: &mut MaybeUninit<[u8; LEN]>) {
+    ///    let ptr = slot.as_mut_ptr() as *mut u8;
+    ///    for i in 0..LEN {
+    ///        ptr.add(i).write(i as u8);
+    ///    }
+    ///}
+    ///
+    ///static TABLE: Global<[u8; LEN]> = unsafe { Global::new_in_place(fill) };
+    ///assert_eq!(TABLE[LEN - 1], 255);
+    ///```
+    ///
+    ///# Safety
+    ///The initializer must fully initialize the value it is given.
+    pub const unsafe fn new_in_place(f: unsafe fn(&mut MaybeUninit<T>)) -> Self {
+        Self::from_init(Init::InPlace(f), B::EMPTY)
+    }
+
+    ///Constructs a stub global, which panics when accessed. This is for globals that only exist
+    ///with some cargo feature enabled, so call sites can use them without `cfg` attributes and
+    ///only fail if they are actually reached. `ctor_static!` and `#[singleton]` generate the
+    ///global or its stub from one declaration with `stub(..)`.
+    ///```rust,should_panic
+    ///# use global_static::Global;
+    ///#[cfg(feature = "metrics")]
+    ///static REGISTRY: Global<Vec<&str>> = Global::new(|| vec!["requests"]);
+    ///#[cfg(not(feature = "metrics"))]
+    ///static REGISTRY: Global<Vec<&str>> = Global::stub();
+    ///
+    ///println!("{}", REGISTRY.len());
+    ///```
+    pub const fn stub() -> Self {
+        Self::from_init(Init::Value(stub::<T>), B::EMPTY)
+    }
+
+    const fn from_init(f: Init<T>, data: B) -> Self {
+        Self {
+            f,
+            replaced: AtomicPtr::new(core::ptr::null_mut()),
+            poisoned: AtomicBool::new(false),
+            data,
+            #[cfg(feature = "instrumented")]
+            budget: None,
+            #[cfg(debug_assertions)]
+            validator: None,
+            #[cfg(feature = "std")]
+            offload: None,
+            #[cfg(all(feature = "std", debug_assertions))]
+            access: None,
+            #[cfg(feature = "observability")]
+            state: startup::InitState::new(),
+            #[cfg(feature = "tracing")]
+            name: None,
+        }
+    }
+
+    ///Sets how long the initializer is expected to take. With the `instrumented` feature, an
+    ///initializer that takes longer is reported according to the
+    ///[`BudgetPolicy`](instrument::BudgetPolicy). Does nothing otherwise.
+    ///```rust
+    ///# use global_static::Global;
+    ///# use std::time::Duration;
+    ///static TABLE: Global<Vec<u64>> = Global::<Vec<u64>>::new(|| (0..1000).collect())
+    ///    .with_budget(Duration::from_millis(50));
+    ///```
+    #[allow(unused_mut)]
+    pub const fn with_budget(mut self, budget: Duration) -> Self {
+        #[cfg(feature = "instrumented")]
+        { self.budget = Some(budget); }
+        let _ = budget;
+        self
+    }
+
+    ///Gives the global a human-readable name, which is included in the span and event emitted
+    ///when it is initialized with the `tracing` feature. Does nothing otherwise. `ctor_static!`
+    ///and `#[singleton]` name globals after their static.
+    ///```rust
+    ///# use global_static::Global;
+    ///static ROUTES: Global<Vec<&str>> = Global::<Vec<&str>>::new(|| vec!["/"]).named("routes");
+    ///```
+    #[allow(unused_mut)]
+    pub const fn named(mut self, name: &'static str) -> Self {
+        #[cfg(feature = "tracing")]
+        { self.name = Some(name); }
+        let _ = name;
+        self
+    }
+
+    ///Checks an invariant of the value on every access in debug builds, to catch it being broken
+    ///by unsafe code or foreign writes into the global. Accessing the global panics with the name
+    ///of the check if it returns `false`. Does nothing in release builds.
+    ///```rust
+    ///# use global_static::Global;
+    ///static PRIMES: Global<Vec<u32>> = Global::<Vec<u32>>::new(|| vec![2, 3, 5, 7])
+    ///    .ensure_with("sorted", |v| v.windows(2).all(|w| w[0] < w[1]));
+    ///assert_eq!(PRIMES[0], 2);
+    ///```
+    #[allow(unused_mut)]
+    pub const fn ensure_with(mut self, name: &'static str, check: fn(&T) -> bool) -> Self {
+        #[cfg(debug_assertions)]
+        { self.validator = Some((name, check)); }
+        let _ = (name, check);
+        self
+    }
+
+    ///Checks that the global is only accessed from the threads `access` allows, panicking on
+    ///every access from another one in debug builds. Threads are tagged with the functions in
+    ///[`access`]. Does nothing in release builds.
+    ///```rust
+    ///# use global_static::{Global, access::{self, Access, Tag}};
+    ///static DB_POOL: Global<Vec<&str>> = Global::<Vec<&str>>::new(|| vec!["conn"])
+    ///    .restrict_to(Access::NotAsync);
+    ///
+    ///fn on_executor_thread_start() {
+    ///    access::mark_thread(Tag::Async);
+    ///    //accessing `DB_POOL` on this thread now panics in debug builds
+    ///}
+    ///
+    ///assert_eq!(DB_POOL[0], "conn");
+    ///```
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[cfg(feature = "std")]
+    #[allow(unused_mut)]
+    pub const fn restrict_to(mut self, access: access::Access) -> Self {
+        #[cfg(debug_assertions)]
+        { self.access = Some(access); }
+        let _ = access;
+        self
+    }
+
+    ///Initializes the contents of a global. Does nothing if already initialized.
+    pub fn init(&self) {
+        self.force();
+    }
+
+    //what the crate's own startup and diagnostics use, which skips the access check since they
+    //don't run on behalf of the caller's thread
+    #[cfg(feature = "std")]
+    pub(crate) fn init_unchecked(&self) {
+        if self.peek().is_none() {
+            self.force_slow();
+        }
+    }
+
+    ///Sets the value of the global before it is first accessed, so the initializer never runs.
+    ///Returns the value back if the global was already initialized.
+    ///```rust
+    ///# use global_static::Global;
+    ///static PORT: Global<u16> = Global::new(|| 8080);
+    ///
+    ///fn main() {
+    ///    let port = std::env::args().nth(1).and_then(|a| a.parse().ok());
+    ///    if let Some(port) = port {
+    ///        PORT.set(port).unwrap();
+    ///    }
+    ///    println!("listening on {}", *PORT);
+    ///}
+    ///```
+    pub fn set(&self, value: T) -> Result<(), T> {
+        let res = self.data.set(value);
+        if res.is_ok() {
+            #[cfg(feature = "observability")]
+            self.state.record(true);
+            self.poisoned.store(false, Ordering::Release);
+            #[cfg(feature = "async")]
+            wait::wake_all();
+        }
+        res
+    }
+
+    ///Whether the last attempt to initialize the global panicked. This is cleared once the global
+    ///is successfully initialized.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::Acquire)
+    }
+
+    ///Retrieves the value inside the global, initializing it if needed. Unlike
+    ///[`force`](Global::force), a poisoned global returns an error instead of running the
+    ///initializer again.
+    ///```rust
+    ///# use global_static::{Global, Poisoned};
+    ///static FLAKY: Global<u8> = Global::new(|| panic!("backend unavailable"));
+    ///
+    ///assert!(std::panic::catch_unwind(|| *FLAKY).is_err());
+    ///assert_eq!(FLAKY.try_get(), Err(Poisoned));
+    ///FLAKY.set(0).unwrap();
+    ///assert_eq!(FLAKY.try_get(), Ok(&0));
+    ///```
+    pub fn try_get(&self) -> Result<&T, Poisoned> {
+        if let Some(v) = self.get() {
+            return Ok(v);
+        }
+        if self.is_poisoned() {
+            return Err(Poisoned);
+        }
+        Ok(self.force())
+    }
+
+    ///Replaces the initializer of the global before it is first accessed, so libraries can
+    ///provide a default that applications override during startup. Returns an error if the
+    ///global was already initialized. An initialization already in progress on another thread
+    ///still uses the previous initializer.
+    ///```rust
+    ///# use global_static::Global;
+    ///static CODEC: Global<&str> = Global::new(|| "json");
+    ///
+    ///CODEC.set_initializer(|| "msgpack").unwrap();
+    ///assert_eq!(*CODEC, "msgpack");
+    ///assert!(CODEC.set_initializer(|| "cbor").is_err());
+    ///```
+    pub fn set_initializer(&self, f: fn() -> T) -> Result<(), AlreadyInit> {
+        if self.data.get().is_some() {
+            return Err(AlreadyInit);
+        }
+        self.replaced.store(f as *mut (), Ordering::Release);
+        Ok(())
+    }
+
+    ///Retrieves the value inside the global, initializing it with `f` instead of the global's
+    ///own initializer if it is empty. `f` is timed and traced like the initializer, but always
+    ///runs on the calling thread, even if the global is [offloaded](Global::offload).
+    pub fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+        if let Some(v) = self.get() {
+            return v;
+        }
+        #[cfg(feature = "instrumented")]
+        instrument::check_context::<T>();
+        let poison = Poison(&self.poisoned);
+        let value = self.data.get_or_init(|| self.produce(f));
+        poison.disarm();
+        #[cfg(feature = "async")]
+        wait::wake_all();
+        value
+    }
+
+    ///Retrieves a reference to the value inside the global without allocating.
+    ///This function will return `None` if the global has not been allocated.
+    #[inline]
+    pub fn get(&self) -> Option<&T> {
+        self.check_access();
+        self.peek()
+    }
+
+    #[inline]
+    fn check_access(&self) {
+        #[cfg(all(feature = "std", debug_assertions))]
+        if let Some(access) = self.access {
+            access::check::<T>(access);
+        }
+    }
+
+    //`get` without the access check
+    #[inline]
+    pub(crate) fn peek(&self) -> Option<&T> {
+        #[cfg(feature = "test-support")]
+        if let Some(v) = self.overridden() {
+            return Some(v);
+        }
+        let value = self.data.get();
+        #[cfg(debug_assertions)]
+        if let (Some(v), Some((name, check))) = (value, self.validator) {
+            assert!(check(v), "`{}` failed its `{name}` check", core::any::type_name::<T>());
+        }
+        value
+    }
+
+    ///Retrieves a reference to the value inside the global, or `fallback` if it has not been
+    ///initialized. The initializer is never run, so this is safe to use from diagnostics paths
+    ///that must not trigger heavy initialization.
+    ///```rust
+    ///# use global_static::Global;
+    ///static BUILD_INFO: Global<String> = Global::new(|| "v1.2 (abc123)".to_owned());
+    ///
+    ///let unknown = String::from("unknown");
+    ///assert_eq!(BUILD_INFO.get_or(&unknown), "unknown");
+    ///BUILD_INFO.init();
+    ///assert_eq!(BUILD_INFO.get_or(&unknown), "v1.2 (abc123)");
+    ///```
+    #[inline]
+    pub fn get_or<'a>(&'a self, fallback: &'a T) -> &'a T {
+        self.get().unwrap_or(fallback)
+    }
+
+    ///Retrieves a reference to the value inside the global without allocating. Calling this function on
+    ///an unallocated global is undefined behavior.
+    ///
+    ///# Safety
+    ///The global must already be initialized.
+    pub unsafe fn get_unchecked(&self) -> &T {
+        self.get().unwrap_unchecked()
+    }
+
+    ///Retrieves a reference to the value inside the global, initializing it if needed. This is
+    ///what [`Deref`](core::ops::Deref) uses, and is the accessor to use with the `no-panic`
+    ///feature, where `Deref` is unavailable.
+    #[inline]
+    pub fn force(&self) -> &T {
+        match self.get() {
+            Some(v) => v,
+            None => self.force_slow(),
+        }
+    }
+
+    #[cfg(feature = "test-support")]
+    #[inline]
+    fn overridden(&self) -> Option<&T> {
+        //safety: overrides are leaked, so they outlive the global
+        overrides::lookup::<T>(self as *const Self as *const ()).map(|v| unsafe { &*v })
+    }
+
+    //kept out of line so the initialized path stays small enough to inline
+    #[cold]
+    #[inline(never)]
+    fn force_slow(&self) -> &T {
+        #[cfg(feature = "instrumented")]
+        instrument::check_context::<T>();
+        #[cfg(feature = "panic-safe-ctor")]
+        startup::raise_deferred(self as *const Self as usize);
+        #[cfg(feature = "ctor")]
+        startup::check_ctor_ran(core::any::type_name::<T>());
+        #[cfg(feature = "async")]
+        {
+            wait::wait_bound(self as *const Self as usize);
+            if let Some(v) = self.peek() {
+                return v;
+            }
+        }
+
+        let replaced = self.replaced.load(Ordering::Acquire);
+        let init = if replaced.is_null() {
+            self.f
+        } else {
+            //safety: only ever set from a `fn() -> T`
+            Init::Value(unsafe { core::mem::transmute::<*mut (), fn() -> T>(replaced) })
+        };
+
+        let offloaded = |f: fn() -> T| {
+            #[cfg(feature = "std")]
+            if let Some(value) = self.offload.and_then(|offload| offload(f)) {
+                return value;
+            }
+            f()
+        };
+
+        let poison = Poison(&self.poisoned);
+        let value = match init {
+            Init::Value(f) => self.data.get_or_init(|| self.produce(|| offloaded(f))),
+            #[cfg(feature = "std")]
+            Init::With(f, arg) => self.data.get_or_init(|| self.produce(|| f(arg))),
+            #[cfg(feature = "std")]
+            Init::Collect(f) => self.data.get_or_init(|| self.produce(|| f(self as *const Self as usize))),
+            Init::Preset => unreachable!("`{}` was constructed with a value", core::any::type_name::<T>()),
+            //safety: guaranteed by the caller of new_in_place
+            Init::InPlace(f) => unsafe { self.data.get_or_init_in_place(|slot| self.produce(|| f(slot))) },
+        };
+        poison.disarm();
+
+        #[cfg(feature = "async")]
+        wait::wake_all();
+        value
+    }
+
+    //runs an initializer the way every initialization path does, timed against the budget and
+    //traced
+    #[inline(always)]
+    fn produce<R>(&self, f: impl FnOnce() -> R) -> R {
+        #[cfg(feature = "instrumented")]
+        return instrument::timed::<T, _>(self.budget, || self.traced(f));
+        #[cfg(not(feature = "instrumented"))]
+        self.traced(f)
+    }
+
+    //runs the initializer inside a span, reports how long it took, and records how the global
+    //was initialized
+    #[inline(always)]
+    fn traced<R>(&self, f: impl FnOnce() -> R) -> R {
+        #[cfg(feature = "observability")]
+        let f = || {
+            let value = f();
+            self.state.record(false);
+            value
+        };
+        #[cfg(feature = "tracing")]
+        {
+            let type_name = core::any::type_name::<T>();
+            let name = self.name.unwrap_or(type_name);
+            let thread = std::thread::current();
+            let thread = thread.name().unwrap_or("<unnamed>");
+            let span = tracing::info_span!(target: "global_static", "global_init", name, type_name, thread);
+            let _span = span.enter();
+            let start = std::time::Instant::now();
+            let value = f();
+            let duration_us = if cfg!(feature = "deterministic") { 0 } else { start.elapsed().as_micros() as u64 };
+            tracing::info!(target: "global_static", name, type_name, thread, duration_us, "global initialized");
+            value
+        }
+        #[cfg(not(feature = "tracing"))]
+        f()
+    }
+
+    ///Drops the value inside the global, for explicit shutdown of globals that own resources
+    ///which need flushing or closing. Does nothing if the global is not initialized, or if its
+    ///backend doesn't support it. With the `dtor` feature this is done automatically at exit
+    ///for registered globals.
+    ///```rust
+    ///# use global_static::Global;
+    ///# use std::{io::{BufWriter, Write}, sync::Mutex};
+    ///static LOG: Global<Mutex<BufWriter<std::io::Stderr>>> =
+    ///    Global::new(|| Mutex::new(BufWriter::new(std::io::stderr())));
+    ///
+    ///writeln!(LOG.lock().unwrap(), "shutting down").unwrap();
+    /////safety: nothing uses the log after this
+    ///unsafe { LOG.deinit() };
+    ///```
+    ///
+    ///# Safety
+    ///No references to the value may be alive, and the global must not be accessed or
+    ///deinitialized again afterwards.
+    pub unsafe fn deinit(&'static self) {
+        self.data.deinit()
+    }
+
+    ///Takes the value out of the global during shutdown, so it can be consumed by value, such as
+    ///a buffered writer that has to be flushed with `into_inner`, rather than only being dropped.
+    ///The global is then skipped by [`deinit`](Global::deinit) and the `dtor` feature's exit
+    ///pass, so the value is never dropped twice. Returns `None` if the global is not initialized, its
+    ///value was already taken or dropped, or its backend doesn't support it.
+    ///```rust
+    ///# use global_static::Global;
+    ///# use std::{io::{BufWriter, Write}, sync::Mutex};
+    ///static LOG: Global<Mutex<BufWriter<Vec<u8>>>> = Global::new(|| Mutex::new(BufWriter::new(Vec::new())));
+    ///
+    ///writeln!(LOG.lock().unwrap(), "shutting down").unwrap();
+    /////safety: nothing uses the log after this
+    ///let log = unsafe { LOG.take_at_exit() }.unwrap();
+    ///let bytes = log.into_inner().unwrap().into_inner().unwrap();
+    ///assert_eq!(bytes, b"shutting down\n");
+    ///assert!(unsafe { LOG.take_at_exit() }.is_none());
+    ///```
+    ///
+    ///# Safety
+    ///No references to the value may be alive, and the global must not be accessed afterwards.
+    pub unsafe fn take_at_exit(&'static self) -> Option<T> {
+        self.data.take()
+    }
+
+    ///Takes the value out of a global that isn't a static, such as one owned by a struct, leaving
+    ///it uninitialized, as if it had just been constructed. Returns `None` if it wasn't
+    ///initialized, or its backend doesn't support this. The next access runs the initializer
+    ///again.
+    ///
+    ///Globals constructed with [`with_value`](Global::with_value) have no initializer to run
+    ///again, so their value stays in place and `None` is returned; use
+    ///[`into_inner`](Global::into_inner) to get it out.
+    ///```rust
+    ///# use global_static::Global;
+    ///let mut conn = Global::<String>::new(|| "connection".to_owned());
+    ///assert_eq!(conn.take(), None);
+    ///conn.init();
+    ///assert_eq!(conn.take().as_deref(), Some("connection"));
+    ///assert!(conn.get().is_none());
+    ///```
+    pub fn take(&mut self) -> Option<T> {
+        if matches!(self.f, Init::Preset) {
+            return None;
+        }
+        self.take_value()
+    }
+
+    fn take_value(&mut self) -> Option<T> {
+        //safety: `&mut self` rules out other references to the value, and the backend is
+        //replaced, so the emptied value is never read
+        let value = unsafe { self.data.take() }?;
+        self.data = B::EMPTY;
+        #[cfg(feature = "observability")]
+        self.state.forget();
+        Some(value)
+    }
+
+    ///Consumes the global, returning its value, or `None` if it wasn't initialized.
+    ///```rust
+    ///# use global_static::Global;
+    ///let conn = Global::<String>::new(|| "connection".to_owned());
+    ///conn.init();
+    ///assert_eq!(conn.into_inner().as_deref(), Some("connection"));
+    ///```
+    pub fn into_inner(mut self) -> Option<T> {
+        self.take_value()
+    }
+
+    ///The memory the global's value is expected to take once initialized, or 0 if it already
+    ///is. This is the size of `T`, which is reserved in the static but usually not backed by
+    ///memory until it is written, plus any heap memory given to
+    ///[`estimate_heap`](Global::estimate_heap).
+    ///```rust
+    ///# use global_static::Global;
+    ///static TABLE: Global<[u64; 512]> = Global::new(|| [0; 512]);
+    ///assert_eq!(TABLE.uninit_size(), 4096);
+    ///TABLE.init();
+    ///assert_eq!(TABLE.uninit_size(), 0);
+    ///```
+    pub fn uninit_size(&self) -> usize {
+        if self.data.get().is_some() {
+            return 0;
+        }
+        #[cfg(feature = "observability")]
+        let heap = self.state.estimate();
+        #[cfg(not(feature = "observability"))]
+        let heap = 0;
+        core::mem::size_of::<T>() + heap
+    }
+
+    ///Sets how much heap memory the value is expected to own once initialized, such as the
+    ///buffer of a large `Vec`, which is counted by [`uninit_size`](Global::uninit_size) and
+    ///[`forecast`].
+    #[cfg(feature = "observability")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "observability")))]
+    pub fn estimate_heap(&self, bytes: usize) {
+        self.state.set_estimate(bytes);
+    }
+
+    ///How and when the global was initialized, or `None` if it hasn't been, or was constructed
+    ///with [`with_value`](Global::with_value) and never needed initializing.
+    ///```rust
+    ///# use global_static::{Global, InitMode};
+    ///static PORT: Global<u16> = Global::new(|| 8080);
+    ///
+    ///assert!(PORT.initialized().is_none());
+    ///PORT.init();
+    ///assert_eq!(PORT.initialized().unwrap().mode, InitMode::Lazy);
+    ///assert!(PORT.is_lazily_initialized() && !PORT.is_ctor_initialized());
+    ///```
+    #[cfg(feature = "observability")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "observability")))]
+    pub fn initialized(&self) -> Option<Initialized> {
+        self.state.get()
+    }
+
+    ///Whether the global was initialized by a generated ctor, or by `init_all`. See
+    ///[`InitMode::Ctor`].
+    #[cfg(feature = "observability")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "observability")))]
+    pub fn is_ctor_initialized(&self) -> bool {
+        self.initialized().is_some_and(|i| i.mode == InitMode::Ctor)
+    }
+
+    ///Whether the global was initialized by an access after startup.
+    #[cfg(feature = "observability")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "observability")))]
+    pub fn is_lazily_initialized(&self) -> bool {
+        self.initialized().is_some_and(|i| i.mode == InitMode::Lazy)
+    }
+
+    ///Checks whether the global has been initialized without blocking or initializing it.
+    ///```rust
+    ///# use global_static::Global;
+    ///# use std::task::Poll;
+    ///static NUM: Global<u8> = Global::new(|| 1);
+    ///assert_eq!(NUM.try_wait(), Poll::Pending);
+    ///NUM.init();
+    ///assert_eq!(NUM.try_wait(), Poll::Ready(&1));
+    ///```
+    pub fn try_wait(&self) -> Poll<&T> {
+        match self.get() {
+            Some(v) => Poll::Ready(v),
+            None => Poll::Pending,
+        }
+    }
+}
+
+//marks a global as poisoned if initialization unwinds
+struct Poison<'a>(&'a AtomicBool);
+
+impl Poison<'_> {
+    fn disarm(self) {
+        self.0.store(false, Ordering::Release);
+        core::mem::forget(self);
+    }
+}
+
+impl Drop for Poison<'_> {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+///Error returned when accessing a global whose initializer panicked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Poisoned;
+
+impl Display for Poisoned {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("global is poisoned: its initializer panicked")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Poisoned {}
+
+///Error returned when changing a global that has already been initialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlreadyInit;
+
+impl Display for AlreadyInit {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("global is already initialized")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AlreadyInit {}
+
+#[cfg(feature = "std")]
+impl<T: Send + 'static, B: OnceBackend<T>> Global<T, B> {
+    ///Lets the global be initialized on the [init executor](executor) when it is first accessed
+    ///from a latency-sensitive thread. Globals initialized in place are always initialized on
+    ///the accessing thread.
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub const fn offload(mut self) -> Self {
+        self.offload = Some(executor::run::<T>);
+        self
+    }
+}
+
+impl<T: Clone, B: OnceBackend<T>> Global<T, B> {
+    ///Returns a clone of the value inside the global, initializing it if needed.
+    ///```rust
+    ///# use global_static::Global;
+    ///static NAME: Global<String> = Global::new(|| "app".to_owned());
+    ///let owned: String = NAME.get_cloned();
+    ///```
+    pub fn get_cloned(&self) -> T {
+        self.force().clone()
+    }
+
+    ///Returns a clone of the value inside the global without initializing it.
+    pub fn try_get_cloned(&self) -> Option<T> {
+        self.get().cloned()
+    }
+}
+
+impl<T> Global<T> {
+    ///Constructs a new global that already holds `value`, for values that can be built in const
+    ///contexts. Accesses never run an initializer, but the type stays the same as for lazily
+    ///initialized globals, so call sites don't change when an initializer becomes const.
+    ///```rust
+    ///# use global_static::Global;
+    ///static LIMITS: Global<[u32; 3]> = Global::with_value([16, 256, 4096]);
+    ///
+    ///assert!(LIMITS.get().is_some());
+    ///assert_eq!(LIMITS[2], 4096);
+    ///```
+    pub const fn with_value(value: T) -> Self {
+        Self::from_init(Init::Preset, DefaultBackend::with_value(value))
+    }
+}
+
+fn stub<T>() -> T {
+    panic!("`{}` is a stub, since the feature it needs is disabled", core::any::type_name::<T>())
+}
+
+impl<T: Default, B: OnceBackend<T>> Global<T, B> {
+    ///Constructs a new global, using the [`Default`] implementation for `T` as the initializer.
+    //cant use trait cus not const
+    #[allow(clippy::should_implement_trait)]
+    pub const fn default() -> Self {
+        Self::new(T::default)
+    } 
+}
+
+#[cfg(not(feature = "no-panic"))]
+impl<T, B: OnceBackend<T>> core::ops::Deref for Global<T, B> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.force()
+    }
+}
+
+impl<T: Debug, B: OnceBackend<T>> Debug for Global<T, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.force())
+    }
+}
+impl<T: Display, B: OnceBackend<T>> Display for Global<T, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.force())
+    }
+}
+
+
+//kept apart from the other tests, so it also runs without `std`
+#[cfg(test)]
+mod size {
+    use core::mem::size_of;
+
+    use super::*;
+
+    //fields for optional features only exist when they are enabled, so a global is never
+    //larger than what the enabled features need. fields are laid out without padding between
+    //them, so the global is the sum of its fields rounded up to its alignment, and every field
+    //is counted here rather than compared against a struct that pads the same way
+    #[test]
+    fn global() {
+        let mut fields = size_of::<Init<u64>>()
+            + size_of::<AtomicPtr<()>>()
+            + size_of::<AtomicBool>()
+            + size_of::<DefaultBackend<u64>>();
+        if cfg!(feature = "instrumented") {
+            fields += size_of::<Option<Duration>>();
+        }
+        if cfg!(debug_assertions) {
+            fields += size_of::<Option<(&str, fn(&u64) -> bool)>>();
+        }
+        if cfg!(feature = "std") {
+            fields += size_of::<Option<fn(fn() -> u64) -> Option<u64>>>();
+        }
+        #[cfg(all(feature = "std", debug_assertions))]
+        {
+            fields += size_of::<Option<access::Access>>();
+        }
+        #[cfg(feature = "observability")]
+        {
+            fields += size_of::<std::sync::Mutex<Option<Initialized>>>() + size_of::<usize>();
+        }
+        if cfg!(feature = "tracing") {
+            fields += size_of::<Option<&str>>();
+        }
+        let align = core::mem::align_of::<Global<u64>>();
+        assert_eq!(size_of::<Global<u64>>(), fields.div_ceil(align) * align);
+    }
+
+    //without `observability`, the registry, startup records and the type names they report
+    //aren't in the binary at all, rather than being present but unused
+    #[test]
+    #[cfg(all(feature = "std", not(feature = "observability")))]
+    fn observability_compiled_out() {
+        struct ProbeValue;
+        static PROBE: Global<ProbeValue> = Global::new(|| ProbeValue);
+        PROBE.init();
+        let _ = PROBE.set(ProbeValue);
+
+        let exe = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let contains = |needle: &str| exe.windows(needle.len()).any(|w| w == needle.as_bytes());
+        assert!(contains(std::hint::black_box("global-static-size-probe")));
+        //reversed, so the needles themselves don't end up in the binary
+        let mut needles = vec!["DERETSIGER", "SSAP_PUTRATS", "gol_putrats_tes", ":\"su_latot\","];
+        //debug checks name the type of the global in their messages
+        if !cfg!(debug_assertions) {
+            needles.push("eulaVeborP::tuo_delipmoc_ytilibavresbo::ezis::citats_labolg");
+        }
+        for needle in needles {
+            let needle: String = needle.chars().rev().collect();
+            assert!(!contains(&needle), "`{needle}` is linked in");
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std", not(feature = "no-panic")))]
+mod tests {
+    use std::ops::Add;
+
+    use super::*;
+    static TEST: super::Global<u8> = super::Global::new(|| 5);
+
+    #[test]
+    fn it_works() {
+        assert_eq!(TEST.add(1), 6);
+        assert_eq!(*TEST, 5);
+    }
+
+    use std::sync::OnceLock;
+
+    struct LoudCell<T>(OnceLock<T>, std::sync::atomic::AtomicUsize);
+    impl<T> OnceBackend<T> for LoudCell<T> {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const EMPTY: Self = LoudCell(OnceLock::new(), std::sync::atomic::AtomicUsize::new(0));
+        fn get(&self) -> Option<&T> { self.0.get() }
+        fn set(&self, value: T) -> Result<(), T> { self.0.set(value) }
+        fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &T {
+            self.1.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            self.0.get_or_init(f)
+        }
+    }
+
+    #[test]
+    fn custom_backend() {
+        static LOUD: Global<u8, LoudCell<u8>> = Global::new(|| 7);
+        assert!(LOUD.get().is_none());
+        assert_eq!(*LOUD, 7);
+        assert_eq!(*LOUD, 7);
+        assert_eq!(LOUD.data.1.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn wait_ready() {
+        use std::{future::Future, pin::pin, sync::Arc, task::{Context, Wake}};
+        struct Flag(std::sync::atomic::AtomicBool);
+        impl Wake for Flag {
+            fn wake(self: Arc<Self>) { self.0.store(true, std::sync::atomic::Ordering::SeqCst) }
+        }
+        static LATE: Global<u8> = Global::new(|| 9);
+
+        let flag = Arc::new(Flag(false.into()));
+        let waker = flag.clone().into();
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = pin!(LATE.wait_ready());
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        std::thread::spawn(|| LATE.init()).join().unwrap();
+        assert!(flag.0.load(std::sync::atomic::Ordering::SeqCst));
+        assert_eq!(fut.poll(&mut cx), Poll::Ready(&9));
+    }
+
+    #[test]
+    fn in_place() {
+        unsafe fn fill(slot: &mut MaybeUninit<[u32; 1024]>) {
+            let ptr = slot.as_mut_ptr() as *mut u32;
+            for i in 0..1024 {
+                ptr.add(i).write(i as u32);
+            }
+        }
+        static TABLE: Global<[u32; 1024]> = unsafe { Global::new_in_place(fill) };
+        static LOUD_TABLE: Global<[u32; 1024], LoudCell<[u32; 1024]>> = unsafe { Global::new_in_place(fill) };
+        assert_eq!(TABLE[1023], 1023);
+        assert_eq!(*TABLE, *LOUD_TABLE);
+    }
+
+    #[test]
+    fn prefetch_initializes() {
+        static BIG: Global<[u16; 10_000]> = Global::new(|| [3; 10_000]);
+        static EMPTY: Global<()> = Global::new(|| ());
+        assert_eq!(BIG.prefetch()[9_999], 3);
+        assert!(BIG.get().is_some());
+        EMPTY.prefetch();
+    }
+
+    #[test]
+    fn injected() {
+        static PORT: Global<u16> = Global::new(|| 80);
+        static NAME: Global<&str> = Global::new(|| "default");
+        assert_eq!(PORT.set(8080), Ok(()));
+        assert_eq!(PORT.set(9090), Err(9090));
+        assert_eq!(*PORT, 8080);
+        assert_eq!(*NAME.get_or_init(|| "injected"), "injected");
+        assert_eq!(*NAME, "injected");
+    }
+
+    #[test]
+    #[cfg(feature = "instrumented")]
+    fn injected_instrumented() {
+        use std::time::Duration;
+        struct Injected(u8);
+        static INJECTED: Global<Injected> = Global::<Injected>::new(|| Injected(0))
+            .with_budget(Duration::from_secs(60));
+
+        assert_eq!(INJECTED.get_or_init(|| Injected(1)).0, 1);
+        let record = instrument::records().into_iter().find(|r| r.type_name.ends_with("::Injected")).unwrap();
+        assert_eq!(record.budget, Some(Duration::from_secs(60)));
+        assert_eq!(INJECTED.initialized().unwrap().mode, InitMode::Lazy);
+    }
+
+    #[test]
+    fn cloned() {
+        static LIST: Global<Vec<u8>> = Global::new(|| vec![1, 2]);
+        assert_eq!(LIST.try_get_cloned(), None);
+        assert_eq!(LIST.get_cloned(), [1, 2]);
+        assert_eq!(LIST.try_get_cloned(), Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn concurrent_first_access() {
+        use std::sync::atomic::AtomicUsize;
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+        static SLOW: Global<u64> = Global::new(|| {
+            RUNS.fetch_add(1, Ordering::Relaxed);
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            7
+        });
+        let barrier = std::sync::Barrier::new(8);
+        let addrs: Vec<usize> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| s.spawn(|| { barrier.wait(); SLOW.force() as *const u64 as usize }))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(RUNS.load(Ordering::Relaxed), 1);
+        assert!(addrs.iter().all(|&a| a == addrs[0]));
+    }
+
+    #[test]
+    fn map_from_threads() {
+        static MAP: GlobalMap<u32, u32> = GlobalMap::new();
+        std::thread::scope(|s| {
+            for t in 0..4 {
+                s.spawn(move || (0..100).for_each(|i| { MAP.insert(t * 100 + i, i); }));
+            }
+        });
+        assert_eq!(MAP.len(), 400);
+        MAP.upsert(5, || 0, |v| *v += 10);
+        assert_eq!(MAP.get_cloned(&5), Some(15));
+        MAP.retain(|k, _| k % 2 == 0);
+        let mut sum = 0;
+        MAP.for_each(|k, _| sum += k);
+        assert_eq!(sum, (0..400).filter(|k| k % 2 == 0).sum::<u32>());
+
+        let metrics = MAP.metrics();
+        assert_eq!(metrics.len, 200);
+        assert_eq!(metrics.writes, 402);
+        assert!(metrics.shards.is_power_of_two());
+    }
+
+    #[test]
+    fn dyn_captures() {
+        static SUM: GlobalDyn<u32> = GlobalDyn::empty();
+        let base = 40;
+        assert!(SUM.set_init(move || base + 1).is_ok());
+        assert!(SUM.set_init(move || base + 2).is_ok());
+        assert_eq!(*SUM, 42);
+        assert!(SUM.set_init(|| 0).is_err());
+
+        let local = GlobalDyn::new(move || vec![base]);
+        assert_eq!(local.get(), None);
+        assert_eq!(*local, [40]);
+    }
+
+    #[test]
+    fn dyn_releases_captures() {
+        let buffer = std::sync::Arc::new(vec![0u8; 64]);
+        let captured = buffer.clone();
+        let global = GlobalDyn::new(move || captured.len());
+        assert!(!global.initializer_dropped());
+        assert_eq!(std::sync::Arc::strong_count(&buffer), 2);
+
+        assert_eq!(*global, 64);
+        assert!(global.initializer_dropped());
+        assert_eq!(std::sync::Arc::strong_count(&buffer), 1);
+    }
+
+    #[test]
+    fn try_global_stores_error() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        static CALLS: AtomicU32 = AtomicU32::new(0);
+        static CONFIG: TryGlobal<u32> = TryGlobal::new(|| {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            Err(Error::new("CONFIG", ErrorKind::Validation("empty".to_owned())))
+        });
+
+        assert_eq!(CONFIG.try_init().unwrap_err().global(), "CONFIG");
+        assert!(CONFIG.try_get().is_err());
+        assert!(CONFIG.get_err().is_some());
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+        assert!(std::panic::catch_unwind(|| *CONFIG).is_err());
+    }
+
+    #[cfg(feature = "async")]
+    fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+        use std::{sync::Arc, task::{Context, Wake, Poll}, thread::Thread};
+        struct Unpark(Thread);
+        impl Wake for Unpark {
+            fn wake(self: Arc<Self>) { self.0.unpark() }
+        }
+        let waker = Arc::new(Unpark(std::thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = std::pin::pin!(fut);
+        loop {
+            match fut.as_mut().poll(&mut cx) {
+                Poll::Ready(v) => return v,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn poisoned_then_recovered() {
+        use std::sync::atomic::AtomicUsize;
+        static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+        static FLAKY: Global<usize> = Global::new(|| match ATTEMPTS.fetch_add(1, Ordering::SeqCst) {
+            0 => panic!("first attempt fails"),
+            n => n,
+        });
+
+        assert!(std::panic::catch_unwind(|| *FLAKY).is_err());
+        assert!(FLAKY.is_poisoned());
+        assert_eq!(FLAKY.try_get(), Err(Poisoned));
+        assert_eq!(*FLAKY, 1);
+        assert!(!FLAKY.is_poisoned());
+        assert_eq!(FLAKY.try_get(), Ok(&1));
+    }
+
+    #[test]
+    fn concurrent_panic_during_init() {
+        use std::sync::atomic::AtomicUsize;
+        static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+        static FLAKY: Global<usize> = Global::new(|| {
+            let n = ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            if n == 0 {
+                panic!("first attempt fails");
+            }
+            n
+        });
+
+        let results: Vec<_> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..8).map(|_| s.spawn(|| *FLAKY)).collect();
+            handles.into_iter().map(|h| h.join()).collect()
+        });
+        //only the thread that ran the failing attempt sees the panic, the rest share one value
+        assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+        assert!(results.iter().flatten().all(|&v| v == 1));
+        assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 2);
+        assert!(!FLAKY.is_poisoned());
+    }
+
+    #[test]
+    #[cfg(feature = "observability")]
+    fn startup_summary_json() {
+        use std::time::Duration;
+        static TRACKED: Global<[u8; 3]> = Global::new(|| [1, 2, 3]);
+        register(&TRACKED);
+        init_all();
+        assert!(startup_summary().records.iter().any(|r| r.type_name == "[u8; 3]"));
+
+        let record = |type_name, ms, failure: Option<&str>| StartupRecord {
+            type_name,
+            duration: Duration::from_millis(ms),
+            failure: failure.map(str::to_owned),
+        };
+        let summary = StartupSummary {
+            total: Duration::from_millis(7),
+            records: vec![record("a", 1, None), record("b", 5, Some("no \"config\""))],
+        };
+        assert_eq!(summary.to_json(), concat!(
+            r#"{"globals":2,"total_us":7000,"slowest":[{"global":"b","us":5000},{"global":"a","us":1000}],"#,
+            r#""failures":[{"global":"b","error":"no \"config\""}]}"#,
+        ));
+    }
+
+    #[test]
+    fn deinit_drops_value() {
+        use std::sync::atomic::AtomicUsize;
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        struct Handle;
+        impl Drop for Handle {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        static HANDLE: Global<Handle> = Global::new(|| Handle);
+        static UNUSED: Global<Handle> = Global::new(|| Handle);
+        HANDLE.init();
+        unsafe {
+            HANDLE.deinit();
+            UNUSED.deinit();
+        }
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn optional_global() {
+        static PRESENT: GlobalOpt<u8> = GlobalOpt::new(|| Some(1));
+        static ABSENT: GlobalOpt<u8> = GlobalOpt::new(|| None);
+        assert_eq!(PRESENT.peek(), None);
+        assert_eq!(PRESENT.get(), Some(&1));
+        assert_eq!(PRESENT.peek(), Some(Some(&1)));
+
+        assert!(!ABSENT.is_initialized());
+        assert!(!ABSENT.is_some());
+        assert!(ABSENT.is_initialized());
+        assert_eq!(ABSENT.peek(), Some(None));
+        assert_eq!(ABSENT.set(Some(2)), Err(Some(2)));
+    }
+
+    #[test]
+    fn mapped_and_zipped() {
+        use std::sync::atomic::AtomicUsize;
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+        static CONFIG: Global<Vec<&str>> = Global::new(|| vec!["/", "/users"]);
+        static ROUTES: MappedGlobal<Vec<&str>, usize> = CONFIG.map(|c| {
+            RUNS.fetch_add(1, Ordering::SeqCst);
+            c.len()
+        });
+        static DOUBLED: MappedGlobal<usize, usize> = ROUTES.map(|n| n * 2);
+        static PORT: Global<u16> = Global::new(|| 80);
+        static BOTH: ZipGlobal<usize, u16> = DOUBLED.zip(&PORT);
+        static SUM: MappedGlobal<(&usize, &u16), usize> = BOTH.map(|(a, b)| **a + **b as usize);
+
+        assert!(ROUTES.get().is_none());
+        assert_eq!(*SUM, 84);
+        assert!(CONFIG.get().is_some());
+        assert_eq!(*ROUTES, 2);
+        assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn registry_by_type_and_name() {
+        use std::sync::atomic::AtomicUsize;
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+        static REGISTRY: GlobalRegistry = GlobalRegistry::new();
+
+        assert!(REGISTRY.get::<u8>().is_none());
+        assert!(REGISTRY.register(|| {
+            RUNS.fetch_add(1, Ordering::SeqCst);
+            5u8
+        }));
+        assert!(!REGISTRY.register(|| 6u8));
+        assert!(REGISTRY.register_named("port", || REGISTRY.get::<u8>().map_or(0, |n| *n as u16 * 2)));
+        assert!(!REGISTRY.register_named("port", || 0u16));
+
+        assert_eq!(RUNS.load(Ordering::SeqCst), 0);
+        assert_eq!(REGISTRY.get_named::<u16>("port"), Some(&10));
+        assert_eq!(REGISTRY.get::<u8>(), Some(&5));
+        assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+        assert!(REGISTRY.get_named::<u8>("port").is_none());
+        assert!(REGISTRY.contains::<u8>() && !REGISTRY.contains::<u16>());
+        assert_eq!(REGISTRY.names(), ["port"]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn ensure_with_catches_writes() {
+        use std::sync::atomic::AtomicU8;
+        static LEVEL: Global<AtomicU8> = Global::<AtomicU8>::new(|| AtomicU8::new(1))
+            .ensure_with("at most 3", |v| v.load(Ordering::SeqCst) <= 3);
+
+        LEVEL.store(3, Ordering::SeqCst);
+        LEVEL.store(4, Ordering::SeqCst);
+        let err = std::panic::catch_unwind(|| LEVEL.load(Ordering::SeqCst)).unwrap_err();
+        assert_eq!(err.downcast_ref::<String>().unwrap(), "`core::sync::atomic::AtomicU8` failed its `at most 3` check");
+    }
+
+    #[test]
+    #[cfg(feature = "init-cache")]
+    fn cached_between_runs() {
+        use std::sync::atomic::AtomicUsize;
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+        fn table() -> Vec<u32> {
+            RUNS.fetch_add(1, Ordering::SeqCst);
+            (0..100).collect()
+        }
+        static FIRST_RUN: GlobalCached<Vec<u32>> = GlobalCached::new("test-table", "1", table);
+        static NEXT_RUN: GlobalCached<Vec<u32>> = GlobalCached::new("test-table", "1", table);
+        static NEW_VERSION: GlobalCached<Vec<u32>> = GlobalCached::new("test-table", "2", table);
+
+        FIRST_RUN.invalidate().unwrap();
+        NEW_VERSION.invalidate().unwrap();
+        assert_eq!(FIRST_RUN[99], 99);
+        assert!(!FIRST_RUN.loaded_from_cache());
+        assert_eq!(*NEXT_RUN, *FIRST_RUN);
+        assert!(NEXT_RUN.loaded_from_cache());
+        assert!(!NEW_VERSION.loaded_from_cache() && NEW_VERSION.len() == 100);
+        assert_eq!(RUNS.load(Ordering::SeqCst), 2);
+
+        FIRST_RUN.invalidate().unwrap();
+        NEW_VERSION.invalidate().unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "handoff")]
+    fn handoff_to_next_process() {
+        static OLD: GlobalHandoff<Vec<u32>> = GlobalHandoff::new("test-handoff", "1", || vec![1, 2]);
+        static UNUSED: GlobalHandoff<u8> = GlobalHandoff::new("test-unused", "1", || 0);
+
+        OLD.init();
+        //the next process is this test binary again, running only `handoff_inherited`
+        let mut cmd = std::process::Command::new(std::env::current_exe().unwrap());
+        cmd.args(["--exact", "tests::handoff_inherited", "--ignored"]);
+        Handoff::new().add(&OLD).unwrap().add(&UNUSED).unwrap().pass_to(&mut cmd).unwrap();
+        let (var, path) = cmd.get_envs().next().unwrap();
+        assert_eq!(var, HANDOFF_VAR);
+        let path = std::path::PathBuf::from(path.unwrap());
+
+        let out = cmd.output().unwrap();
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        assert!(out.status.success() && stdout.contains("1 passed"), "{stdout}");
+        assert!(!path.exists());
+        assert!(!OLD.inherited());
+    }
+
+    #[test]
+    #[cfg(feature = "handoff")]
+    #[ignore = "run by handoff_to_next_process as the next process"]
+    fn handoff_inherited() {
+        static NEW: GlobalHandoff<Vec<u32>> = GlobalHandoff::new("test-handoff", "1", Vec::new);
+        static NEW_VERSION: GlobalHandoff<Vec<u32>> = GlobalHandoff::new("test-handoff", "2", Vec::new);
+        //nothing to inherit when run on its own
+        if std::env::var_os(HANDOFF_VAR).is_none() {
+            return;
+        }
+
+        assert_eq!(*NEW, [1, 2]);
+        assert!(NEW.inherited());
+        assert!(std::env::var_os(HANDOFF_VAR).is_none());
+        assert!(NEW_VERSION.is_empty() && !NEW_VERSION.inherited());
+    }
+
+    #[cfg(all(feature = "ctor", not(feature = "ctor-fallback")))]
+    pub static CODECS: Global<std::collections::BTreeMap<&str, u8>> = Global::new_from_iter();
+    #[cfg(all(feature = "ctor", not(feature = "ctor-fallback")))]
+    contribute_to!(CODECS, || [("json", 1), ("cbor", 2)]);
+    #[cfg(all(feature = "ctor", not(feature = "ctor-fallback")))]
+    contribute_to!(CODECS, || Some(("json", 3)));
+
+    #[test]
+    #[cfg(all(feature = "ctor", not(feature = "ctor-fallback")))]
+    fn contributions() {
+        //later contributions win for maps, since they are collected in source order
+        assert_eq!(CODECS["json"], 3);
+        assert_eq!(CODECS.len(), 2);
+        assert!(CODECS.contribute(|| vec![("toml", 4)]).is_err());
+    }
+
+    #[test]
+    fn scoped_reset() {
+        use std::sync::atomic::AtomicU32;
+        static LIVE: AtomicU32 = AtomicU32::new(0);
+        struct Tracked(u32);
+        impl Drop for Tracked {
+            fn drop(&mut self) { LIVE.fetch_sub(1, Ordering::SeqCst); }
+        }
+        static COUNTER: ScopedGlobal<AtomicU32> = ScopedGlobal::new(|| AtomicU32::new(0));
+        static TRACKED: ScopedGlobal<Tracked> = ScopedGlobal::new(|| Tracked(LIVE.fetch_add(1, Ordering::SeqCst)));
+
+        let total = GlobalScope::run(|scope| {
+            std::thread::scope(|s| {
+                for _ in 0..4 {
+                    s.spawn(|| COUNTER.get(scope).fetch_add(1, Ordering::SeqCst));
+                }
+            });
+            assert_eq!(TRACKED.get(scope).0, 0);
+            COUNTER.get(scope).load(Ordering::SeqCst)
+        });
+        assert_eq!(total, 4);
+        assert!(!COUNTER.is_initialized());
+
+        //values are dropped even if the scope panics
+        let res = std::panic::catch_unwind(|| GlobalScope::run(|scope| {
+            TRACKED.get(scope);
+            panic!("test failed");
+        }));
+        assert!(res.is_err());
+        assert_eq!(LIVE.load(Ordering::SeqCst), 0);
+        GlobalScope::run(|scope| assert_eq!(COUNTER.get(scope).load(Ordering::SeqCst), 0));
+    }
+
+    #[test]
+    fn library_version_check() {
+        static OLD: GlobalLibrary = GlobalLibrary::new("libold", || panic!("initialized despite the check"))
+            .teardown(|| panic!("torn down without being initialized"))
+            .version_check(|| Err("version 0.1 is too old".to_owned()));
+        let err = OLD.try_init().unwrap_err();
+        assert_eq!(err.global(), "libold");
+        assert!(matches!(err.kind(), ErrorKind::Validation(msg) if msg == "version 0.1 is too old"));
+        assert!(OLD.try_init().is_err() && !OLD.is_initialized());
+        unsafe { OLD.deinit() };
+    }
+
+    #[test]
+    fn from_env() {
+        static MISSING: Global<u16> = Global::from_env("GLOBAL_STATIC_TEST_MISSING");
+
+        assert_eq!(config::parse_var::<u16>("GLOBAL_STATIC_TEST_PORT", Ok("8080".to_owned())), 8080);
+        let err = std::panic::catch_unwind(|| config::parse_var::<u16>("GLOBAL_STATIC_TEST_PORT", Ok("http".to_owned())))
+            .unwrap_err();
+        assert!(err.downcast_ref::<String>().unwrap().contains("is not a valid `u16`"));
+        let err = std::panic::catch_unwind(|| *MISSING).unwrap_err();
+        assert!(err.downcast_ref::<String>().unwrap().contains("`GLOBAL_STATIC_TEST_MISSING`"));
+        assert!(MISSING.is_poisoned());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_config() {
+        use std::collections::BTreeMap;
+        const PATH: &str = "target/global-static-test-config.json";
+        static ROUTES: Global<BTreeMap<String, u16>> = Global::from_config(PATH);
+
+        std::fs::write(PATH, r#"{"api": 8080, "web": 80}"#).unwrap();
+        assert_eq!(ROUTES["api"], 8080);
+        assert_eq!(serde_json::to_string(&ROUTES).unwrap(), r#"{"api":8080,"web":80}"#);
+        std::fs::remove_file(PATH).unwrap();
+    }
+
+    #[test]
+    fn offloaded_init() {
+        use std::{sync::{Condvar, Mutex}, time::Duration};
+        static RELEASED: Mutex<bool> = Mutex::new(false);
+        static RELEASE: Condvar = Condvar::new();
+        static THREAD: Global<Option<String>> = Global::<Option<String>>::new(|| {
+            std::thread::current().name().map(str::to_owned)
+        }).offload();
+        //held on the init thread until the access below has timed out
+        static SLOW: Global<u8> = Global::<u8>::new(|| {
+            drop(RELEASE.wait_while(RELEASED.lock().unwrap(), |released| !*released).unwrap());
+            1
+        }).offload();
+
+        executor::spawn_init_thread();
+        {
+            let _guard = executor::latency_sensitive(Duration::from_secs(60));
+            assert_eq!(THREAD.as_deref(), Some("global-static-init"));
+        }
+
+        let guard = executor::latency_sensitive(Duration::ZERO);
+        let err = std::panic::catch_unwind(|| *SLOW).unwrap_err();
+        assert!(err.downcast_ref::<String>().unwrap().contains("timed out"));
+        drop(guard);
+        *RELEASED.lock().unwrap() = true;
+        RELEASE.notify_all();
+        assert_eq!(*SLOW, 1);
+    }
+
+    #[test]
+    fn family_lru_eviction() {
+        static LENGTHS: GlobalFamily<&str, usize> =
+            GlobalFamily::<&str, usize>::new(|k| k.len()).with_capacity(2);
+
+        let a = LENGTHS.get(&"a");
+        assert_eq!(*LENGTHS.get(&"bb"), 2);
+        assert!(std::sync::Arc::ptr_eq(&a, &LENGTHS.get(&"a")));
+        //"bb" is now the least recently used
+        assert_eq!(*LENGTHS.get(&"ccc"), 3);
+        assert!(LENGTHS.contains_key(&"a") && !LENGTHS.contains_key(&"bb"));
+        assert_eq!(*a, 1);
+
+        assert_eq!(LENGTHS.metrics(), FamilyMetrics { hits: 1, misses: 3, evictions: 1, len: 2, capacity: Some(2) });
+        assert_eq!(LENGTHS.remove(&"a").as_deref(), Some(&1));
+        assert_eq!(LENGTHS.peek(&"a"), None);
+    }
+
+    #[test]
+    fn set_initializer() {
+        static CODEC: Global<u8> = Global::new(|| 1);
+        CODEC.set_initializer(|| 2).unwrap();
+        CODEC.set_initializer(|| 3).unwrap();
+        assert_eq!(*CODEC, 3);
+        assert_eq!(CODEC.set_initializer(|| 4), Err(AlreadyInit));
+        assert_eq!(*CODEC, 3);
+    }
+
+    #[test]
+    #[cfg(feature = "test-support")]
+    fn override_scoped() {
+        static LEVEL: Global<u8> = Global::new(|| 1);
+        {
+            let _outer = LEVEL.override_scoped(2);
+            assert_eq!(*LEVEL, 2);
+            {
+                let _inner = LEVEL.override_scoped(3);
+                assert_eq!(*LEVEL, 3);
+                std::thread::spawn(|| assert_eq!(*LEVEL, 1)).join().unwrap();
+            }
+            assert_eq!(*LEVEL, 2);
+        }
+        assert_eq!(*LEVEL, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn async_global_once() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        static RUNS: AtomicU32 = AtomicU32::new(0);
+        async fn make() -> u32 {
+            RUNS.fetch_add(1, Ordering::SeqCst);
+            //yield once so other tasks have to wait
+            let mut yielded = false;
+            std::future::poll_fn(|cx| {
+                if yielded { return Poll::Ready(()); }
+                yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }).await;
+            7
+        }
+        async_static! {
+            POOL: u32 = make();
+        };
+
+        assert_eq!(POOL.try_get(), None);
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| assert_eq!(*block_on(POOL.get()), 7));
+            }
+        });
+        assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn staged_recovers() {
+        use std::sync::atomic::AtomicU32;
+        static ATTEMPTS: AtomicU32 = AtomicU32::new(0);
+        static STAGED: GlobalStaged<u32, u32> = GlobalStaged::new(|| 2, |&basic| {
+            //the background attempt fails, and the awaiting task tries again
+            if ATTEMPTS.fetch_add(1, Ordering::SeqCst) == 0 {
+                panic!("index shard unavailable");
+            }
+            basic * 10
+        });
+
+        assert_eq!(*STAGED.get_basic(), 2);
+        assert_eq!(STAGED.try_get_full(), None);
+        assert_eq!(*block_on(STAGED.get_full()), 20);
+        assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn async_global_cancel() {
+        use std::{future::Future, pin::pin, sync::atomic::{AtomicU32, Ordering}, task::{Context, Waker}};
+        static RUNS: AtomicU32 = AtomicU32::new(0);
+        //hangs on the first attempt, like a connection that never answers
+        static CONN: AsyncGlobal<u32> = AsyncGlobal::new(|| Box::pin(async {
+            if RUNS.fetch_add(1, Ordering::SeqCst) == 0 {
+                std::future::pending::<()>().await;
+            }
+            3
+        }));
+
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut hung = pin!(CONN.get_cancellable());
+        assert!(hung.as_mut().poll(&mut cx).is_pending());
+        assert!(CONN.cancel());
+        assert!(!CONN.cancel());
+        assert!(CONN.is_cancelled());
+        assert_eq!(hung.poll(&mut cx), Poll::Ready(Err(Cancelled)));
+
+        assert_eq!(*block_on(CONN.get()), 3);
+        assert!(!CONN.is_cancelled());
+        assert_eq!(RUNS.load(Ordering::SeqCst), 2);
+
+        //a result that arrives after the initialization was cancelled is not published
+        static LATE: AsyncGlobal<u32> = AsyncGlobal::new(|| Box::pin(async {
+            LATE.cancel();
+            4
+        }));
+        let mut late = pin!(LATE.get_cancellable());
+        assert!(late.as_mut().poll(&mut cx).is_pending());
+        assert!(LATE.is_cancelled() && LATE.try_get().is_none());
+        assert_eq!(late.poll(&mut cx), Poll::Ready(Err(Cancelled)));
+    }
+
+    #[test]
+    #[cfg(feature = "tracing")]
+    fn traced_init() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{field::{Field, Visit}, span, Event, Metadata, Subscriber};
+
+        #[derive(Default, Clone)]
+        struct Capture(Arc<Mutex<Vec<String>>>);
+        impl Visit for Capture {
+            fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+                self.0.lock().unwrap().push(format!("{}={value:?}", field.name()));
+            }
+        }
+        impl Subscriber for Capture {
+            fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+            fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+                span.record(&mut self.clone());
+                span::Id::from_u64(1)
+            }
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn event(&self, event: &Event<'_>) { event.record(&mut self.clone()) }
+            fn enter(&self, _: &span::Id) {}
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        static NAMED: Global<u8> = Global::<u8>::new(|| 1).named("named");
+        let capture = Capture::default();
+        tracing::subscriber::with_default(capture.clone(), || NAMED.init());
+        tracing::subscriber::with_default(capture.clone(), || NAMED.init());
+
+        let fields = capture.0.lock().unwrap().clone();
+        assert_eq!(fields.iter().filter(|f| *f == "name=\"named\"").count(), 2, "{fields:?}");
+        assert!(fields.contains(&"type_name=\"u8\"".to_owned()));
+        assert!(fields.iter().any(|f| f.starts_with("duration_us=")));
+        assert!(fields.iter().any(|f| f.starts_with("thread=")));
+    }
+
+    #[test]
+    fn global_str() {
+        static FROM_FILE: GlobalStr = GlobalStr::file(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"));
+        static FROM_ENV: GlobalStr = GlobalStr::env("CARGO_PKG_NAME");
+        static DEFAULTED: GlobalStr = GlobalStr::env_or("__GLOBAL_STATIC_UNSET", "fallback");
+
+        assert!(FROM_FILE.starts_with("workspace"));
+        assert!(!FROM_FILE.ends_with('\n'));
+        assert_eq!(FROM_ENV, "global-static");
+        assert_eq!(DEFAULTED, DEFAULTED);
+        assert_eq!("fallback", DEFAULTED);
+
+        static MISSING: GlobalStr = GlobalStr::file("/__global_static/missing");
+        let err = MISSING.try_as_str().unwrap_err();
+        assert_eq!(err.global(), "/__global_static/missing");
+        assert!(matches!(err.kind(), ErrorKind::Io(_)));
+        assert!(std::error::Error::source(err).is_some());
+        assert_eq!(MISSING.get(), None);
+    }
+
+    #[test]
+    fn unit_globals() {
+        use std::time::Duration;
+        static TIMEOUT: GlobalDuration = GlobalDuration::env_or("__GLOBAL_STATIC_UNSET", "1m 30s");
+        static LIMIT: GlobalSize = GlobalSize::new(|| "64 KiB".to_owned());
+        static BAD: GlobalSize = GlobalSize::env_or("__GLOBAL_STATIC_UNSET", "12 parsecs");
+
+        assert_eq!(*TIMEOUT, Duration::from_secs(90));
+        assert_eq!(LIMIT.bytes(), 65536);
+        assert_eq!(GlobalDuration::parse("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(GlobalDuration::parse("250ms"), Ok(Duration::from_millis(250)));
+        assert!(GlobalDuration::parse("5 fortnights").is_err());
+        assert_eq!(GlobalSize::parse("512MB"), Ok(512_000_000));
+        assert_eq!(GlobalSize::parse("2g"), Ok(2 << 30));
+        assert_eq!(GlobalSize::parse("100"), Ok(100));
+        assert!(GlobalSize::parse("0.5B").is_err());
+
+        let err = BAD.try_bytes().unwrap_err();
+        assert_eq!(err.global(), "__GLOBAL_STATIC_UNSET");
+        assert!(matches!(err.kind(), ErrorKind::Parse(_)));
+        assert!(err.to_string().contains("12 parsecs"));
+        assert_eq!(BAD.get(), None);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn access_restrictions() {
+        use access::{Access, Tag};
+        static BLOCKING: Global<u8> = Global::<u8>::new(|| 1).restrict_to(Access::Blocking);
+        static OFF_EXECUTOR: Global<u8> = Global::<u8>::builder().init(|| 2).restrict_to(Access::NotAsync).build();
+        static MAIN_ONLY: Global<u8> = Global::<u8>::new(|| 3).restrict_to(Access::MainThread);
+
+        //startup and diagnostics aren't accesses on behalf of the calling thread
+        assert!(!AnyGlobal::is_initialized(&MAIN_ONLY));
+        AnyGlobal::init(&MAIN_ONLY);
+        assert!(AnyGlobal::is_initialized(&MAIN_ONLY));
+        assert_eq!(AnyGlobal::uninit_size(&MAIN_ONLY), 0);
+        assert!(std::panic::catch_unwind(|| *MAIN_ONLY).is_err());
+
+        //test threads are untagged
+        assert_eq!(*OFF_EXECUTOR, 2);
+        assert!(std::panic::catch_unwind(|| *BLOCKING).is_err());
+        {
+            let _blocking = access::enter(Tag::Blocking);
+            assert_eq!(access::current_tag(), Some(Tag::Blocking));
+            assert_eq!(*BLOCKING, 1);
+        }
+        assert_eq!(access::current_tag(), None);
+
+        std::thread::spawn(|| {
+            access::mark_thread(Tag::Async);
+            let err = std::panic::catch_unwind(|| *OFF_EXECUTOR).unwrap_err();
+            let msg = err.downcast_ref::<String>().unwrap();
+            assert!(msg.contains("accessed from an async executor thread"), "{msg}");
+        }).join().unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn bind_future() {
+        use std::{future::poll_fn, sync::{Arc, Mutex}, task::{Poll, Waker}};
+        static FETCHED: Global<String> = Global::from_future();
+        static UNBOUND: Global<String> = Global::from_future();
+        static FAILING: Global<String> = Global::from_future();
+
+        let ready = Arc::new(Mutex::new((None::<String>, None::<Waker>)));
+        let fut = {
+            let ready = ready.clone();
+            poll_fn(move |cx| {
+                let mut ready = ready.lock().unwrap();
+                match ready.0.take() {
+                    Some(v) => Poll::Ready(v),
+                    None => {
+                        ready.1 = Some(cx.waker().clone());
+                        Poll::Pending
+                    },
+                }
+            })
+        };
+        FETCHED.bind(fut).unwrap();
+        assert_eq!(FETCHED.get(), None);
+        assert!(matches!(FETCHED.bind(async { String::new() }), Err(BindError::AlreadyInit)));
+
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            let mut ready = ready.lock().unwrap();
+            ready.0 = Some("fetched".to_owned());
+            if let Some(waker) = ready.1.take() {
+                waker.wake();
+            }
+        });
+        //blocks until the future is done instead of running the initializer
+        assert_eq!(*FETCHED, "fetched");
+        assert!(matches!(FETCHED.bind(async { String::new() }), Err(BindError::AlreadyInit)));
+        assert!(std::panic::catch_unwind(|| UNBOUND.len()).is_err());
+
+        FAILING.bind(async { panic!("config server unreachable") }).unwrap();
+        let err = std::panic::catch_unwind(|| FAILING.len()).unwrap_err();
+        let msg = err.downcast_ref::<String>().unwrap();
+        assert!(msg.contains("panicked: config server unreachable"), "{msg}");
+        assert!(FAILING.is_poisoned());
+        FAILING.bind(async { "retried".to_owned() }).unwrap();
+        assert_eq!(*FAILING, "retried");
+    }
+
+    #[test]
+    fn macro_version_check() {
+        const _: () = crate::__check_macro_version("0.0.0", MACRO_ABI);
+        let (msg, len) = version_mismatch("0.0.0");
+        let msg = std::str::from_utf8(&msg[..len]).unwrap();
+        assert!(msg.starts_with("global-static-singleton 0.0.0 is not compatible with global-static "));
+        assert!(msg.contains(env!("CARGO_PKG_VERSION")));
+    }
+
+    #[test]
+    fn swap_hooks() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        static SEEN: AtomicU32 = AtomicU32::new(0);
+        static VALUE: SwapGlobal<u32> = SwapGlobal::new(|| 1);
+
+        VALUE.on_swap(|old, new| {
+            SEEN.fetch_add(old * 10 + new, Ordering::SeqCst);
+        });
+        assert_eq!(*VALUE.swap(2), 1);
+        assert_eq!(SEEN.load(Ordering::SeqCst), 12);
+        VALUE.swap(3);
+        assert_eq!(SEEN.load(Ordering::SeqCst), 12 + 23);
+        assert_eq!(*VALUE.load(), 3);
+    }
+
+    #[test]
+    fn swap_rollback() {
+        static CONFIG: SwapGlobal<u32> = SwapGlobal::new(|| 1).with_history(2);
+        assert_eq!(CONFIG.version(), 0);
+        CONFIG.swap(2);
+        CONFIG.swap(3);
+        CONFIG.swap(4);
+        assert_eq!(CONFIG.version(), 3);
+        let history: Vec<_> = CONFIG.history().into_iter().map(|(v, val)| (v, *val)).collect();
+        assert_eq!(history, [(2, 3), (1, 2)]);
+
+        assert!(CONFIG.rollback(3).is_none());
+        assert_eq!(CONFIG.rollback(2).as_deref(), Some(&4));
+        assert_eq!(*CONFIG.load(), 2);
+        assert_eq!(CONFIG.version(), 4);
+        assert!(CONFIG.history().is_empty());
+    }
+
+    #[test]
+    fn swap_compare_and_swap() {
+        static CONFIG: SwapGlobal<u32> = SwapGlobal::new(|| 1).with_history(4);
+
+        //writers that all read version 0, only one of which can win
+        let winners: Vec<_> = std::thread::scope(|s| {
+            let writers: Vec<_> = (10..20).map(|v| s.spawn(move || CONFIG.compare_and_swap(0, v).is_ok())).collect();
+            writers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+        assert_eq!(winners.iter().filter(|&&won| won).count(), 1);
+        let (version, winner) = CONFIG.load_versioned();
+        assert_eq!(version, 1);
+
+        assert_eq!(CONFIG.compare_and_swap(0, 99), Err((1, 99)));
+        assert_eq!(*CONFIG.compare_and_swap(1, 2).unwrap(), *winner);
+        assert_eq!(CONFIG.load_versioned(), (2, std::sync::Arc::new(2)));
+        assert_eq!(CONFIG.history()[0], (1, winner));
+    }
+
+    #[test]
+    fn striped_counter() {
+        static HITS: GlobalStripedCounter = GlobalStripedCounter::new();
+        assert_eq!(std::mem::align_of_val(&HITS), 128);
+
+        std::thread::scope(|s| {
+            for i in 0..64 {
+                s.spawn(move || (0..100).for_each(|_| HITS.add(i)));
+            }
+        });
+        let total = (0..64).sum::<u64>() * 100;
+        assert_eq!(HITS.get(), total);
+        assert_eq!(HITS.take(), total);
+        assert_eq!(HITS.get(), 0);
+        HITS.increment();
+        assert_eq!(format!("{HITS:?}"), "1");
+
+        //counting from a thread-local destructor doesn't panic
+        struct CountOnExit;
+        impl Drop for CountOnExit {
+            fn drop(&mut self) { HITS.increment(); }
+        }
+        thread_local! { static ON_EXIT: CountOnExit = const { CountOnExit }; }
+        std::thread::spawn(|| ON_EXIT.with(|_| ())).join().unwrap();
+        assert_eq!(HITS.get(), 2);
+    }
+
+    #[test]
+    fn swap_store_snapshots() {
+        static PAIR: SwapGlobal<(u32, u32)> = SwapGlobal::new(|| (0, 0));
+        assert!(!PAIR.is_initialized());
+
+        let writer = std::thread::spawn(|| {
+            for i in 1..=1000 {
+                PAIR.store((i, i));
+            }
+        });
+        while !writer.is_finished() {
+            let pair = PAIR.load();
+            assert_eq!(pair.0, pair.1);
+        }
+        writer.join().unwrap();
+        assert_eq!(*PAIR.load(), (1000, 1000));
+        assert_eq!(PAIR.version(), 1000);
+    }
+
+    #[test]
+    fn fetched_refreshes_in_background() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::time::Duration;
+        static FETCHES: AtomicU32 = AtomicU32::new(0);
+        static TOKEN: GlobalFetched<u32> = GlobalFetched::new(
+            || FETCHES.fetch_add(1, Ordering::SeqCst), 
+            Duration::from_millis(10));
+
+        assert_eq!(TOKEN.age(), None);
+        assert_eq!(*TOKEN.get(), 0);
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(TOKEN.is_stale());
+        //stale value is served while refreshing
+        assert_eq!(*TOKEN.get(), 0);
+        while TOKEN.is_refreshing() || TOKEN.is_stale() {
+            std::thread::yield_now();
+        }
+        assert!(*TOKEN.get() >= 1);
+    }
+
+    #[repr(C)]
+    pub struct Point { x: i32, y: i32 }
+    unsafe impl FfiSafe for Point {}
+    static ORIGIN: Global<Point> = Global::new(|| Point { x: 1, y: 2 });
+    export_global! {
+        __global_static_test_origin: Point = ORIGIN;
+    }
+
+    #[test]
+    fn exported_accessor() {
+        let ptr = __global_static_test_origin();
+        assert_eq!(ptr, ORIGIN.freeze_layout());
+        let point = unsafe { &*ptr };
+        assert_eq!((point.x, point.y), (1, 2));
+    }
+
+    #[test]
+    #[cfg(feature = "ctor")]
     fn ctor_test() {
         ctor_static! { 
             THING: u32 = { 5 };
@@ -242,10 +2675,352 @@ mod tests {
     } 
 
     #[test]
-    #[cfg(feature = "singleton")]
+    #[cfg(all(feature = "runtime-docs", feature = "singleton", not(feature = "ctor-fallback")))]
+    fn documented_globals() {
+        use crate as global_static;
+        ctor_static! {
+            ///Requests served since startup.
+            ///
+            ///Reset by the admin API.
+            #[allow(dead_code)]
+            SERVED: u32 = { 0 };
+            UNDOCUMENTED: u32 = { 0 };
+        };
+        ///The storage backend.
+        #[singleton(|| Store)]
+        struct Store;
+
+        let docs = registered_docs();
+        let doc = |name: &str| docs.iter().find(|g| g.name.is_some_and(|n| n.ends_with(name))).unwrap();
+        let served = doc("tests::SERVED");
+        assert_eq!(served.doc, "Requests served since startup.\n\nReset by the admin API.");
+        assert_eq!(served.type_name, "u32");
+        assert_eq!(doc("tests::UNDOCUMENTED").doc, "");
+        assert_eq!(doc("tests::STORE").doc, "The storage backend.");
+    }
+
+    #[test]
+    #[cfg(feature = "wit")]
+    fn wit_lowering() {
+        static NAME: Global<String> = Global::new(|| "app".to_owned());
+        static AREA: ReturnArea = ReturnArea::new();
+        wit_export! {
+            fn test_wit = "test:globals/names";
+            "name" => NAME: String;
+            "also-name" => NAME: String;
+        }
+
+        let [ptr, len] = unsafe { *NAME.force().lower(&AREA) };
+        let bytes = unsafe { std::slice::from_raw_parts(ptr as *const u8, len) };
+        assert_eq!(bytes, b"app");
+        assert_eq!('a'.lower(&AREA), 97);
+        assert!(test_wit().contains("also-name: func() -> string;"));
+    }
+
+    #[test]
+    fn with_value() {
+        static LIMIT: Global<u32> = Global::with_value(64);
+        assert_eq!(LIMIT.get(), Some(&64));
+        assert_eq!(LIMIT.set(1), Err(1));
+        assert!(LIMIT.set_initializer(|| 1).is_err());
+        assert_eq!(*LIMIT, 64);
+
+        let mut owned: Global<u32> = Global::with_value(64);
+        assert_eq!(owned.take(), None);
+        assert_eq!(owned.get(), Some(&64));
+        assert_eq!(*owned, 64);
+        assert_eq!(owned.into_inner(), Some(64));
+    }
+
+    #[cfg(feature = "ctor")]
+    mod ctor_grammar {
+        fn base() -> u32 { 40 }
+        crate::ctor_static! {
+            ///The base plus two.
+            #[allow(dead_code)]
+            pub(crate) SUM: u32 = base() + 2;
+            pub(super) CLOSURE: u32 = || base() * 2;
+            pub(in super) QUALIFIED: u32 = u32::default;
+            pub TURBOFISH: Vec<u8> = Vec::<u8>::new;
+            pub ABSOLUTE: String = ::std::string::String::new;
+            pub CALL: String = std::env::var("GLOBAL_STATIC_UNSET").unwrap_or_default();
+            #[cfg(any())]
+            MISSING: u32 = { 1 };
+            #[cfg(all())]
+            #[after(SUM)]
+            pub(crate) mut PRESENT: Vec<u32> = vec![*SUM];
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "ctor")]
+    fn ctor_grammar() {
+        assert_eq!(*ctor_grammar::SUM, 42);
+        assert_eq!(*ctor_grammar::CLOSURE, 80);
+        assert_eq!(*ctor_grammar::QUALIFIED, 0);
+        assert!(ctor_grammar::TURBOFISH.is_empty() && ctor_grammar::ABSOLUTE.is_empty());
+        assert!(ctor_grammar::CALL.is_empty());
+        assert_eq!(*ctor_grammar::PRESENT.read(), [42]);
+    }
+
+    #[test]
+    #[cfg(feature = "ctor")]
+    fn ctor_value() {
+        ctor_static! {
+            value NAME: &str = "app";
+            pub value PORTS: [u16; 2] = [80, 443];
+        };
+        assert_eq!(*NAME, "app");
+        assert_eq!(PORTS[1], 443);
+    }
+
+    #[cfg(feature = "ctor")]
+    static CTOR_ORDER: std::sync::Mutex<Vec<&str>> = std::sync::Mutex::new(Vec::new());
+
+    #[cfg(feature = "ctor")]
+    mod ctor_dependent {
+        use super::CTOR_ORDER;
+        ctor_static! {
+            #[after(super::ctor_dependency::FIRST)]
+            pub SECOND: u8 = { CTOR_ORDER.lock().unwrap().push("second"); 2 };
+        }
+    }
+
+    #[cfg(feature = "ctor")]
+    mod ctor_dependency {
+        use super::CTOR_ORDER;
+        ctor_static! {
+            pub FIRST: u8 = { CTOR_ORDER.lock().unwrap().push("first"); 1 };
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "ctor", not(feature = "ctor-fallback")))]
+    fn ctor_after() {
+        #[cfg(feature = "deterministic")]
+        init_all();
+        assert_eq!(*ctor_dependent::SECOND + *ctor_dependency::FIRST, 3);
+        assert_eq!(*CTOR_ORDER.lock().unwrap(), ["first", "second"]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "ctor", not(feature = "ctor-fallback")))]
+    fn init_modes() {
+        static LAZY: Global<u8> = Global::new(|| 1);
+        static SET: Global<u8> = Global::new(|| 1);
+        #[cfg(feature = "deterministic")]
+        init_all();
+
+        assert!(ctor_dependency::FIRST.is_ctor_initialized());
+        assert!(!LAZY.is_lazily_initialized() && LAZY.initialized().is_none());
+        let before = std::time::Instant::now();
+        LAZY.init();
+        SET.set(2).unwrap();
+        let lazy = LAZY.initialized().unwrap();
+        assert_eq!(lazy.mode, InitMode::Lazy);
+        assert!(lazy.at >= before);
+        assert!(ctor_dependency::FIRST.initialized().unwrap().at <= before);
+        assert_eq!(SET.initialized().unwrap().mode, InitMode::Set);
+    }
+
+    #[test]
+    #[cfg(feature = "observability")]
+    fn init_records_move_with_global() {
+        let global: Global<u8> = Global::new(|| 1);
+        global.init();
+        let moved = Box::new(global);
+        assert_eq!(moved.initialized().unwrap().mode, InitMode::Lazy);
+        drop(moved);
+
+        //a new global in the same place starts without a record
+        let fresh = Box::new(Global::<u8>::new(|| 1));
+        assert!(fresh.initialized().is_none());
+        let mut fresh = *fresh;
+        fresh.set(2).unwrap();
+        assert_eq!(fresh.take(), Some(2));
+        assert!(fresh.initialized().is_none());
+    }
+
+    #[cfg(all(feature = "panic-safe-ctor", not(feature = "deterministic"), not(feature = "ctor-fallback")))]
+    mod ctor_failing {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        static FAILED: AtomicBool = AtomicBool::new(false);
+        #[derive(Debug, PartialEq)]
+        pub struct Flaky(pub u8);
+        ctor_static! {
+            pub FLAKY: Flaky = { if !FAILED.swap(true, Ordering::SeqCst) { panic!("no config") } Flaky(1) };
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "panic-safe-ctor", not(feature = "deterministic"), not(feature = "ctor-fallback")))]
+    fn ctor_panic_deferred() {
+        use std::panic::catch_unwind;
+        let message = |p: Box<dyn std::any::Any + Send>| *p.downcast::<String>().unwrap();
+        assert!(startup_summary().failures().any(|r| r.type_name.ends_with("Flaky")));
+
+        let err = message(catch_unwind(check_startup).unwrap_err());
+        assert!(err.contains("Flaky`: no config"), "{err}");
+        let err = message(catch_unwind(|| ctor_failing::FLAKY.force()).unwrap_err());
+        assert!(err.contains("failed to initialize before main: no config"), "{err}");
+        assert_eq!(*ctor_failing::FLAKY, ctor_failing::Flaky(1));
+        check_startup();
+    }
+
+    #[test]
+    #[cfg(feature = "ctor-fallback")]
+    fn ctor_fallback() {
+        static LATE: Global<u8> = Global::new(|| 4);
+        init_all_globals! {
+            mod ctor_dependent;
+            LATE;
+        }
+
+        assert!(ctor_dependent::SECOND.get().is_none() && ctor_dependency::FIRST.get().is_none());
+        init_all_globals();
+        assert!(ctor_dependency::FIRST.is_ctor_initialized());
+        assert_eq!(*CTOR_ORDER.lock().unwrap(), ["first", "second"]);
+        assert_eq!(LATE.get(), Some(&4));
+    }
+
+    #[test]
+    #[cfg(all(feature = "ctor", feature = "instrumented", not(feature = "ctor-fallback")))]
+    fn ctor_exempt() {
+        use std::sync::OnceLock;
+        ctor_static! {
+            #[exempt]
+            WRAPPED: OnceLock<u64> = OnceLock::new;
+            UNEXEMPT: OnceLock<u32> = OnceLock::new;
+        }
+        register(&WRAPPED);
+        register(&UNEXEMPT);
+
+        let found = instrument::check_foreign_globals();
+        assert!(found.iter().any(|g| g.type_name.ends_with("OnceLock<u32>")));
+        assert!(!found.iter().any(|g| g.type_name.ends_with("OnceLock<u64>")));
+    }
+
+    #[test]
+    #[cfg(feature = "ctor")]
+    fn ctor_mut() {
+        ctor_static! { 
+            mut HITS: u32 = { 1 };
+            pub default mut NAMES: Vec<String>;
+        };
+
+        *HITS.write() += 1;
+        NAMES.write().push("a".to_owned());
+        assert_eq!(*HITS.read(), 2);
+        assert_eq!(NAMES.read().len(), 1);
+    }
+
+    #[test]
+    fn unload_runs_in_reverse() {
+        use std::sync::Mutex;
+        static ORDER: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+        unload::on_unload(|| ORDER.lock().unwrap().push(1));
+        unload::on_unload(|| ORDER.lock().unwrap().push(2));
+        unload::unload();
+        unload::unload();
+        assert_eq!(*ORDER.lock().unwrap(), [2, 1]);
+    }
+
+    #[test]
+    fn builder_options() {
+        static TABLE: Global<Vec<u32>> = Global::builder()
+            .name("TABLE")
+            .init(|| vec![0, 1, 2, 3])
+            .budget(Duration::from_secs(1))
+            .ensure("short", |v| v.len() < 8)
+            .offload()
+            .build();
+        static IN_PLACE: Global<[u8; 4]> = unsafe {
+            Global::builder().init_in_place(|slot| { slot.write([7; 4]); }).build()
+        };
+
+        assert!(TABLE.get().is_none());
+        assert_eq!(*TABLE, [0, 1, 2, 3]);
+        assert_eq!(IN_PLACE[3], 7);
+        let err = std::panic::catch_unwind(|| Global::<u8>::builder().name("MISSING").build()).unwrap_err();
+        assert_eq!(*err.downcast_ref::<&str>().unwrap(), "`GlobalBuilder::build` called without an initializer");
+    }
+
+    #[test]
+    #[cfg(feature = "observability")]
+    fn forecast_pending() {
+        static SMALL: Global<[u8; 16]> = Global::new(|| [0; 16]);
+        static LARGE: Global<Vec<u8>> = Global::new(|| vec![0; 1 << 16]);
+        LARGE.estimate_heap(1 << 16);
+        register(&SMALL);
+        register(&LARGE);
+
+        let large = std::mem::size_of::<Vec<u8>>() + (1 << 16);
+        assert_eq!(LARGE.uninit_size(), large);
+        let sizes: Vec<_> = forecast().pending.into_iter()
+            .filter(|g| g.type_name.contains("u8"))
+            .map(|g| g.size)
+            .collect();
+        assert!(sizes.windows(2).all(|w| w[0] >= w[1]));
+        assert!(sizes.contains(&large) && sizes.contains(&16));
+
+        LARGE.init();
+        assert_eq!(LARGE.uninit_size(), 0);
+        assert!(!forecast().pending.iter().any(|g| g.size == large));
+    }
+
+    #[test]
+    #[cfg(feature = "observability")]
+    fn estimate_moves_with_global() {
+        let global: Global<Vec<u8>> = Global::new(Vec::new);
+        global.estimate_heap(1 << 10);
+        let moved = Box::new(global);
+        assert_eq!(moved.uninit_size(), std::mem::size_of::<Vec<u8>>() + (1 << 10));
+        drop(moved);
+
+        //a new global in the same place has no estimate
+        let fresh = Box::new(Global::<Vec<u8>>::new(Vec::new));
+        assert_eq!(fresh.uninit_size(), std::mem::size_of::<Vec<u8>>());
+    }
+
+    #[test]
+    #[cfg(feature = "test-support")]
+    fn init_order_fuzz() {
+        struct Base(u32);
+        struct Dependent(#[allow(dead_code)] u32);
+        static BASE: Global<Base> = Global::new(|| Base(1));
+        static DEPENDENT: Global<Dependent> = Global::new(|| Dependent(BASE.get().expect("BASE isn't initialized").0 + 1));
+        register(&BASE);
+        register(&DEPENDENT);
+
+        let fuzz = InitOrderFuzz::new("tests::init_order_fuzz").threads(1);
+        let failure = fuzz.seed(1).iterations(16).run().unwrap_err();
+        assert_eq!(failure.kind, OrderFailureKind::Panic("BASE isn't initialized".to_owned()));
+        let pos = |ty| failure.order.iter().position(|t| t.ends_with(ty)).unwrap();
+        assert!(pos("::Dependent") < pos("::Base"));
+        assert_eq!(fuzz.seed(failure.seed).iterations(1).run(), Err(failure));
+    }
+
+    #[test]
+    #[cfg(feature = "observability")]
+    fn init_all_registered() {
+        static A: Global<u8> = Global::new(|| 1);
+        static B: Global<u8> = Global::new(|| *A + 1);
+        register(&B);
+        register(&A);
+        register(&B);
+        init_all();
+        assert_eq!(A.get(), Some(&1));
+        assert_eq!(B.get(), Some(&2));
+        let addrs: Vec<_> = registered().into_iter().map(|g| g as *const dyn AnyGlobal as *const ()).collect();
+        assert_eq!(addrs.iter().filter(|a| std::ptr::eq(**a, &B as *const _ as *const ())).count(), 1);
+    }
+
+    #[test]
+    #[cfg(all(feature = "singleton", not(feature = "ctor-fallback")))]
     fn singleton_attr() {
         use crate as global_static;
         #[singleton(|| Thing::new("hai!"))]
+        #[allow(dead_code)]
         struct Thing {
             data: String,
         }
@@ -255,6 +3030,8 @@ mod tests {
             }
         }
 
+        #[cfg(feature = "deterministic")]
+        init_all();
         assert!(THING.get().is_some());
 
         #[singleton_fn]
@@ -266,4 +3043,322 @@ mod tests {
         assert!(MAKE_THING.get().is_some());
         assert!(MY_THING.get().is_some());
     }
+
+    #[test]
+    #[cfg(all(feature = "singleton", not(feature = "ctor-fallback")))]
+    fn singleton_fn_result() {
+        use crate as global_static;
+        #[singleton_fn]
+        fn load_port() -> Result<u16, std::num::ParseIntError> {
+            "8080".parse()
+        }
+        #[singleton_fn(BAD_PORT)]
+        fn load_bad_port() -> std::result::Result<u16, std::num::ParseIntError> {
+            "eighty".parse()
+        }
+
+        #[cfg(feature = "deterministic")]
+        init_all();
+        assert_eq!(LOAD_PORT.get(), Some(&8080));
+        assert!(BAD_PORT.get_err().is_some());
+    }
+
+    #[test]
+    #[cfg(all(feature = "singleton", feature = "async"))]
+    fn singleton_fn_async() {
+        use crate as global_static;
+        #[singleton_fn]
+        async fn make_pool() -> Vec<u8> {
+            vec![1, 2]
+        }
+
+        assert_eq!(MAKE_POOL.try_get(), None);
+        assert_eq!(block_on(MAKE_POOL.get()), &[1, 2]);
+    }
+
+    #[test]
+    #[cfg(feature = "singleton")]
+    fn singleton_arc() {
+        use crate as global_static;
+        #[singleton(arc, || Counter { hits: 3 })]
+        struct Counter {
+            hits: u32,
+        }
+
+        let shared = Counter::shared();
+        assert_eq!(shared.hits, 3);
+        assert!(std::sync::Arc::ptr_eq(&shared, &COUNTER.shared()));
+    }
+
+    #[test]
+    #[cfg(feature = "singleton")]
+    fn singleton_mut() {
+        use crate as global_static;
+        #[singleton(mut)]
+        #[derive(Default)]
+        struct Sessions {
+            open: Vec<u32>,
+        }
+
+        SESSIONS.write().open.push(4);
+        assert_eq!(SESSIONS.read().open, [4]);
+    }
+
+    #[test]
+    #[cfg(feature = "singleton")]
+    fn singleton_guarded_cells() {
+        use crate as global_static;
+        use std::sync::{atomic::AtomicPtr, Mutex};
+        //a type of the user's own that happens to share a name with `std::cell::Cell`
+        #[derive(Default)]
+        struct Cell(u8);
+
+        #[singleton]
+        #[derive(Default)]
+        struct Scratch {
+            buf: Mutex<std::cell::RefCell<Vec<u8>>>,
+            last: AtomicPtr<std::cell::Cell<u8>>,
+            own: Cell,
+        }
+
+        SCRATCH.buf.lock().unwrap().borrow_mut().push(1);
+        assert_eq!(SCRATCH.buf.lock().unwrap().borrow().len(), 1);
+        assert_eq!(SCRATCH.own.0, 0);
+        assert!(SCRATCH.last.load(std::sync::atomic::Ordering::Relaxed).is_null());
+    }
+
+    #[test]
+    #[cfg(feature = "singleton")]
+    fn singleton_thread_local() {
+        use crate as global_static;
+        use std::cell::Cell;
+        #[singleton(thread_local)]
+        #[derive(Default)]
+        struct Arena {
+            used: Cell<usize>,
+        }
+
+        ctor_static! {
+            thread_local SEEN: Cell<u32> = { Cell::new(0) };
+        }
+
+        ARENA.with(|a| a.used.set(a.used.get() + 8));
+        SEEN.with(|s| s.set(1));
+        std::thread::spawn(|| {
+            assert!(!ARENA.is_initialized());
+            assert_eq!(ARENA.with(|a| a.used.get()), 0);
+            assert_eq!(SEEN.get().get(), 0);
+        }).join().unwrap();
+        assert_eq!(ARENA.with(|a| a.used.get()), 8);
+        assert_eq!(SEEN.with_initialized(Cell::get), Some(1));
+    }
+
+    #[test]
+    #[cfg(feature = "ctor")]
+    fn ctor_stub() {
+        //`all()` always holds, and `any()` never does
+        ctor_static! {
+            stub(all()) ENABLED: u8 = { 1 };
+            pub stub(any()) DISABLED: u8 = { 1 };
+            stub(any(), || 0) NOOP: u8 = { 1 };
+        };
+        assert_eq!(*ENABLED, 1);
+        assert_eq!(*NOOP, 0);
+        assert!(DISABLED.get().is_none());
+        let err = std::panic::catch_unwind(|| *DISABLED).unwrap_err();
+        assert_eq!(err.downcast_ref::<String>().unwrap(), "`u8` is a stub, since the feature it needs is disabled");
+    }
+
+    #[test]
+    #[cfg(feature = "singleton")]
+    fn singleton_stub() {
+        use crate as global_static;
+        #[singleton(stub(any(), || Exporter("noop")), || Exporter("otlp"))]
+        struct Exporter(&'static str);
+        #[singleton(stub(all()), || Sampler(0.5))]
+        struct Sampler(f32);
+
+        assert_eq!(EXPORTER.0, "noop");
+        assert_eq!(SAMPLER.0, 0.5);
+    }
+
+    #[test]
+    #[cfg(feature = "singleton")]
+    fn singleton_instance() {
+        use crate as global_static;
+        #[singleton(instance, || Settings { retries: 3 })]
+        struct Settings {
+            retries: u8,
+        }
+
+        fn retries<S: Singleton>(f: fn(&S) -> u8) -> u8 {
+            f(S::instance())
+        }
+        assert_eq!(retries::<Settings>(|s| s.retries), 3);
+        assert!(std::ptr::eq(Settings::instance(), Settings::try_instance().unwrap()));
+        assert!(std::ptr::eq(Settings::instance(), &*SETTINGS));
+    }
+
+    #[test]
+    #[cfg(feature = "singleton")]
+    fn singleton_inject() {
+        use crate as global_static;
+        struct Database(&'static str);
+        #[derive(Clone)]
+        struct Port(u16);
+
+        #[singleton(inject)]
+        struct Service {
+            db: &'static Database,
+            port: Port,
+        }
+        #[singleton(inject, name = MISSING)]
+        struct Unwired(#[allow(dead_code)] &'static ServiceName);
+        struct ServiceName;
+
+        let registry = GlobalRegistry::global();
+        registry.register(|| Database("postgres"));
+        registry.register(|| Port(5432));
+        assert!(!SERVICE.is_initialized());
+        assert_eq!(SERVICE.db.0, "postgres");
+        assert_eq!(SERVICE.port.0, 5432);
+
+        let err = std::panic::catch_unwind(|| MISSING.init()).unwrap_err();
+        let msg = err.downcast_ref::<String>().unwrap();
+        assert!(msg.contains("`Unwired` depends on") && msg.contains("ServiceName"), "{msg}");
+    }
+
+    #[test]
+    #[cfg(feature = "singleton")]
+    fn singleton_enum_and_generic() {
+        use crate as global_static;
+        #[singleton]
+        #[derive(Default, Debug, PartialEq)]
+        enum Mode {
+            #[default]
+            Fast,
+            #[allow(dead_code)]
+            Careful { retries: u8 },
+        }
+
+        #[singleton(ty = Cache<&'static str, u32>, init = Cache::new)]
+        struct Cache<K, V> {
+            entries: Vec<(K, V)>,
+        }
+        impl<K, V> Cache<K, V> {
+            fn new() -> Self {
+                Self { entries: Vec::new() }
+            }
+        }
+
+        #[cfg(feature = "deterministic")]
+        init_all();
+        assert_eq!(*MODE, Mode::Fast);
+        assert!(CACHE.entries.is_empty());
+    }
+
+    #[cfg(feature = "singleton")]
+    mod private_singleton {
+        use crate as global_static;
+        use global_static::singleton;
+
+        ///Settings only this module can see.
+        #[singleton(name = LOCAL_SETTINGS, vis = pub(super), no_ctor, || Settings { level: 2 })]
+        #[allow(dead_code)]
+        pub(super) struct Settings {
+            pub(super) level: u8,
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "singleton")]
+    fn singleton_name_and_vis() {
+        assert!(private_singleton::LOCAL_SETTINGS.get().is_none());
+        assert_eq!(private_singleton::LOCAL_SETTINGS.level, 2);
+    }
+
+    #[test]
+    #[cfg(feature = "singleton")]
+    fn derive_ffi_safe() {
+        use crate as global_static;
+        #[derive(FfiSafe)]
+        #[repr(C)]
+        struct Pair<T> { a: T, b: [u8; 4] }
+
+        static PAIR: Global<Pair<u16>> = Global::new(|| Pair { a: 3, b: [0; 4] });
+        let pair = unsafe { &*PAIR.freeze_layout() };
+        assert_eq!(pair.a, 3);
+        assert_eq!(pair.b, [0; 4]);
+    }
+
+    #[cfg(feature = "singleton")]
+    mod c_header {
+        use crate::{self as global_static, CType, FfiSafe, Global};
+
+        #[derive(FfiSafe, CType)]
+        #[repr(u8)]
+        pub enum Mode { Idle, Busy = 4 }
+
+        #[derive(FfiSafe, CType)]
+        #[repr(transparent)]
+        pub struct Port(u16);
+
+        #[derive(FfiSafe, CType)]
+        #[repr(C)]
+        pub struct Node {
+            pub mode: Mode,
+            pub ports: [Port; 2],
+            pub next: *const Node,
+        }
+        unsafe impl Sync for Node {}
+        unsafe impl Send for Node {}
+
+        static ROOT: Global<Node> = Global::new(|| Node { mode: Mode::Busy, ports: [Port(80), Port(443)], next: core::ptr::null() });
+        static MODE: Global<Mode> = Global::new(|| Mode::Idle);
+
+        global_static::export_global! {
+            header node_header = "NODE_H";
+            test_root_get: Node = ROOT;
+            test_mode_get: Mode = MODE;
+        }
+
+        #[test]
+        fn render() {
+            let header = node_header();
+            assert!(header.starts_with("/* generated by global-static, do not edit */\n#ifndef NODE_H\n#define NODE_H\n"));
+            let enum_at = header.find("typedef uint8_t Mode;\nenum {\n    Mode_Idle = 0,\n    Mode_Busy = 4,\n};").unwrap();
+            let node_at = header.find(
+                "struct Node {\n    Mode mode;\n    uint16_t ports[2];\n    struct Node const *next;\n};"
+            ).unwrap();
+            assert!(enum_at < node_at);
+            assert_eq!(header.matches("typedef uint8_t Mode;").count(), 1);
+            assert!(header.contains("struct Node const *test_root_get(void);\nMode const *test_mode_get(void);\n"));
+            assert_eq!(unsafe { &*test_root_get() }.ports[1].0, 443);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "no-panic"))]
+mod no_panic_tests {
+    use super::*;
+
+    #[test]
+    fn force_without_deref() {
+        static NUM: Global<u8> = Global::new(|| 5);
+        assert_eq!(NUM.get(), None);
+        assert_eq!(*NUM.force(), 5);
+        assert_eq!(format!("{NUM:?}"), "5");
+    }
+
+    #[test]
+    fn failures_are_errors() {
+        static NAME: GlobalStr = GlobalStr::env("GLOBAL_STATIC_NO_PANIC_UNSET");
+        static LATE: GlobalDyn<u8> = GlobalDyn::empty();
+
+        NAME.init();
+        assert!(matches!(NAME.try_as_str().unwrap_err().kind(), ErrorKind::Env(_)));
+        assert!(matches!(LATE.try_get().unwrap_err().kind(), ErrorKind::NoInitializer));
+        LATE.set_init(|| 3).ok();
+        assert_eq!(LATE.try_get().ok(), Some(&3));
+    }
 }
@@ -0,0 +1,214 @@
+use std::{
+    fmt::Debug,
+    sync::{atomic::{AtomicBool, Ordering}, OnceLock},
+};
+
+use crate::{AnyGlobal, Error, ErrorKind};
+
+///One-time setup of a C library, such as OpenSSL or GStreamer, that has to be initialized
+///before it is used and torn down at most once afterwards.
+///
+///The library's init function runs once no matter how many threads call
+///[`init`](GlobalLibrary::init), after an optional version check. If the check fails, the error
+///is stored and returned by every later call, and the library is never initialized. If the init
+///function panics, the next call tries again. [`deinit`](GlobalLibrary::deinit) runs the teardown
+///function only if the library was initialized, and only once. Registering the library with
+///[`register`](crate::register) tears it down with [`deinit_all`](crate::deinit_all), or at exit
+///with the `dtor` feature.
+///
+///[`c_library!`](crate::c_library) generates a type around one of these.
+///```rust
+///# use global_static::GlobalLibrary;
+///# use std::sync::atomic::{AtomicU32, Ordering};
+///static INITS: AtomicU32 = AtomicU32::new(0);
+///static ZLIB: GlobalLibrary = GlobalLibrary::new("zlib", || { INITS.fetch_add(1, Ordering::Relaxed); })
+///    .teardown(|| { INITS.fetch_sub(1, Ordering::Relaxed); })
+///    .version_check(|| Ok(()));
+///
+///ZLIB.init();
+///ZLIB.init();
+///assert_eq!(INITS.load(Ordering::Relaxed), 1);
+/////safety: nothing uses zlib after this
+///unsafe { ZLIB.deinit() };
+///unsafe { ZLIB.deinit() };
+///assert_eq!(INITS.load(Ordering::Relaxed), 0);
+///```
+pub struct GlobalLibrary {
+    name: &'static str,
+    init: fn(),
+    teardown: Option<fn()>,
+    version: Option<fn() -> Result<(), String>>,
+    data: OnceLock<Result<(), Error>>,
+    torn_down: AtomicBool,
+}
+
+impl GlobalLibrary {
+    ///Constructs a new library from its name, which is used in errors, and the function that
+    ///initializes it.
+    pub const fn new(name: &'static str, init: fn()) -> Self {
+        Self { name, init, teardown: None, version: None, data: OnceLock::new(), torn_down: AtomicBool::new(false) }
+    }
+
+    ///Sets the function that tears the library down, run by [`deinit`](GlobalLibrary::deinit).
+    pub const fn teardown(mut self, f: fn()) -> Self {
+        self.teardown = Some(f);
+        self
+    }
+
+    ///Sets a check run before the library is initialized, such as comparing the version of the
+    ///library loaded at runtime against the one the bindings were written for. The error
+    ///message is returned as an [`ErrorKind::Validation`] error.
+    pub const fn version_check(mut self, f: fn() -> Result<(), String>) -> Self {
+        self.version = Some(f);
+        self
+    }
+
+    ///Initializes the library. Does nothing if already initialized.
+    ///
+    ///# Panics
+    ///Panics if the version check fails.
+    #[cfg(not(feature = "no-panic"))]
+    pub fn init(&self) {
+        if let Err(e) = self.try_init() {
+            panic!("{e}");
+        }
+    }
+
+    ///Initializes the library, returning the error if the version check failed. Does nothing if
+    ///already initialized.
+    pub fn try_init(&self) -> Result<(), &Error> {
+        self.data.get_or_init(|| {
+            if let Some(Err(msg)) = self.version.map(|check| check()) {
+                return Err(Error::new(self.name, ErrorKind::Validation(msg)));
+            }
+            (self.init)();
+            Ok(())
+        }).as_ref().map(|_| ())
+    }
+
+    ///Whether the library has been initialized successfully.
+    pub fn is_initialized(&self) -> bool {
+        matches!(self.data.get(), Some(Ok(())))
+    }
+
+    ///Tears the library down, if it was initialized and hasn't been torn down already.
+    ///
+    ///# Safety
+    ///Nothing may use the library afterwards, including through values it handed out earlier.
+    pub unsafe fn deinit(&self) {
+        if self.is_initialized() && !self.torn_down.swap(true, Ordering::AcqRel) {
+            if let Some(teardown) = self.teardown {
+                teardown();
+            }
+        }
+    }
+}
+
+impl AnyGlobal for GlobalLibrary {
+    fn init(&self) {
+        let _ = self.try_init();
+    }
+    fn is_initialized(&self) -> bool {
+        GlobalLibrary::is_initialized(self)
+    }
+    fn type_name(&self) -> &'static str {
+        self.name
+    }
+    unsafe fn deinit(&'static self) {
+        GlobalLibrary::deinit(self)
+    }
+}
+
+impl Debug for GlobalLibrary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GlobalLibrary")
+            .field("name", &self.name)
+            .field("initialized", &self.is_initialized())
+            .field("torn_down", &self.torn_down.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+#[macro_export]
+///Generate a type wrapping the one-time setup of a C library, from the function that
+///initializes it, and optionally a function that tears it down and a version check. See
+///[`GlobalLibrary`](crate::GlobalLibrary).
+///
+///`init()` returns a reference to the type once the library is initialized, so functions that
+///need the library can take one as proof, and `try_init()` returns the version check's error
+///instead of panicking.
+///```rust
+///# mod sys { pub unsafe fn ogg_init() {} pub unsafe fn ogg_shutdown() {} pub fn ogg_version() -> u32 { 0x0103 } }
+///global_static::c_library! {
+///    ///The Ogg container library.
+///    pub struct Ogg = "libogg" {
+///        init: || unsafe { sys::ogg_init() },
+///        deinit: || unsafe { sys::ogg_shutdown() },
+///        version: || match sys::ogg_version() {
+///            v if v >= 0x0100 => Ok(()),
+///            v => Err(format!("libogg {v:#x} is older than 1.0")),
+///        },
+///    }
+///}
+///
+///fn decode(_ogg: &Ogg, _data: &[u8]) {}
+///
+///decode(Ogg::init(), b"OggS");
+///assert!(Ogg::is_initialized());
+/////safety: nothing uses libogg after this
+///unsafe { Ogg::deinit() };
+///```
+macro_rules! c_library {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident = $lib:literal {
+            init: $init:expr
+            $(, deinit: $deinit:expr)?
+            $(, version: $version:expr)?
+            $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name(());
+
+        impl $name {
+            fn library() -> &'static $crate::GlobalLibrary {
+                static LIBRARY: $crate::GlobalLibrary = $crate::GlobalLibrary::new($lib, $init)
+                    $(.teardown($deinit))?
+                    $(.version_check($version))?;
+                &LIBRARY
+            }
+
+            ///Initializes the library if needed.
+            ///
+            ///# Panics
+            ///Panics if the version check fails.
+            $vis fn init() -> &'static Self {
+                match Self::try_init() {
+                    Ok(lib) => lib,
+                    Err(e) => panic!("{e}"),
+                }
+            }
+
+            ///Initializes the library if needed, returning the error if the version check
+            ///failed.
+            $vis fn try_init() -> ::std::result::Result<&'static Self, &'static $crate::Error> {
+                static TOKEN: $name = $name(());
+                Self::library().try_init().map(|_| &TOKEN)
+            }
+
+            ///Whether the library has been initialized.
+            $vis fn is_initialized() -> bool {
+                Self::library().is_initialized()
+            }
+
+            ///Tears the library down, if it was initialized and hasn't been torn down already.
+            ///
+            ///# Safety
+            ///Nothing may use the library afterwards.
+            $vis unsafe fn deinit() {
+                Self::library().deinit()
+            }
+        }
+    };
+}
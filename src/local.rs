@@ -0,0 +1,81 @@
+use std::{cell::OnceCell, fmt::Debug, thread::LocalKey};
+
+///Lazily evaluated static with a separate value for each thread, for per-thread caches such as
+///random number generators or arenas.
+///
+///The value is created on first access from each thread, and dropped when that thread exits.
+///Since the value can't outlive its thread, it is only borrowed for the duration of
+///[`with`](LocalGlobal::with). The storage is a [`thread_local!`](std::thread_local) key, which
+///`ctor_static!` and `#[singleton]` generate with their `thread_local` modifier.
+///```rust
+///# use global_static::LocalGlobal;
+///# use std::cell::{Cell, OnceCell};
+///thread_local! {
+///    static SEED: OnceCell<Cell<u64>> = const { OnceCell::new() };
+///}
+///static RNG: LocalGlobal<Cell<u64>> = LocalGlobal::new(&SEED, || Cell::new(1));
+///
+///let next = || RNG.with(|s| { s.set(s.get().wrapping_mul(6364136223846793005).wrapping_add(1)); s.get() });
+///let first = next();
+///std::thread::spawn(move || assert_eq!(next(), first)).join().unwrap();
+///assert_ne!(next(), first);
+///```
+pub struct LocalGlobal<T: 'static> {
+    key: &'static LocalKey<OnceCell<T>>,
+    f: fn() -> T,
+}
+
+impl<T: 'static> LocalGlobal<T> {
+    ///Constructs a new global from a thread-local key to store the value in, and a function that
+    ///produces the value for each thread.
+    pub const fn new(key: &'static LocalKey<OnceCell<T>>, f: fn() -> T) -> Self {
+        Self { key, f }
+    }
+
+    ///Initializes the contents of the global for the current thread. Does nothing if already
+    ///initialized.
+    pub fn init(&self) {
+        self.with(|_| ());
+    }
+
+    ///Borrows the value for the current thread, initializing it if needed.
+    ///
+    ///# Panics
+    ///Panics if called while the thread's thread-locals are being destroyed, or if the
+    ///initializer accesses this global.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.key.with(|cell| f(cell.get_or_init(self.f)))
+    }
+
+    ///Borrows the value for the current thread without initializing it. Returns `None` if it
+    ///has not been initialized on this thread.
+    pub fn with_initialized<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        self.key.with(|cell| cell.get().map(f))
+    }
+
+    ///Returns a clone of the value for the current thread, initializing it if needed.
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+
+    ///Whether the global has been initialized on the current thread.
+    pub fn is_initialized(&self) -> bool {
+        self.key.with(|cell| cell.get().is_some())
+    }
+
+    ///Sets the value for the current thread before it is first accessed there, so the
+    ///initializer never runs on this thread. Returns the value back if it was already
+    ///initialized.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        self.key.with(|cell| cell.set(value))
+    }
+}
+
+impl<T: Debug> Debug for LocalGlobal<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.with(|v| write!(f, "{v:?}"))
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    borrow::Borrow,
+    collections::{hash_map::RandomState, HashMap},
+    hash::{BuildHasher, Hash},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+};
+
+struct Shards<K, V> {
+    hasher: RandomState,
+    shards: Box<[RwLock<HashMap<K, V>>]>,
+}
+
+///Counters describing how a [`GlobalMap`] has been used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MapMetrics {
+    ///Number of lookups.
+    pub reads: u64,
+    ///Number of insertions, updates and removals.
+    pub writes: u64,
+    ///Number of entries.
+    pub len: usize,
+    ///Number of shards the map is split into.
+    pub shards: usize,
+}
+
+///Concurrent hash map static.
+///
+///The map is split into shards, each behind its own [`RwLock`], so that threads working on
+///different keys rarely contend. This replaces the common `Lazy<Mutex<HashMap<K, V>>>` pattern.
+///The shards are allocated on first access.
+///```rust
+///# use global_static::GlobalMap;
+///static SESSIONS: GlobalMap<u64, String> = GlobalMap::new();
+///
+///SESSIONS.insert(1, "alice".to_owned());
+///assert_eq!(SESSIONS.get_cloned(&1).as_deref(), Some("alice"));
+///assert_eq!(SESSIONS.with(&1, |name| name.len()), Some(5));
+///```
+pub struct GlobalMap<K, V> {
+    data: OnceLock<Shards<K, V>>,
+    reads: AtomicU64,
+    writes: AtomicU64,
+}
+
+impl<K, V> GlobalMap<K, V> {
+    ///Constructs a new, empty map.
+    pub const fn new() -> Self {
+        Self { data: OnceLock::new(), reads: AtomicU64::new(0), writes: AtomicU64::new(0) }
+    }
+
+    ///Allocates the shards of the map. Does nothing if already allocated.
+    pub fn init(&self) {
+        self.shards();
+    }
+
+    ///Whether the shards of the map have been allocated.
+    pub fn is_initialized(&self) -> bool {
+        self.data.get().is_some()
+    }
+
+    ///Number of entries in the map. This locks each shard in turn, so the result may be out of
+    ///date under concurrent modification.
+    pub fn len(&self) -> usize {
+        self.data.get().map_or(0, |d| d.shards.iter().map(|s| read(s).len()).sum())
+    }
+
+    ///Whether the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///Removes all entries.
+    pub fn clear(&self) {
+        if let Some(d) = self.data.get() {
+            self.writes.fetch_add(1, Ordering::Relaxed);
+            d.shards.iter().for_each(|s| write(s).clear());
+        }
+    }
+
+    ///Calls `f` on every entry. Each shard is read locked while its entries are visited, so `f`
+    ///must not modify the map.
+    pub fn for_each(&self, mut f: impl FnMut(&K, &V)) {
+        if let Some(d) = self.data.get() {
+            for shard in d.shards.iter() {
+                read(shard).iter().for_each(|(k, v)| f(k, v));
+            }
+        }
+    }
+
+    ///Keeps only the entries for which `f` returns `true`.
+    pub fn retain(&self, mut f: impl FnMut(&K, &mut V) -> bool) {
+        if let Some(d) = self.data.get() {
+            self.writes.fetch_add(1, Ordering::Relaxed);
+            d.shards.iter().for_each(|s| write(s).retain(|k, v| f(k, v)));
+        }
+    }
+
+    ///Usage counters for the map.
+    pub fn metrics(&self) -> MapMetrics {
+        MapMetrics {
+            reads: self.reads.load(Ordering::Relaxed),
+            writes: self.writes.load(Ordering::Relaxed),
+            len: self.len(),
+            shards: self.data.get().map_or(0, |d| d.shards.len()),
+        }
+    }
+
+    fn shards(&self) -> &Shards<K, V> {
+        self.data.get_or_init(|| {
+            let count = std::thread::available_parallelism().map_or(1, |n| n.get() * 4).next_power_of_two();
+            Shards {
+                hasher: RandomState::new(),
+                shards: (0..count).map(|_| RwLock::new(HashMap::new())).collect(),
+            }
+        })
+    }
+}
+
+impl<K: Hash + Eq, V> GlobalMap<K, V> {
+    fn shard<Q: Hash + ?Sized>(&self, key: &Q) -> &RwLock<HashMap<K, V>> {
+        let data = self.shards();
+        let index = data.hasher.hash_one(key) as usize & (data.shards.len() - 1);
+        &data.shards[index]
+    }
+
+    ///Inserts an entry, returning the previous value for the key.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.writes.fetch_add(1, Ordering::Relaxed);
+        write(self.shard(&key)).insert(key, value)
+    }
+
+    ///Removes an entry, returning its value.
+    pub fn remove<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<V> where K: Borrow<Q> {
+        self.writes.fetch_add(1, Ordering::Relaxed);
+        write(self.shard(key)).remove(key)
+    }
+
+    ///Whether the map has an entry for the key.
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool where K: Borrow<Q> {
+        self.with(key, |_| ()).is_some()
+    }
+
+    ///Calls `f` with the value for the key, if there is one. The key's shard is read locked
+    ///while `f` runs, so `f` must not modify the map.
+    pub fn with<Q: Hash + Eq + ?Sized, R>(&self, key: &Q, f: impl FnOnce(&V) -> R) -> Option<R> where K: Borrow<Q> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        read(self.shard(key)).get(key).map(f)
+    }
+
+    ///Calls `f` with the value for the key, inserting one produced by `default` first if there
+    ///is none. The key's shard is write locked while `f` runs, so `f` must not access the map.
+    pub fn upsert<R>(&self, key: K, default: impl FnOnce() -> V, f: impl FnOnce(&mut V) -> R) -> R {
+        self.writes.fetch_add(1, Ordering::Relaxed);
+        f(write(self.shard(&key)).entry(key).or_insert_with(default))
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> GlobalMap<K, V> {
+    ///Returns a clone of the value for the key.
+    pub fn get_cloned<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<V> where K: Borrow<Q> {
+        self.with(key, V::clone)
+    }
+}
+
+impl<K, V> Default for GlobalMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(|e| e.into_inner())
+}
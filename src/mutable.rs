@@ -0,0 +1,71 @@
+use std::{
+    fmt::Debug,
+    sync::{OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+///Lazily evaluated static that can be mutated.
+///
+///The value is kept behind an [`RwLock`], which is created along with the value on first access.
+///A panic while a write guard is held does not poison the global; later accesses see the value
+///as it was left.
+///```rust
+///# use global_static::GlobalMut;
+///static NAMES: GlobalMut<Vec<String>> = GlobalMut::new(Vec::new);
+///
+///NAMES.write().push("a".to_owned());
+///assert_eq!(NAMES.read().len(), 1);
+///```
+pub struct GlobalMut<T> {
+    f: fn() -> T,
+    data: OnceLock<RwLock<T>>,
+}
+
+impl<T> GlobalMut<T> {
+    ///Constructs a new global from a function that produces its initial value.
+    pub const fn new(f: fn() -> T) -> Self {
+        Self { f, data: OnceLock::new() }
+    }
+
+    ///Initializes the contents of a global. Does nothing if already initialized.
+    pub fn init(&self) {
+        self.lock();
+    }
+
+    ///Whether the global has been initialized.
+    pub fn is_initialized(&self) -> bool {
+        self.data.get().is_some()
+    }
+
+    ///Locks the global for reading, initializing it if needed.
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.lock().read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    ///Locks the global for writing, initializing it if needed.
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.lock().write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    ///Replaces the value, returning the previous one.
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace(&mut *self.write(), value)
+    }
+
+    fn lock(&self) -> &RwLock<T> {
+        self.data.get_or_init(|| RwLock::new((self.f)()))
+    }
+}
+
+impl<T: Default> GlobalMut<T> {
+    ///Constructs a new global, using the [`Default`] implementation for `T` as the initializer.
+    #[allow(clippy::should_implement_trait)]
+    pub const fn default() -> Self {
+        Self::new(T::default)
+    }
+}
+
+impl<T: Debug> Debug for GlobalMut<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", &*self.read())
+    }
+}
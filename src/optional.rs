@@ -0,0 +1,66 @@
+use core::fmt::Debug;
+
+use crate::Global;
+
+///Lazily evaluated static that may turn out to have no value.
+///
+///Optional subsystems (a GPU context, a connection to a service that may not be configured) are
+///awkward as a `Global<Option<T>>`, since `get` then can't tell "not initialized yet" apart from
+///"initialized, but absent". `GlobalOpt` keeps the two apart.
+///```rust
+///# use global_static::GlobalOpt;
+///static GPU: GlobalOpt<String> = GlobalOpt::new(|| std::env::var("MY_APP_GPU").ok());
+///
+///assert_eq!(GPU.peek(), None);
+///if let Some(gpu) = GPU.get() {
+///    println!("rendering on {gpu}");
+///}
+///assert!(GPU.peek().is_some());
+///```
+pub struct GlobalOpt<T>(Global<Option<T>>);
+
+impl<T> GlobalOpt<T> {
+    ///Constructs a new global from a function that produces its value, if there is one.
+    pub const fn new(f: fn() -> Option<T>) -> Self {
+        Self(Global::new(f))
+    }
+
+    ///Initializes the contents of a global. Does nothing if already initialized.
+    pub fn init(&self) {
+        self.0.init();
+    }
+
+    ///Retrieves the value, initializing the global if needed. Returns `None` if the initializer
+    ///produced no value.
+    pub fn get(&self) -> Option<&T> {
+        self.0.force().as_ref()
+    }
+
+    ///Retrieves the value without initializing the global. Returns `None` if it has not been
+    ///initialized, and `Some(None)` if it was initialized without a value.
+    pub fn peek(&self) -> Option<Option<&T>> {
+        self.0.get().map(Option::as_ref)
+    }
+
+    ///Whether the global has been initialized, with or without a value.
+    pub fn is_initialized(&self) -> bool {
+        self.0.get().is_some()
+    }
+
+    ///Whether the global has a value, initializing it if needed.
+    pub fn is_some(&self) -> bool {
+        self.get().is_some()
+    }
+
+    ///Sets the value of the global before it is first accessed, so the initializer never runs.
+    ///Returns the value back if the global was already initialized.
+    pub fn set(&self, value: Option<T>) -> Result<(), Option<T>> {
+        self.0.set(value)
+    }
+}
+
+impl<T: Debug> Debug for GlobalOpt<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.get())
+    }
+}
@@ -0,0 +1,76 @@
+use std::{
+    cell::RefCell,
+    marker::PhantomData,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{Global, OnceBackend};
+
+thread_local! {
+    //(global, value) pairs, most recent last
+    static OVERRIDES: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+//overrides active on any thread, so globals can skip the thread local when there are none
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+#[inline]
+pub(crate) fn lookup<T>(global: *const ()) -> Option<*const T> {
+    if ACTIVE.load(Ordering::Relaxed) == 0 {
+        return None;
+    }
+    OVERRIDES
+        .try_with(|o| {
+            o.borrow().iter().rev().find(|(g, _)| *g == global as usize).map(|&(_, v)| v as *const T)
+        })
+        .ok()
+        .flatten()
+}
+
+///Restores the original value of a global when dropped. Returned by
+///[`Global::override_scoped`].
+#[must_use = "the override is removed as soon as the guard is dropped"]
+pub struct OverrideGuard {
+    global: usize,
+    value: usize,
+    //overrides belong to the thread that made them
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for OverrideGuard {
+    fn drop(&mut self) {
+        let _ = OVERRIDES.try_with(|o| {
+            let mut o = o.borrow_mut();
+            if let Some(i) = o.iter().rposition(|&e| e == (self.global, self.value)) {
+                o.remove(i);
+            }
+        });
+        ACTIVE.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl<T: 'static, B: OnceBackend<T>> Global<T, B> {
+    ///Replaces the value of the global on the current thread until the returned guard is
+    ///dropped. The global itself is not initialized or modified, and other threads still see
+    ///the original value. Overrides can be nested, and the most recent one wins.
+    ///
+    ///The replacement is leaked so references to it stay valid after the guard is dropped; this
+    ///is meant for tests, not for swapping values at runtime.
+    ///```rust
+    ///# use global_static::Global;
+    ///static ENDPOINT: Global<String> = Global::new(|| "https://example.com".to_owned());
+    ///
+    ///{
+    ///    let _mock = ENDPOINT.override_scoped("http://localhost:8080".to_owned());
+    ///    assert_eq!(*ENDPOINT, "http://localhost:8080");
+    ///}
+    ///assert_eq!(*ENDPOINT, "https://example.com");
+    ///```
+    pub fn override_scoped(&self, value: T) -> OverrideGuard {
+        let global = self as *const Self as usize;
+        let value = Box::leak(Box::new(value)) as *const T as usize;
+        OVERRIDES.with(|o| o.borrow_mut().push((global, value)));
+        ACTIVE.fetch_add(1, Ordering::Relaxed);
+        OverrideGuard { global, value, _thread: PhantomData }
+    }
+}
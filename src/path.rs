@@ -0,0 +1,168 @@
+use std::{
+    fmt::Debug,
+    io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use directories::ProjectDirs;
+
+use crate::{Error, ErrorKind};
+
+#[derive(Clone, Copy)]
+enum Dir {
+    Config,
+    Cache,
+    Data,
+    DataLocal,
+}
+
+///Lazily evaluated path to one of an application's platform directories, such as
+///`~/.config/app` on Linux, `~/Library/Application Support/com.example.App` on macOS and
+///`%APPDATA%\Example\App\config` on Windows.
+///
+///The directory follows each platform's conventions, as found by the
+///[`directories`](https://docs.rs/directories) crate, and can be overridden by an environment
+///variable given with [`env`](GlobalPath::env). With [`create`](GlobalPath::create), it is also
+///created when the global is initialized, so it can be written to straight away.
+///```rust
+///# use global_static::GlobalPath;
+///static CACHE: GlobalPath = GlobalPath::cache_dir("com", "Example", "App").env("APP_CACHE_DIR").create();
+///
+///# let tmp = std::env::temp_dir().join("global-static-path-doc");
+///std::env::set_var("APP_CACHE_DIR", &tmp);
+///assert_eq!(CACHE.as_path(), tmp);
+///assert!(CACHE.is_dir());
+///```
+pub struct GlobalPath {
+    dir: Dir,
+    qualifier: &'static str,
+    organization: &'static str,
+    application: &'static str,
+    env: Option<&'static str>,
+    create: bool,
+    data: OnceLock<Result<PathBuf, Error>>,
+}
+
+impl GlobalPath {
+    const fn new(dir: Dir, qualifier: &'static str, organization: &'static str, application: &'static str) -> Self {
+        Self { dir, qualifier, organization, application, env: None, create: false, data: OnceLock::new() }
+    }
+
+    ///The application's configuration directory. `qualifier` and `organization` are only used on
+    ///some platforms, such as `"com"` and `"Example"` in `com.example.App` on macOS.
+    pub const fn config_dir(qualifier: &'static str, organization: &'static str, application: &'static str) -> Self {
+        Self::new(Dir::Config, qualifier, organization, application)
+    }
+
+    ///The application's cache directory, for files that can be deleted at any time.
+    pub const fn cache_dir(qualifier: &'static str, organization: &'static str, application: &'static str) -> Self {
+        Self::new(Dir::Cache, qualifier, organization, application)
+    }
+
+    ///The application's data directory, which roams with the user's profile on Windows.
+    pub const fn data_dir(qualifier: &'static str, organization: &'static str, application: &'static str) -> Self {
+        Self::new(Dir::Data, qualifier, organization, application)
+    }
+
+    ///The application's data directory for files that stay on this machine.
+    pub const fn data_local_dir(qualifier: &'static str, organization: &'static str, application: &'static str) -> Self {
+        Self::new(Dir::DataLocal, qualifier, organization, application)
+    }
+
+    ///Uses the path in the environment variable `var` instead, if it is set and not empty.
+    pub const fn env(mut self, var: &'static str) -> Self {
+        self.env = Some(var);
+        self
+    }
+
+    ///Creates the directory and its parents when the global is initialized, if they don't
+    ///exist.
+    pub const fn create(mut self) -> Self {
+        self.create = true;
+        self
+    }
+
+    ///Initializes the contents of a global. Does nothing if already initialized.
+    ///
+    ///Panics if the path could not be found or created, unless the `no-panic` feature is
+    ///enabled, in which case the error is stored and returned by
+    ///[`try_as_path`](GlobalPath::try_as_path).
+    pub fn init(&self) {
+        #[cfg(not(feature = "no-panic"))]
+        self.as_path();
+        #[cfg(feature = "no-panic")]
+        let _ = self.try_as_path();
+    }
+
+    ///Retrieves the path without initializing it. Returns `None` if finding or creating it
+    ///failed.
+    pub fn get(&self) -> Option<&Path> {
+        self.data.get()?.as_deref().ok()
+    }
+
+    ///Retrieves the path, initializing it if needed. Panics if it could not be found or created.
+    #[cfg(not(feature = "no-panic"))]
+    pub fn as_path(&self) -> &Path {
+        match self.try_as_path() {
+            Ok(p) => p,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    ///Retrieves the path, initializing it if needed. If it could not be found or created, the
+    ///error is stored and returned on every access.
+    pub fn try_as_path(&self) -> Result<&Path, &Error> {
+        self.data.get_or_init(|| self.produce()).as_deref()
+    }
+
+    fn produce(&self) -> Result<PathBuf, Error> {
+        let path = match self.env.and_then(std::env::var_os).filter(|v| !v.is_empty()) {
+            Some(path) => PathBuf::from(path),
+            None => {
+                //only fails if the home directory can't be found
+                let Some(dirs) = ProjectDirs::from(self.qualifier, self.organization, self.application) else {
+                    let e = io::Error::new(io::ErrorKind::NotFound, "no home directory found");
+                    return Err(Error::new(self.application, ErrorKind::Io(e)));
+                };
+                match self.dir {
+                    Dir::Config => dirs.config_dir(),
+                    Dir::Cache => dirs.cache_dir(),
+                    Dir::Data => dirs.data_dir(),
+                    Dir::DataLocal => dirs.data_local_dir(),
+                }.to_path_buf()
+            },
+        };
+        if self.create {
+            if let Err(e) = std::fs::create_dir_all(&path) {
+                return Err(Error::new(self.env.unwrap_or(self.application), ErrorKind::Io(e)));
+            }
+        }
+        Ok(path)
+    }
+}
+
+#[cfg(not(feature = "no-panic"))]
+impl std::ops::Deref for GlobalPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_path()
+    }
+}
+
+#[cfg(not(feature = "no-panic"))]
+impl AsRef<Path> for GlobalPath {
+    fn as_ref(&self) -> &Path {
+        self.as_path()
+    }
+}
+
+impl Debug for GlobalPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.try_as_path() {
+            Ok(p) => write!(f, "{p:?}"),
+            Err(e) => write!(f, "<{e}>"),
+        }
+    }
+}
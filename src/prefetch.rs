@@ -0,0 +1,68 @@
+use core::mem::{size_of, MaybeUninit};
+
+use crate::{Global, OnceBackend};
+
+const PAGE: usize = 4096;
+
+impl<T, B: OnceBackend<T>> Global<T, B> {
+    ///Initializes the global and touches every page of its value, so that the first real access
+    ///doesn't pay for initialization or page faults. Call this while warming up a service, for
+    ///example during a health check phase.
+    ///
+    ///Only the memory of `T` itself is touched, not memory it points to, such as the contents of
+    ///a `Vec`. With the `numa` feature on Linux, the pages are also moved to the NUMA node of the
+    ///calling thread where possible. Pages shared with neighbouring statics move with them.
+    ///```rust
+    ///# use global_static::Global;
+    ///static TABLE: Global<[u64; 4096]> = Global::new(|| [7; 4096]);
+    ///TABLE.prefetch();
+    ///```
+    pub fn prefetch(&self) -> &T {
+        let value = self.force();
+        let ptr = value as *const T as *const MaybeUninit<u8>;
+        let len = size_of::<T>();
+        #[cfg(all(feature = "numa", target_os = "linux"))]
+        numa::move_to_current_node(ptr as *const u8, len);
+        for offset in (0..len).step_by(PAGE) {
+            //safety: in bounds of the value, and MaybeUninit allows reading padding
+            unsafe { core::ptr::read_volatile(ptr.add(offset)) };
+        }
+        value
+    }
+}
+
+#[cfg(all(feature = "numa", target_os = "linux"))]
+mod numa {
+    use super::PAGE;
+
+    const MPOL_PREFERRED: libc::c_long = 1;
+    const MPOL_MF_MOVE: libc::c_long = 1 << 1;
+
+    //best effort, failing to move pages only costs performance
+    pub(super) fn move_to_current_node(ptr: *const u8, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let mut cpu: libc::c_uint = 0;
+        let mut node: libc::c_uint = 0;
+        let res = unsafe {
+            libc::syscall(libc::SYS_getcpu, &mut cpu, &mut node, core::ptr::null_mut::<libc::c_void>())
+        };
+        if res != 0 || node as usize >= libc::c_ulong::BITS as usize {
+            return;
+        }
+        let start = ptr as usize & !(PAGE - 1);
+        let end = (ptr as usize + len).next_multiple_of(PAGE);
+        let mask: libc::c_ulong = 1 << node;
+        unsafe {
+            libc::syscall(
+                libc::SYS_mbind, 
+                start, 
+                end - start, 
+                MPOL_PREFERRED, 
+                &mask as *const libc::c_ulong, 
+                libc::c_ulong::BITS as libc::c_ulong, 
+                MPOL_MF_MOVE);
+        }
+    }
+}
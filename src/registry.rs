@@ -0,0 +1,156 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::Debug,
+    sync::{RwLock, RwLockReadGuard},
+};
+
+use crate::Global;
+
+#[derive(Clone, Copy)]
+struct Entry {
+    global: &'static (dyn Any + Send + Sync),
+    type_name: &'static str,
+}
+
+#[derive(Default)]
+struct Entries {
+    types: HashMap<TypeId, Entry>,
+    names: HashMap<&'static str, Entry>,
+}
+
+static GLOBAL: GlobalRegistry = GlobalRegistry::new();
+
+///Registry of lazily evaluated values, looked up by type or by name at runtime.
+///
+///Plugin-style applications often don't know which values exist until runtime, so they can't
+///declare them as individual statics. Each registered value has the same semantics as a
+///[`Global`]: it is produced once, on first lookup, and lives for the rest of the program.
+///Entries can't be removed or replaced once registered.
+///
+///[`GlobalRegistry::global`] is the process-wide registry. With the `ctor` feature,
+///`register_globals!` adds entries to it at startup.
+///```rust
+///# use global_static::GlobalRegistry;
+///struct Renderer(&'static str);
+///
+///let registry = GlobalRegistry::global();
+///registry.register(|| Renderer("vulkan"));
+///registry.register_named("greeting", || "hello".to_owned());
+///
+///assert_eq!(registry.get::<Renderer>().unwrap().0, "vulkan");
+///assert_eq!(registry.get_named::<String>("greeting").unwrap(), "hello");
+///assert!(registry.get_named::<u32>("greeting").is_none());
+///```
+pub struct GlobalRegistry {
+    entries: RwLock<Option<Entries>>,
+}
+
+impl GlobalRegistry {
+    ///Constructs a new, empty registry.
+    pub const fn new() -> Self {
+        Self { entries: RwLock::new(None) }
+    }
+
+    ///The process-wide registry.
+    pub fn global() -> &'static Self {
+        &GLOBAL
+    }
+
+    ///Registers the value of type `T`, produced by `init` on its first lookup. Returns `false`
+    ///and does nothing if a value of this type is already registered.
+    pub fn register<T: Send + Sync + 'static>(&self, init: fn() -> T) -> bool {
+        let mut lock = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        let types = &mut lock.get_or_insert_with(Entries::default).types;
+        if types.contains_key(&TypeId::of::<T>()) {
+            return false;
+        }
+        types.insert(TypeId::of::<T>(), entry(init));
+        true
+    }
+
+    ///Registers a value under `name`, produced by `init` on its first lookup. Returns `false`
+    ///and does nothing if the name is already taken.
+    pub fn register_named<T: Send + Sync + 'static>(&self, name: &'static str, init: fn() -> T) -> bool {
+        let mut lock = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        let names = &mut lock.get_or_insert_with(Entries::default).names;
+        if names.contains_key(name) {
+            return false;
+        }
+        names.insert(name, entry(init));
+        true
+    }
+
+    ///Retrieves the value of type `T`, initializing it if needed. Returns `None` if it is not
+    ///registered.
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        let entry = *self.read().as_ref()?.types.get(&TypeId::of::<T>())?;
+        Some(entry.global.downcast_ref::<Global<T>>()?.force())
+    }
+
+    ///Retrieves the value registered under `name`, initializing it if needed. Returns `None` if
+    ///the name is not registered, or holds a value of a different type.
+    pub fn get_named<T: Send + Sync + 'static>(&self, name: &str) -> Option<&T> {
+        let entry = *self.read().as_ref()?.names.get(name)?;
+        Some(entry.global.downcast_ref::<Global<T>>()?.force())
+    }
+
+    ///Whether a value of type `T` is registered.
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.read().as_ref().is_some_and(|e| e.types.contains_key(&TypeId::of::<T>()))
+    }
+
+    ///Whether a value is registered under `name`.
+    pub fn contains_named(&self, name: &str) -> bool {
+        self.read().as_ref().is_some_and(|e| e.names.contains_key(name))
+    }
+
+    ///The registered names, in no particular order.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.read().as_ref().map_or_else(Vec::new, |e| e.names.keys().copied().collect())
+    }
+
+    //the value types of every entry, for auditing
+    #[cfg(feature = "instrumented")]
+    pub(crate) fn type_names(&self) -> Vec<&'static str> {
+        self.read().as_ref().map_or_else(Vec::new, |e| {
+            e.types.values().chain(e.names.values()).map(|e| e.type_name).collect()
+        })
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Option<Entries>> {
+        self.entries.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+//entries are leaked, so references to their values stay valid without holding the lock, and
+//initializers can look up other entries
+fn entry<T: Send + Sync + 'static>(init: fn() -> T) -> Entry {
+    Entry { global: Box::leak(Box::new(Global::<T>::new(init))), type_name: std::any::type_name::<T>() }
+}
+
+//resolves a dependency of a `#[singleton(inject)]` type
+#[doc(hidden)]
+pub fn __inject<T: Send + Sync + 'static>(singleton: &str) -> &'static T {
+    match GLOBAL.get::<T>() {
+        Some(v) => v,
+        None => panic!(
+            "`{singleton}` depends on `{}`, which is not registered in `GlobalRegistry::global()`",
+            std::any::type_name::<T>()),
+    }
+}
+
+impl Default for GlobalRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for GlobalRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lock = self.read();
+        let types: Vec<_> = lock.iter().flat_map(|e| e.types.values()).map(|e| e.type_name).collect();
+        let names: Vec<_> = lock.iter().flat_map(|e| e.names.keys()).collect();
+        f.debug_struct("GlobalRegistry").field("types", &types).field("names", &names).finish()
+    }
+}
@@ -0,0 +1,150 @@
+use std::{
+    cell::Cell,
+    fmt::Debug,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+//held by the scope that is running, so scopes in different threads take turns
+static ACTIVE: Mutex<()> = Mutex::new(());
+
+thread_local! {
+    static IN_SCOPE: Cell<bool> = const { Cell::new(false) };
+}
+
+trait Slot: Sync {
+    fn clear(&self);
+}
+
+///A run of code that [`ScopedGlobal`]s live for.
+///
+///Values of scoped globals are created on their first access within the scope, and dropped in
+///reverse order when it ends, even if it panics, so every run starts from a fresh state. This
+///covers globals that should be reset between integration tests or benchmark iterations.
+///
+///Only one scope runs at a time, so runs on other threads wait for it to end. The values can be
+///shared with [scoped threads](std::thread::scope) started within the scope.
+pub struct GlobalScope {
+    created: Mutex<Vec<&'static dyn Slot>>,
+    //also keeps the scope on the thread that started it, which tracks that it is in a scope
+    _lock: MutexGuard<'static, ()>,
+}
+
+///Drops the values created in the scope, including when it unwinds.
+struct Exit<'a>(&'a GlobalScope);
+
+impl Drop for Exit<'_> {
+    fn drop(&mut self) {
+        let created = std::mem::take(&mut *self.0.created.lock().unwrap_or_else(PoisonError::into_inner));
+        created.into_iter().rev().for_each(|slot| slot.clear());
+        IN_SCOPE.with(|s| s.set(false));
+    }
+}
+
+impl GlobalScope {
+    ///Runs `f` in a new scope, waiting for any scope running on another thread to end first.
+    ///```rust
+    ///# use global_static::{GlobalScope, ScopedGlobal};
+    ///# use std::sync::atomic::{AtomicU32, Ordering};
+    ///static CONNECTIONS: AtomicU32 = AtomicU32::new(0);
+    ///struct Db;
+    ///impl Drop for Db {
+    ///    fn drop(&mut self) { CONNECTIONS.fetch_sub(1, Ordering::Relaxed); }
+    ///}
+    ///static DB: ScopedGlobal<Db> = ScopedGlobal::new(|| { CONNECTIONS.fetch_add(1, Ordering::Relaxed); Db });
+    ///
+    ///for _ in 0..3 {
+    ///    GlobalScope::run(|scope| {
+    ///        let _db: &Db = DB.get(scope);
+    ///        assert_eq!(CONNECTIONS.load(Ordering::Relaxed), 1);
+    ///    });
+    ///    assert_eq!(CONNECTIONS.load(Ordering::Relaxed), 0);
+    ///}
+    ///```
+    ///
+    ///# Panics
+    ///Panics if called within another scope on the same thread, which would never end.
+    pub fn run<R>(f: impl FnOnce(&GlobalScope) -> R) -> R {
+        assert!(!IN_SCOPE.with(Cell::get), "`GlobalScope::run` called within another scope");
+        let lock = ACTIVE.lock().unwrap_or_else(PoisonError::into_inner);
+        IN_SCOPE.with(|s| s.set(true));
+        let scope = GlobalScope { created: Mutex::new(Vec::new()), _lock: lock };
+        let exit = Exit(&scope);
+        let res = f(&scope);
+        drop(exit);
+        res
+    }
+}
+
+impl Debug for GlobalScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let created = self.created.lock().unwrap_or_else(PoisonError::into_inner).len();
+        f.debug_struct("GlobalScope").field("created", &created).finish()
+    }
+}
+
+///Static whose value lives for a [`GlobalScope`] rather than the whole program.
+///
+///The value can only be accessed by passing in the running scope, and references to it can't
+///outlive that scope, so it can be dropped at the end without any unsafe code on the caller's
+///side. The next scope creates a new one.
+pub struct ScopedGlobal<T: Send + Sync + 'static> {
+    f: fn() -> T,
+    //boxed so references stay valid while other accesses lock the slot
+    value: Mutex<Option<Box<T>>>,
+}
+
+impl<T: Send + Sync + 'static> ScopedGlobal<T> {
+    ///Constructs a new scoped global from a function that produces its value for each scope.
+    pub const fn new(f: fn() -> T) -> Self {
+        Self { f, value: Mutex::new(None) }
+    }
+
+    ///Retrieves the value for `scope`, creating it if this is the first access within it. The
+    ///initializer runs without any locks held, so it can access other scoped globals.
+    pub fn get<'s>(&'static self, scope: &'s GlobalScope) -> &'s T {
+        if let Some(v) = self.current() {
+            return v;
+        }
+        let value = Box::new((self.f)());
+        let mut slot = self.lock();
+        let fresh = slot.is_none();
+        let ptr: *const T = &**slot.get_or_insert(value);
+        drop(slot);
+        if fresh {
+            scope.created.lock().unwrap_or_else(PoisonError::into_inner).push(self);
+        }
+        //safety: the value is only dropped when the scope ends, and `'s` doesn't outlive it
+        unsafe { &*ptr }
+    }
+
+    ///Whether the value for the running scope has been created.
+    pub fn is_initialized(&self) -> bool {
+        self.lock().is_some()
+    }
+
+    fn current(&self) -> Option<&T> {
+        //safety: see get
+        self.lock().as_deref().map(|v| unsafe { &*(v as *const T) })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Box<T>>> {
+        self.value.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: Send + Sync + 'static> Slot for ScopedGlobal<T> {
+    fn clear(&self) {
+        //dropped outside the lock, in case the value's drop accesses it
+        let value = self.lock().take();
+        drop(value);
+    }
+}
+
+impl<T: Send + Sync + Debug + 'static> Debug for ScopedGlobal<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &*self.lock() {
+            Some(v) => write!(f, "{v:?}"),
+            None => f.write_str("<outside of a scope>"),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    fmt::Debug,
+    panic::AssertUnwindSafe,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{AnyGlobal, Global};
+
+///Lazily evaluated static in two stages: a cheap basic value that is usable straight away, and an
+///expensive full value computed from it later, on first use or in the background.
+///
+///This is the warm-up pattern for large models or indexes, where a small or approximate
+///version can serve requests while the complete one is built. Each stage is computed at most
+///once. [`warm_up`](GlobalStaged::warm_up) starts the full stage on a background thread, and
+///registering the global with [`register`](crate::register) initializes the basic stage with
+///[`init_all`](crate::init_all) and generated ctors.
+///```rust
+///# use global_static::GlobalStaged;
+///struct Index { words: Vec<&'static str> }
+///
+///static INDEX: GlobalStaged<Vec<&str>, Index> = GlobalStaged::new(
+///    || vec!["the", "of", "and"],
+///    |common| Index { words: common.iter().copied().chain(["zebra", "zygote"]).collect() },
+///);
+///
+///assert_eq!(INDEX.get_basic().len(), 3);
+///INDEX.warm_up();
+///assert_eq!(INDEX.get_full_blocking().words.len(), 5);
+///```
+pub struct GlobalStaged<B: 'static, F: 'static> {
+    basic: Global<B>,
+    full: Global<F>,
+    upgrade: fn(&B) -> F,
+    //a background thread is computing the full stage
+    started: AtomicBool,
+}
+
+impl<B: 'static, F: 'static> GlobalStaged<B, F> {
+    ///Constructs a new global from a function that produces the basic stage, and one that
+    ///produces the full stage from it.
+    pub const fn new(basic: fn() -> B, full: fn(&B) -> F) -> Self {
+        Self {
+            basic: Global::new(basic),
+            //only ever initialized with get_or_init
+            full: Global::new(|| unreachable!("the full stage is computed from the basic stage")),
+            upgrade: full,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    ///Retrieves the basic stage, initializing it if needed.
+    pub fn get_basic(&self) -> &B {
+        self.basic.force()
+    }
+
+    ///Retrieves the full stage without computing it. Returns `None` if it isn't ready yet.
+    pub fn try_get_full(&self) -> Option<&F> {
+        self.full.get()
+    }
+
+    ///Retrieves the full stage, computing it on this thread if needed, or waiting for the
+    ///background thread if it is already computing it.
+    pub fn get_full_blocking(&self) -> &F {
+        self.full.get_or_init(|| (self.upgrade)(self.get_basic()))
+    }
+
+    ///Whether the full stage is ready.
+    pub fn is_full(&self) -> bool {
+        self.full.get().is_some()
+    }
+
+    ///Starts computing the full stage on a background thread, after the basic stage if that
+    ///isn't initialized yet. Does nothing if it is ready or already being computed. If it
+    ///panics, the next call starts again.
+    pub fn warm_up(&'static self)
+    where
+        B: Send + Sync,
+        F: Send + Sync,
+    {
+        if self.is_full() || self.started.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let spawned = std::thread::Builder::new().name("global-static-warm-up".to_owned()).spawn(move || {
+            //the panic is reported by the hook, and retried on the next access
+            let _ = std::panic::catch_unwind(AssertUnwindSafe(|| self.get_full_blocking()));
+            self.started.store(false, Ordering::Release);
+            //if it failed, tasks waiting in get_full compute it themselves
+            #[cfg(feature = "async")]
+            crate::wait::wake_all();
+        });
+        if spawned.is_err() {
+            self.started.store(false, Ordering::Release);
+        }
+    }
+
+    ///Waits for the full stage, [warming it up](GlobalStaged::warm_up) in the background if
+    ///needed, so the executor isn't blocked while it is computed. If computing it in the
+    ///background panics, it is computed again on the awaiting task.
+    ///```rust
+    ///# use global_static::GlobalStaged;
+    ///static MODEL: GlobalStaged<u32, u64> = GlobalStaged::new(|| 10, |&basic| basic as u64 * 1000);
+    ///
+    ///async fn predict() -> u64 {
+    ///    match MODEL.try_get_full() {
+    ///        Some(full) => *full,
+    ///        None => MODEL.get_full().await + 1,
+    ///    }
+    ///}
+    ///```
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub async fn get_full(&'static self) -> &'static F
+    where
+        B: Send + Sync,
+        F: Send + Sync,
+    {
+        use std::{future::Future, task::Poll};
+
+        self.warm_up();
+        let mut ready = std::pin::pin!(self.full.wait_ready());
+        //checked after registering to be woken, so a failure just before that isn't missed
+        std::future::poll_fn(|cx| match ready.as_mut().poll(cx) {
+            Poll::Pending if self.full.is_poisoned() && !self.started.load(Ordering::Acquire) => {
+                Poll::Ready(self.get_full_blocking())
+            },
+            poll => poll,
+        }).await
+    }
+}
+
+impl<B: Send + Sync, F: Send + Sync> AnyGlobal for GlobalStaged<B, F> {
+    fn init(&self) {
+        self.get_basic();
+    }
+    fn is_initialized(&self) -> bool {
+        self.basic.get().is_some()
+    }
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<B>()
+    }
+}
+
+impl<B: Debug, F: Debug> Debug for GlobalStaged<B, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GlobalStaged").field("basic", &self.basic.get()).field("full", &self.full.get()).finish()
+    }
+}
@@ -0,0 +1,394 @@
+use std::{
+    any::Any,
+    cell::Cell,
+    fmt::Write,
+    panic::AssertUnwindSafe,
+    sync::{atomic::AtomicUsize, Mutex},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "ctor")]
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use crate::AnyGlobal;
+
+static RECORDS: Mutex<Vec<StartupRecord>> = Mutex::new(Vec::new());
+static TOTAL: Mutex<Duration> = Mutex::new(Duration::ZERO);
+static LOG: Mutex<Option<fn(&str)>> = Mutex::new(None);
+//set by this crate's own ctor, so it shows whether ctors run at all
+#[cfg(feature = "ctor")]
+static CTOR_RAN: AtomicBool = AtomicBool::new(false);
+#[cfg(feature = "ctor")]
+static CTOR_POLICY: AtomicU8 = AtomicU8::new(CtorPolicy::Ignore as u8);
+#[cfg(feature = "ctor")]
+static WARNED: AtomicBool = AtomicBool::new(false);
+//failures from generated ctors not yet reported, by address of the global
+#[cfg(feature = "panic-safe-ctor")]
+static DEFERRED: Mutex<Vec<(usize, &'static str, String)>> = Mutex::new(Vec::new());
+#[cfg(feature = "panic-safe-ctor")]
+static ANY_DEFERRED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+thread_local! {
+    //set while this thread initializes globals for a ctor or `init_all`
+    static STARTUP_PASS: Cell<bool> = const { Cell::new(false) };
+}
+
+///What caused a global to be initialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InitMode {
+    ///A generated ctor, [`init_all_globals!`](crate::init_all_globals) or
+    ///[`init_all`](crate::init_all). This includes globals that were first accessed by another
+    ///global's initializer at that point.
+    Ctor,
+    ///The first access to the global afterwards.
+    Lazy,
+    ///A value given with [`set`](crate::Global::set).
+    Set,
+}
+
+///How and when a global was initialized. See [`Global::initialized`](crate::Global::initialized).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Initialized {
+    ///What caused the global to be initialized.
+    pub mode: InitMode,
+    ///When the initializer finished, or the value was set.
+    pub at: Instant,
+}
+
+///How initializing a single global went during startup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartupRecord {
+    ///The type of the global's value.
+    pub type_name: &'static str,
+    ///How long initialization took.
+    pub duration: Duration,
+    ///The panic message, if initialization failed.
+    pub failure: Option<String>,
+}
+
+///Summary of the globals initialized by generated ctors and [`init_all`](crate::init_all).
+#[derive(Debug, Clone, Default)]
+pub struct StartupSummary {
+    ///Time spent initializing globals. For [`init_all`](crate::init_all) this is wall time, so
+    ///globals initialized concurrently are counted once.
+    pub total: Duration,
+    ///Every global initialized, in the order they finished.
+    pub records: Vec<StartupRecord>,
+}
+
+impl StartupSummary {
+    ///The `n` slowest globals, slowest first.
+    pub fn slowest(&self, n: usize) -> Vec<&StartupRecord> {
+        let mut records: Vec<_> = self.records.iter().collect();
+        records.sort_by_key(|r| std::cmp::Reverse(r.duration));
+        records.truncate(n);
+        records
+    }
+
+    ///Globals whose initializer panicked.
+    pub fn failures(&self) -> impl Iterator<Item = &StartupRecord> {
+        self.records.iter().filter(|r| r.failure.is_some())
+    }
+
+    ///Formats the summary as a single line of JSON, with the number of globals initialized, the
+    ///total time, the five slowest globals, and any failures. Times are in microseconds.
+    ///```text
+    ///{"globals":2,"total_us":1520,"slowest":[{"global":"app::Config","us":1400},{"global":"u64","us":3}],"failures":[]}
+    ///```
+    pub fn to_json(&self) -> String {
+        let mut out = format!("{{\"globals\":{},\"total_us\":{},\"slowest\":[", self.records.len(), self.total.as_micros());
+        for (i, r) in self.slowest(5).into_iter().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            let _ = write!(out, "{sep}{{\"global\":{},\"us\":{}}}", json_str(r.type_name), r.duration.as_micros());
+        }
+        out.push_str("],\"failures\":[");
+        for (i, r) in self.failures().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            let error = r.failure.as_deref().unwrap_or_default();
+            let _ = write!(out, "{sep}{{\"global\":{},\"error\":{}}}", json_str(r.type_name), json_str(error));
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+fn json_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => { let _ = write!(out, "\\u{:04x}", c as u32); },
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+///A summary of startup initialization so far.
+///
+///Generated ctors run before `main`, so this can be logged from `main` to report on them;
+///[`set_startup_log`] only covers [`init_all`](crate::init_all).
+pub fn startup_summary() -> StartupSummary {
+    StartupSummary {
+        total: *TOTAL.lock().unwrap_or_else(|e| e.into_inner()),
+        records: RECORDS.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+    }
+}
+
+///Sets a function that is given the [JSON summary](StartupSummary::to_json) at the end of every
+///[`init_all`](crate::init_all), or `None` to stop logging. Nothing is logged by default.
+///```rust
+///global_static::set_startup_log(Some(|line| eprintln!("{line}")));
+///global_static::init_all();
+///```
+pub fn set_startup_log(log: Option<fn(&str)>) {
+    *LOG.lock().unwrap_or_else(|e| e.into_inner()) = log;
+}
+
+//initializes a global and records how it went, returning the panic if it failed
+pub(crate) fn init_recorded(global: &dyn AnyGlobal) -> Result<(), Box<dyn Any + Send>> {
+    if global.is_initialized() {
+        return Ok(());
+    }
+    let start = Instant::now();
+    let outer = STARTUP_PASS.with(|p| p.replace(true));
+    let res = std::panic::catch_unwind(AssertUnwindSafe(|| global.init()));
+    STARTUP_PASS.with(|p| p.set(outer));
+    let failure = res.as_ref().err().map(|p| crate::error::message(&**p));
+    let record = StartupRecord { type_name: global.type_name(), duration: elapsed(start), failure };
+    RECORDS.lock().unwrap_or_else(|e| e.into_inner()).push(record);
+    res
+}
+
+//how and when a global was initialized, and the heap memory it is expected to own, kept in the
+//global itself so the records move and are dropped with it
+pub(crate) struct InitState {
+    initialized: Mutex<Option<Initialized>>,
+    heap: AtomicUsize,
+}
+
+impl InitState {
+    pub(crate) const fn new() -> Self {
+        Self { initialized: Mutex::new(None), heap: AtomicUsize::new(0) }
+    }
+
+    //records that the global was just initialized, by a ctor if one is running on this thread,
+    //or set by hand
+    pub(crate) fn record(&self, set: bool) {
+        let mode = match (set, STARTUP_PASS.try_with(Cell::get).unwrap_or(false)) {
+            (true, _) => InitMode::Set,
+            (false, true) => InitMode::Ctor,
+            (false, false) => InitMode::Lazy,
+        };
+        *self.initialized.lock().unwrap_or_else(|e| e.into_inner()) = Some(Initialized { mode, at: Instant::now() });
+    }
+
+    //the global's value was taken out, so it is uninitialized again
+    pub(crate) fn forget(&self) {
+        *self.initialized.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    pub(crate) fn get(&self) -> Option<Initialized> {
+        *self.initialized.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn set_estimate(&self, bytes: usize) {
+        self.heap.store(bytes, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    pub(crate) fn estimate(&self) -> usize {
+        self.heap.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+///A registered global that isn't initialized yet, as listed by [`forecast`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingGlobal {
+    ///The type of the global's value.
+    pub type_name: &'static str,
+    ///The memory it is expected to take once initialized. See
+    ///[`Global::uninit_size`](crate::Global::uninit_size).
+    pub size: usize,
+}
+
+///The memory that initializing the remaining registered globals is expected to take.
+#[derive(Debug, Clone, Default)]
+pub struct Forecast {
+    ///The expected size of every pending global together, in bytes.
+    pub total: usize,
+    ///Every registered global that isn't initialized yet, largest first.
+    pub pending: Vec<PendingGlobal>,
+}
+
+impl Forecast {
+    ///The `n` globals expected to take the most memory, largest first.
+    pub fn largest(&self, n: usize) -> &[PendingGlobal] {
+        &self.pending[..n.min(self.pending.len())]
+    }
+}
+
+///Forecasts the memory that initializing every registered global still uninitialized would
+///take, before any of it happens. Memory-constrained deployments can use this to decide which
+///globals to warm at startup and which to leave to first use.
+///
+///Each global counts the size of its value, plus the heap memory given to
+///[`Global::estimate_heap`](crate::Global::estimate_heap). Only [`Global`](crate::Global)s
+///report a size, and globals that were never registered, such as with
+///[`register`](crate::register) or by `ctor_static!`, aren't included.
+///```rust
+///# use global_static::{Global, forecast, register};
+///static INDEX: Global<Vec<u64>> = Global::new(|| vec![0; 1 << 20]);
+///INDEX.estimate_heap(8 << 20);
+///register(&INDEX);
+///
+///let pending = forecast();
+///assert!(pending.total >= (8 << 20) + std::mem::size_of::<Vec<u64>>());
+///INDEX.init();
+///assert!(forecast().pending.iter().all(|g| g.type_name != "alloc::vec::Vec<u64>"));
+///```
+pub fn forecast() -> Forecast {
+    let mut pending: Vec<_> = crate::registered().into_iter()
+        .filter(|g| !g.is_initialized())
+        .map(|g| PendingGlobal { type_name: g.type_name(), size: g.uninit_size() })
+        .collect();
+    pending.sort_by_key(|g| std::cmp::Reverse(g.size));
+    Forecast { total: pending.iter().map(|g| g.size).sum(), pending }
+}
+
+//stores a ctor failure, to be raised on first access or by `check_startup`
+#[cfg(feature = "panic-safe-ctor")]
+pub(crate) fn defer(global: &'static dyn AnyGlobal, panic: &(dyn Any + Send)) {
+    let addr = global as *const dyn AnyGlobal as *const () as usize;
+    DEFERRED.lock().unwrap_or_else(|e| e.into_inner()).push((addr, global.type_name(), crate::error::message(panic)));
+    ANY_DEFERRED.store(true, std::sync::atomic::Ordering::Release);
+}
+
+//panics with the ctor failure of the global at `addr`, if there is one. The failure is only
+//raised once, so later accesses retry the initializer as usual
+#[cfg(feature = "panic-safe-ctor")]
+pub(crate) fn raise_deferred(addr: usize) {
+    if !ANY_DEFERRED.load(std::sync::atomic::Ordering::Acquire) {
+        return;
+    }
+    let mut deferred = DEFERRED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(i) = deferred.iter().position(|d| d.0 == addr) {
+        let (_, type_name, msg) = deferred.remove(i);
+        drop(deferred);
+        panic!("global `{type_name}` failed to initialize before main: {msg}");
+    }
+}
+
+///Panics if any global failed to initialize in a generated ctor, listing every failure that
+///hasn't already been raised by accessing its global.
+///
+///Panicking before `main` aborts the process, often without a useful message. With the
+///`panic-safe-ctor` feature, panics in generated ctors are caught instead, and raised with the
+///name of the global when it is first accessed. Calling this at the start of `main` reports them
+///all up front. Without the feature, this never panics.
+///```rust
+///global_static::check_startup();
+///```
+pub fn check_startup() {
+    #[cfg(feature = "panic-safe-ctor")]
+    {
+        let deferred = DEFERRED.lock().unwrap_or_else(|e| e.into_inner());
+        if !deferred.is_empty() {
+            let list: Vec<_> = deferred.iter().map(|(_, t, msg)| format!("`{t}`: {msg}")).collect();
+            let n = list.len();
+            drop(deferred);
+            panic!("{n} global(s) failed to initialize before main: {}", list.join("; "));
+        }
+    }
+}
+
+#[cfg(all(feature = "ctor", not(any(feature = "ctor-fallback", target_family = "wasm"))))]
+#[ctor::ctor]
+fn mark_ctor_ran() {
+    CTOR_RAN.store(true, Ordering::Release);
+}
+
+///Whether ctors ran before `main`. This is `false` on platforms without life-before-main, with
+///the `ctor-fallback` feature, or if the platform's loader skipped them, in which case globals
+///from `ctor_static!` and `#[singleton]` are only initialized on first access.
+///```rust
+///assert_eq!(global_static::ctor_ran(), !cfg!(any(feature = "ctor-fallback", target_family = "wasm")));
+///```
+#[cfg(feature = "ctor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ctor")))]
+pub fn ctor_ran() -> bool {
+    CTOR_RAN.load(Ordering::Acquire)
+}
+
+///What to do when a global is first initialized lazily in a process where
+///[ctors didn't run](ctor_ran). See [`set_ctor_policy`].
+#[cfg(feature = "ctor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ctor")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+pub enum CtorPolicy {
+    ///Initialize it lazily without saying anything, which is the default.
+    #[default]
+    Ignore,
+    ///Print a warning to stderr the first time, then initialize it lazily.
+    Warn,
+    ///Panic instead of initializing it.
+    Panic,
+}
+
+///Sets what happens when a global is first initialized lazily in a process where
+///[ctors didn't run](ctor_ran), so builds for a platform where they silently don't run fail
+///loudly rather than only being slower on first access. Call it at the start of `main`.
+///```rust
+///# use global_static::{CtorPolicy, Global};
+///global_static::set_ctor_policy(CtorPolicy::Panic);
+///# #[cfg(not(any(feature = "ctor-fallback", target_family = "wasm")))] {
+///static PORT: Global<u16> = Global::new(|| 8080);
+///assert_eq!(*PORT, 8080);
+///# }
+///```
+#[cfg(feature = "ctor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ctor")))]
+pub fn set_ctor_policy(policy: CtorPolicy) {
+    CTOR_POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+//applies the ctor policy before a global is initialized lazily
+#[cfg(feature = "ctor")]
+pub(crate) fn check_ctor_ran(type_name: &str) {
+    if ctor_ran() {
+        return;
+    }
+    match CTOR_POLICY.load(Ordering::Relaxed) {
+        p if p == CtorPolicy::Warn as u8 && !WARNED.swap(true, Ordering::Relaxed) => {
+            eprintln!("warning: ctors did not run before main, so `{type_name}` and other globals are initialized lazily");
+        },
+        p if p == CtorPolicy::Panic as u8 => {
+            panic!("ctors did not run before main, so `{type_name}` would be initialized lazily");
+        },
+        _ => {},
+    }
+}
+
+pub(crate) fn add_time(start: Instant) {
+    *TOTAL.lock().unwrap_or_else(|e| e.into_inner()) += elapsed(start);
+}
+
+pub(crate) fn log() {
+    let log = *LOG.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(log) = log {
+        log(&startup_summary().to_json());
+    }
+}
+
+//timing is disabled in deterministic mode so summaries are reproducible
+fn elapsed(start: Instant) -> Duration {
+    if cfg!(feature = "deterministic") {
+        Duration::ZERO
+    } else {
+        start.elapsed()
+    }
+}
@@ -0,0 +1,170 @@
+use std::{fmt::Debug, sync::OnceLock};
+
+use crate::{Error, ErrorKind};
+
+enum Source {
+    Fn(fn() -> String),
+    Env(&'static str, Option<&'static str>),
+    File(&'static str),
+}
+
+///Lazily evaluated static string.
+///
+///This is a convenience for the common pattern of producing a `String` once (a hostname, a key
+///prefix, a path) and using it as a `&'static str` afterwards. The value can be produced by a
+///function, read from an environment variable, or read from a file.
+///```rust
+///# use global_static::GlobalStr;
+///static PREFIX: GlobalStr = GlobalStr::new(|| format!("{}-", "app"));
+///static HOST: GlobalStr = GlobalStr::env_or("MY_APP_HOST", "localhost");
+///
+///assert_eq!(PREFIX, "app-");
+///assert_eq!(HOST.as_str(), "localhost");
+///```
+pub struct GlobalStr {
+    source: Source,
+    data: OnceLock<Result<Box<str>, Error>>,
+}
+
+impl GlobalStr {
+    ///Constructs a new global string from a function that produces it.
+    pub const fn new(f: fn() -> String) -> Self {
+        Self { source: Source::Fn(f), data: OnceLock::new() }
+    }
+
+    ///Constructs a new global string from an environment variable. Accessing it panics if the
+    ///variable is not set or is not valid unicode; use [`try_as_str`](GlobalStr::try_as_str) to
+    ///handle this instead.
+    pub const fn env(var: &'static str) -> Self {
+        Self { source: Source::Env(var, None), data: OnceLock::new() }
+    }
+
+    ///Constructs a new global string from an environment variable, using `default` if it is not
+    ///set.
+    pub const fn env_or(var: &'static str, default: &'static str) -> Self {
+        Self { source: Source::Env(var, Some(default)), data: OnceLock::new() }
+    }
+
+    ///Constructs a new global string from the contents of a file, with trailing newlines
+    ///removed. Accessing it panics if the file cannot be read; use
+    ///[`try_as_str`](GlobalStr::try_as_str) to handle this instead.
+    pub const fn file(path: &'static str) -> Self {
+        Self { source: Source::File(path), data: OnceLock::new() }
+    }
+
+    ///Initializes the contents of a global. Does nothing if already initialized.
+    ///
+    ///Panics if the string could not be read, unless the `no-panic` feature is enabled, in which
+    ///case the error is stored and returned by [`try_as_str`](GlobalStr::try_as_str).
+    pub fn init(&self) {
+        #[cfg(not(feature = "no-panic"))]
+        self.as_str();
+        #[cfg(feature = "no-panic")]
+        let _ = self.try_as_str();
+    }
+
+    ///Retrieves the string without initializing it. Returns `None` if reading it failed.
+    pub fn get(&self) -> Option<&str> {
+        self.data.get()?.as_deref().ok()
+    }
+
+    ///Retrieves the string, initializing it if needed. Panics if it could not be read.
+    #[cfg(not(feature = "no-panic"))]
+    pub fn as_str(&self) -> &str {
+        match self.try_as_str() {
+            Ok(s) => s,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    ///Retrieves the string, initializing it if needed. If it could not be read, the error is
+    ///stored and returned on every access.
+    pub fn try_as_str(&self) -> Result<&str, &Error> {
+        self.data.get_or_init(|| self.produce().map(String::into_boxed_str)).as_deref()
+    }
+
+    fn produce(&self) -> Result<String, Error> {
+        match self.source {
+            Source::Fn(f) => Ok(f()),
+            Source::Env(var, default) => match (std::env::var(var), default) {
+                (Ok(val), _) => Ok(val),
+                (Err(std::env::VarError::NotPresent), Some(default)) => Ok(default.to_owned()),
+                (Err(e), _) => Err(Error::new(var, ErrorKind::Env(e))),
+            },
+            Source::File(path) => match std::fs::read_to_string(path) {
+                Ok(mut val) => {
+                    let len = val.trim_end_matches(['\n', '\r']).len();
+                    val.truncate(len);
+                    Ok(val)
+                },
+                Err(e) => Err(Error::new(path, ErrorKind::Io(e))),
+            },
+        }
+    }
+}
+
+#[cfg(not(feature = "no-panic"))]
+impl std::ops::Deref for GlobalStr {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+#[cfg(not(feature = "no-panic"))]
+impl AsRef<str> for GlobalStr {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+#[cfg(not(feature = "no-panic"))]
+impl PartialEq for GlobalStr {
+    fn eq(&self, other: &Self) -> bool {
+        //the same static is always equal to itself, no need to compare bytes
+        std::ptr::eq(self, other) || self.as_str() == other.as_str()
+    }
+}
+#[cfg(not(feature = "no-panic"))]
+impl Eq for GlobalStr {}
+
+#[cfg(not(feature = "no-panic"))]
+impl PartialEq<str> for GlobalStr {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+#[cfg(not(feature = "no-panic"))]
+impl PartialEq<&str> for GlobalStr {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+#[cfg(not(feature = "no-panic"))]
+impl PartialEq<GlobalStr> for str {
+    fn eq(&self, other: &GlobalStr) -> bool {
+        self == other.as_str()
+    }
+}
+#[cfg(not(feature = "no-panic"))]
+impl PartialEq<GlobalStr> for &str {
+    fn eq(&self, other: &GlobalStr) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl Debug for GlobalStr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.try_as_str() {
+            Ok(s) => write!(f, "{s:?}"),
+            Err(e) => write!(f, "<{e}>"),
+        }
+    }
+}
+#[cfg(not(feature = "no-panic"))]
+impl std::fmt::Display for GlobalStr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    sync::{Arc, RwLock, RwLockWriteGuard},
+};
+
+///A hook called with the old and new values of a [`SwapGlobal`].
+pub type SwapHook<T> = fn(&T, &T);
+
+struct State<T> {
+    current: Option<Arc<T>>,
+    version: u64,
+    //oldest first
+    history: VecDeque<(u64, Arc<T>)>,
+}
+
+///Lazily evaluated static that can be replaced at runtime.
+///
+///Readers get a consistent [`Arc`] snapshot of the current value, which stays valid even if the
+///value is swapped out afterwards, so a background task can replace the value (such as reloaded
+///configuration) without readers ever observing a partial update.
+///```rust
+///# use global_static::SwapGlobal;
+///static LEVEL: SwapGlobal<u8> = SwapGlobal::new(|| 1);
+///
+///let before = LEVEL.load();
+///LEVEL.swap(2);
+///assert_eq!((*before, *LEVEL.load()), (1, 2));
+///```
+pub struct SwapGlobal<T> {
+    f: fn() -> T,
+    data: RwLock<State<T>>,
+    hooks: RwLock<Vec<SwapHook<T>>>,
+    history_len: usize,
+}
+
+impl<T> SwapGlobal<T> {
+    ///Constructs a new global from a function that produces its initial value.
+    pub const fn new(f: fn() -> T) -> Self {
+        Self { 
+            f, 
+            data: RwLock::new(State { current: None, version: 0, history: VecDeque::new() }), 
+            hooks: RwLock::new(Vec::new()),
+            history_len: 0,
+        }
+    }
+
+    ///Keeps up to `len` previous values around, so they can be restored with
+    ///[`rollback`](SwapGlobal::rollback).
+    ///```rust
+    ///# use global_static::SwapGlobal;
+    ///static CONFIG: SwapGlobal<&str> = SwapGlobal::new(|| "v1").with_history(8);
+    ///
+    ///CONFIG.swap("v2");
+    ///CONFIG.swap("broken");
+    ///CONFIG.rollback(1);
+    ///assert_eq!(*CONFIG.load(), "v2");
+    ///```
+    pub const fn with_history(mut self, len: usize) -> Self {
+        self.history_len = len;
+        self
+    }
+
+    ///Returns a snapshot of the current value, initializing it if needed.
+    pub fn load(&self) -> Arc<T> {
+        if let Some(v) = &self.data.read().unwrap_or_else(|e| e.into_inner()).current {
+            return v.clone();
+        }
+        self.write().current.get_or_insert_with(|| Arc::new((self.f)())).clone()
+    }
+
+    ///Initializes the contents of a global. Does nothing if already initialized.
+    pub fn init(&self) {
+        self.load();
+    }
+
+    ///Whether the global has been initialized.
+    pub fn is_initialized(&self) -> bool {
+        self.data.read().unwrap_or_else(|e| e.into_inner()).current.is_some()
+    }
+
+    ///The version of the current value. The initial value is version 0, and every swap or
+    ///rollback produces a new, higher version.
+    pub fn version(&self) -> u64 {
+        self.data.read().unwrap_or_else(|e| e.into_inner()).version
+    }
+
+    ///Returns a snapshot of the current value along with its version, initializing it if
+    ///needed. Pass the version to [`compare_and_swap`](SwapGlobal::compare_and_swap) to replace
+    ///the value only if it hasn't changed since.
+    pub fn load_versioned(&self) -> (u64, Arc<T>) {
+        let state = self.data.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(v) = &state.current {
+            return (state.version, v.clone());
+        }
+        drop(state);
+        let mut state = self.write();
+        let v = state.current.get_or_insert_with(|| Arc::new((self.f)())).clone();
+        (state.version, v)
+    }
+
+    ///Previous values that can be rolled back to, with their versions, most recent first.
+    pub fn history(&self) -> Vec<(u64, Arc<T>)> {
+        self.data.read().unwrap_or_else(|e| e.into_inner()).history.iter().rev().cloned().collect()
+    }
+
+    ///Replaces the value, returning the previous one.
+    ///
+    ///Transition hooks registered with [`on_swap`](SwapGlobal::on_swap) run exactly once per
+    ///swap, before the new value becomes visible to readers. If the global was never
+    ///initialized, the initializer runs first so that hooks always see an old value.
+    pub fn swap(&self, value: T) -> Arc<T> {
+        self.replace(&mut self.write(), value)
+    }
+
+    ///Replaces the value only if it is still at version `expected`, returning the previous
+    ///value. Otherwise, returns the version that replaced it along with `value`, so writers that
+    ///raced, such as a reload and an admin API, don't silently overwrite each other's changes.
+    ///```rust
+    ///# use global_static::SwapGlobal;
+    ///static CONFIG: SwapGlobal<&str> = SwapGlobal::new(|| "v1");
+    ///
+    ///let (version, _) = CONFIG.load_versioned();
+    ///CONFIG.swap("reloaded");
+    ///assert_eq!(CONFIG.compare_and_swap(version, "edited"), Err((1, "edited")));
+    ///assert_eq!(*CONFIG.compare_and_swap(1, "edited").unwrap(), "reloaded");
+    ///```
+    pub fn compare_and_swap(&self, expected: u64, value: T) -> Result<Arc<T>, (u64, T)> {
+        let mut state = self.write();
+        if state.version != expected {
+            return Err((state.version, value));
+        }
+        Ok(self.replace(&mut state, value))
+    }
+
+    ///Replaces the value. This is [`swap`](SwapGlobal::swap) without returning the previous
+    ///value.
+    pub fn store(&self, value: T) {
+        self.swap(value);
+    }
+
+    ///Restores the value from `n` versions ago, discarding the newer ones. Returns the value
+    ///that was replaced, or `None` if fewer than `n` previous values are kept.
+    pub fn rollback(&self, n: usize) -> Option<Arc<T>> {
+        let mut state = self.write();
+        if n == 0 || n > state.history.len() {
+            return None;
+        }
+        let keep = state.history.len() - n;
+        let (_, restored) = state.history.drain(keep..).next()?;
+        let old = state.current.clone();
+        self.transition(&mut state, restored);
+        old
+    }
+
+    ///Registers a hook that is called with the old and new values on every swap. Hooks must not
+    ///access this global, as it is locked while they run.
+    ///```rust
+    ///# use global_static::SwapGlobal;
+    ///static LIMIT: SwapGlobal<u32> = SwapGlobal::new(|| 10);
+    ///
+    ///LIMIT.on_swap(|old, new| println!("limit changed from {old} to {new}"));
+    ///LIMIT.swap(20);
+    ///```
+    pub fn on_swap(&self, hook: SwapHook<T>) {
+        self.hooks.write().unwrap_or_else(|e| e.into_inner()).push(hook);
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, State<T>> {
+        self.data.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn replace(&self, state: &mut State<T>, value: T) -> Arc<T> {
+        let old = state.current.get_or_insert_with(|| Arc::new((self.f)())).clone();
+        self.transition(state, Arc::new(value));
+        if self.history_len > 0 {
+            if state.history.len() == self.history_len {
+                state.history.pop_front();
+            }
+            let version = state.version - 1;
+            state.history.push_back((version, old.clone()));
+        }
+        old
+    }
+
+    fn transition(&self, state: &mut State<T>, new: Arc<T>) {
+        if let Some(old) = &state.current {
+            for hook in self.hooks.read().unwrap_or_else(|e| e.into_inner()).iter() {
+                hook(old, &new);
+            }
+        }
+        state.current = Some(new);
+        state.version += 1;
+    }
+}
+
+impl<T: Debug> Debug for SwapGlobal<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.load())
+    }
+}
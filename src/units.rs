@@ -0,0 +1,321 @@
+use std::{fmt::Debug, sync::OnceLock, time::Duration};
+
+use crate::{Error, ErrorKind};
+
+enum Source {
+    Fn(fn() -> String),
+    Env(&'static str, Option<&'static str>),
+}
+
+//shared by the duration and size globals, which only differ in how they parse
+struct Parsed<T> {
+    source: Source,
+    parse: fn(&str) -> Result<T, ParseUnitError>,
+    data: OnceLock<Result<T, Error>>,
+}
+
+impl<T: Copy> Parsed<T> {
+    const fn new(source: Source, parse: fn(&str) -> Result<T, ParseUnitError>) -> Self {
+        Self { source, parse, data: OnceLock::new() }
+    }
+
+    fn get(&self) -> Option<T> {
+        self.data.get()?.as_ref().ok().copied()
+    }
+
+    fn try_get(&self) -> Result<T, &Error> {
+        self.data.get_or_init(|| self.produce()).as_ref().copied()
+    }
+
+    fn produce(&self) -> Result<T, Error> {
+        let (name, value) = match self.source {
+            Source::Fn(f) => (std::any::type_name::<T>(), f()),
+            Source::Env(var, default) => match (std::env::var(var), default) {
+                (Ok(val), _) => (var, val),
+                (Err(std::env::VarError::NotPresent), Some(default)) => (var, default.to_owned()),
+                (Err(e), _) => return Err(Error::new(var, ErrorKind::Env(e))),
+            },
+        };
+        (self.parse)(&value).map_err(|e| Error::new(name, ErrorKind::Parse(Box::new(e))))
+    }
+}
+
+///Error produced when a duration or size can't be parsed, with the text that was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseUnitError {
+    input: String,
+    reason: &'static str,
+}
+
+impl ParseUnitError {
+    fn new(input: &str, reason: &'static str) -> Self {
+        Self { input: input.to_owned(), reason }
+    }
+}
+
+impl std::fmt::Display for ParseUnitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` {}", self.input, self.reason)
+    }
+}
+
+impl std::error::Error for ParseUnitError {}
+
+//splits `12.5ms` into 12.5 and `ms`, and returns the rest of the string
+fn quantity(s: &str) -> Option<(f64, &str, &str)> {
+    let s = s.trim_start();
+    let num_len = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+    let num = s[..num_len].parse().ok()?;
+    let rest = s[num_len..].trim_start();
+    let unit_len = rest.find(|c: char| !c.is_alphabetic()).unwrap_or(rest.len());
+    Some((num, &rest[..unit_len], &rest[unit_len..]))
+}
+
+fn parse_duration(input: &str) -> Result<Duration, ParseUnitError> {
+    let mut rest = input.trim();
+    if rest.is_empty() {
+        return Err(ParseUnitError::new(input, "is empty, expected a duration such as `30s`"));
+    }
+    let mut total = 0.0;
+    while !rest.trim().is_empty() {
+        let Some((num, unit, tail)) = quantity(rest) else {
+            return Err(ParseUnitError::new(input, "is not a duration, expected a number with a unit such as `30s`"));
+        };
+        let secs = match unit {
+            "ns" => 1e-9,
+            "us" | "µs" => 1e-6,
+            "ms" => 1e-3,
+            "s" | "sec" | "secs" => 1.0,
+            "m" | "min" | "mins" => 60.0,
+            "h" | "hr" | "hrs" => 3600.0,
+            "d" | "day" | "days" => 86400.0,
+            "" => return Err(ParseUnitError::new(input, "is missing a unit, such as `s` or `ms`")),
+            _ => return Err(ParseUnitError::new(input, "has an unknown unit, expected one of `ns`, `us`, `ms`, `s`, `m`, `h` or `d`")),
+        };
+        total += num * secs;
+        rest = tail;
+    }
+    Duration::try_from_secs_f64(total).map_err(|_| ParseUnitError::new(input, "is too long"))
+}
+
+fn parse_size(input: &str) -> Result<u64, ParseUnitError> {
+    let Some((num, unit, _)) = quantity(input).filter(|(.., tail)| tail.trim().is_empty()) else {
+        return Err(ParseUnitError::new(input, "is not a size, expected a number with an optional unit such as `512MB`"));
+    };
+    let scale: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000_u64.pow(2),
+        "gb" => 1000_u64.pow(3),
+        "tb" => 1000_u64.pow(4),
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        "t" | "tib" => 1 << 40,
+        _ => return Err(ParseUnitError::new(input, "has an unknown unit, expected one such as `KB`, `MiB` or `G`")),
+    };
+    let bytes = num * scale as f64;
+    if bytes.fract() != 0.0 {
+        return Err(ParseUnitError::new(input, "is not a whole number of bytes"));
+    }
+    if bytes >= u64::MAX as f64 {
+        return Err(ParseUnitError::new(input, "is too large"));
+    }
+    Ok(bytes as u64)
+}
+
+///Lazily evaluated static duration, parsed from a human-friendly string such as `30s`, `250ms`
+///or `1h30m`.
+///
+///The units are `ns`, `us`, `ms`, `s`, `m`, `h` and `d`, and numbers may have a fraction, as in
+///`1.5s`. A number without a unit is rejected rather than guessed at. If the value can't be read
+///or parsed, the error names the variable and the text that was rejected.
+///```rust
+///# use global_static::GlobalDuration;
+///# use std::time::Duration;
+///static TIMEOUT: GlobalDuration = GlobalDuration::env_or("APP_TIMEOUT", "30s");
+///
+///assert_eq!(*TIMEOUT, Duration::from_secs(30));
+///assert_eq!(GlobalDuration::parse("1h30m"), Ok(Duration::from_secs(5400)));
+///assert!(GlobalDuration::parse("30").unwrap_err().to_string().contains("missing a unit"));
+///```
+pub struct GlobalDuration(Parsed<Duration>);
+
+impl GlobalDuration {
+    ///Constructs a new global duration from a function that produces the text to parse, such
+    ///as a field of a configuration file.
+    pub const fn new(f: fn() -> String) -> Self {
+        Self(Parsed::new(Source::Fn(f), parse_duration))
+    }
+
+    ///Constructs a new global duration from an environment variable. Accessing it panics if the
+    ///variable is not set or can't be parsed; use [`try_duration`](GlobalDuration::try_duration)
+    ///to handle this instead.
+    pub const fn env(var: &'static str) -> Self {
+        Self(Parsed::new(Source::Env(var, None), parse_duration))
+    }
+
+    ///Constructs a new global duration from an environment variable, parsing `default` if it is
+    ///not set.
+    pub const fn env_or(var: &'static str, default: &'static str) -> Self {
+        Self(Parsed::new(Source::Env(var, Some(default)), parse_duration))
+    }
+
+    ///Parses a duration the way these globals do.
+    pub fn parse(s: &str) -> Result<Duration, ParseUnitError> {
+        parse_duration(s)
+    }
+
+    ///Initializes the contents of a global. Does nothing if already initialized.
+    ///
+    ///Panics if the duration could not be read or parsed, unless the `no-panic` feature is
+    ///enabled, in which case the error is stored and returned by
+    ///[`try_duration`](GlobalDuration::try_duration).
+    pub fn init(&self) {
+        #[cfg(not(feature = "no-panic"))]
+        self.duration();
+        #[cfg(feature = "no-panic")]
+        let _ = self.try_duration();
+    }
+
+    ///Retrieves the duration without initializing it. Returns `None` if reading it failed.
+    pub fn get(&self) -> Option<Duration> {
+        self.0.get()
+    }
+
+    ///Retrieves the duration, initializing it if needed. Panics if it could not be read or
+    ///parsed.
+    #[cfg(not(feature = "no-panic"))]
+    pub fn duration(&self) -> Duration {
+        match self.try_duration() {
+            Ok(d) => d,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    ///Retrieves the duration, initializing it if needed. If it could not be read or parsed, the
+    ///error is stored and returned on every access.
+    pub fn try_duration(&self) -> Result<Duration, &Error> {
+        self.0.try_get()
+    }
+}
+
+#[cfg(not(feature = "no-panic"))]
+impl std::ops::Deref for GlobalDuration {
+    type Target = Duration;
+
+    fn deref(&self) -> &Self::Target {
+        self.duration();
+        //initialized just above
+        match self.0.data.get() {
+            Some(Ok(d)) => d,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Debug for GlobalDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.try_duration() {
+            Ok(d) => write!(f, "{d:?}"),
+            Err(e) => write!(f, "<{e}>"),
+        }
+    }
+}
+
+///Lazily evaluated static size in bytes, parsed from a human-friendly string such as `512MB`,
+///`64KiB` or `2G`.
+///
+///Units are case-insensitive. `KB`, `MB`, `GB` and `TB` are powers of 1000, while `KiB`, `MiB`,
+///`GiB` and `TiB` and the single letters `K`, `M`, `G` and `T` are powers of 1024, as in most
+///command line tools. A number without a unit is a number of bytes. If the value can't be read
+///or parsed, the error names the variable and the text that was rejected.
+///```rust
+///# use global_static::GlobalSize;
+///static CACHE_SIZE: GlobalSize = GlobalSize::env_or("APP_CACHE_SIZE", "512MB");
+///
+///assert_eq!(*CACHE_SIZE, 512_000_000);
+///assert_eq!(GlobalSize::parse("1.5 KiB"), Ok(1536));
+///```
+pub struct GlobalSize(Parsed<u64>);
+
+impl GlobalSize {
+    ///Constructs a new global size from a function that produces the text to parse, such as a
+    ///field of a configuration file.
+    pub const fn new(f: fn() -> String) -> Self {
+        Self(Parsed::new(Source::Fn(f), parse_size))
+    }
+
+    ///Constructs a new global size from an environment variable. Accessing it panics if the
+    ///variable is not set or can't be parsed; use [`try_bytes`](GlobalSize::try_bytes) to handle
+    ///this instead.
+    pub const fn env(var: &'static str) -> Self {
+        Self(Parsed::new(Source::Env(var, None), parse_size))
+    }
+
+    ///Constructs a new global size from an environment variable, parsing `default` if it is not
+    ///set.
+    pub const fn env_or(var: &'static str, default: &'static str) -> Self {
+        Self(Parsed::new(Source::Env(var, Some(default)), parse_size))
+    }
+
+    ///Parses a size the way these globals do.
+    pub fn parse(s: &str) -> Result<u64, ParseUnitError> {
+        parse_size(s)
+    }
+
+    ///Initializes the contents of a global. Does nothing if already initialized.
+    ///
+    ///Panics if the size could not be read or parsed, unless the `no-panic` feature is enabled,
+    ///in which case the error is stored and returned by [`try_bytes`](GlobalSize::try_bytes).
+    pub fn init(&self) {
+        #[cfg(not(feature = "no-panic"))]
+        self.bytes();
+        #[cfg(feature = "no-panic")]
+        let _ = self.try_bytes();
+    }
+
+    ///Retrieves the size without initializing it. Returns `None` if reading it failed.
+    pub fn get(&self) -> Option<u64> {
+        self.0.get()
+    }
+
+    ///Retrieves the size in bytes, initializing it if needed. Panics if it could not be read or
+    ///parsed.
+    #[cfg(not(feature = "no-panic"))]
+    pub fn bytes(&self) -> u64 {
+        match self.try_bytes() {
+            Ok(b) => b,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    ///Retrieves the size in bytes, initializing it if needed. If it could not be read or parsed,
+    ///the error is stored and returned on every access.
+    pub fn try_bytes(&self) -> Result<u64, &Error> {
+        self.0.try_get()
+    }
+}
+
+#[cfg(not(feature = "no-panic"))]
+impl std::ops::Deref for GlobalSize {
+    type Target = u64;
+
+    fn deref(&self) -> &Self::Target {
+        self.bytes();
+        //initialized just above
+        match self.0.data.get() {
+            Some(Ok(b)) => b,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Debug for GlobalSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.try_bytes() {
+            Ok(b) => write!(f, "{b}"),
+            Err(e) => write!(f, "<{e}>"),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//!Teardown for libraries that can be unloaded.
+//!
+//!A `cdylib` loaded by a host with `dlopen`/`LoadLibrary` cannot rely on life-before-main, and may
+//!be unloaded while the host keeps running. Globals that own resources (files, threads, handles
+//!into the host) should register a teardown with [`on_unload`], and the library's unload entry
+//!point should call [`unload`].
+//!```rust
+//!# use global_static::{Global, unload};
+//!# use std::sync::Mutex;
+//!static LOG: Global<Mutex<Vec<String>>> = Global::new(|| {
+//!    unload::on_unload(|| LOG.lock().unwrap().clear());
+//!    Mutex::new(Vec::new())
+//!});
+//!
+//!#[no_mangle]
+//!pub extern "C" fn plugin_unload() {
+//!    unload::unload();
+//!}
+//!```
+use std::sync::Mutex;
+
+static TEARDOWNS: Mutex<Vec<fn()>> = Mutex::new(Vec::new());
+
+///Registers a function to run when [`unload`] is called.
+pub fn on_unload(f: fn()) {
+    TEARDOWNS.lock().unwrap_or_else(|e| e.into_inner()).push(f);
+}
+
+///Runs every registered teardown, most recently registered first. Each teardown runs once, so
+///calling this again only runs teardowns registered since.
+pub fn unload() {
+    let teardowns = std::mem::take(&mut *TEARDOWNS.lock().unwrap_or_else(|e| e.into_inner()));
+    for f in teardowns.into_iter().rev() {
+        f();
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    fmt::{self, Display},
+    future::Future,
+    panic::AssertUnwindSafe,
+    pin::{pin, Pin},
+    sync::{atomic::Ordering, Arc, Condvar, Mutex},
+    task::{Context, Poll, Wake, Waker},
+    thread::Thread,
+};
+
+use crate::{error, Global, Init, OnceBackend};
+
+//wakers are shared between all globals, since finishing any initialization is rare enough that
+//spurious wakeups are cheaper than a waker list per global
+static WAITERS: Mutex<Vec<Waker>> = Mutex::new(Vec::new());
+
+//addresses of globals with a bound future that hasn't finished yet
+static BOUND: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+static UNBOUND: Condvar = Condvar::new();
+
+//panic messages of bound futures that failed, reported when a global made with from_future is
+//accessed. bound globals are static, so their addresses stay valid
+static FAILED: Mutex<Vec<(usize, String)>> = Mutex::new(Vec::new());
+
+//blocks until the future bound to the global at `addr` has finished, if there is one
+pub(crate) fn wait_bound(addr: usize) {
+    let bound = BOUND.lock().unwrap_or_else(|e| e.into_inner());
+    drop(UNBOUND.wait_while(bound, |b| b.contains(&addr)).unwrap_or_else(|e| e.into_inner()));
+}
+
+struct Unpark(Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = pin!(fut);
+    let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(value) = fut.as_mut().poll(&mut cx) {
+            return value;
+        }
+        std::thread::park();
+    }
+}
+
+fn unbound<T>(addr: usize) -> T {
+    let failed = FAILED.lock().unwrap_or_else(|e| e.into_inner())
+        .iter().find(|(a, _)| *a == addr).map(|(_, msg)| msg.clone());
+    match failed {
+        Some(msg) => panic!("the future bound to `{}` panicked: {msg}", core::any::type_name::<T>()),
+        None => panic!("`{}` was accessed before a future was bound to it", core::any::type_name::<T>()),
+    }
+}
+
+fn set_failed(addr: usize, msg: Option<String>) {
+    let mut failed = FAILED.lock().unwrap_or_else(|e| e.into_inner());
+    failed.retain(|(a, _)| *a != addr);
+    failed.extend(msg.map(|msg| (addr, msg)));
+}
+
+pub(crate) fn wake_all() {
+    let waiters = std::mem::take(&mut *WAITERS.lock().unwrap_or_else(|e| e.into_inner()));
+    for waker in waiters {
+        waker.wake();
+    }
+}
+
+///Future returned by [`Global::wait_ready`].
+pub struct WaitReady<'a, T, B: OnceBackend<T>> {
+    global: &'a Global<T, B>,
+}
+
+impl<'a, T, B: OnceBackend<T>> Future for WaitReady<'a, T, B> {
+    type Output = &'a T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(v) = self.global.try_wait() {
+            return Poll::Ready(v);
+        }
+        let mut waiters = WAITERS.lock().unwrap_or_else(|e| e.into_inner());
+        //check again in case initialization finished before we registered
+        match self.global.try_wait() {
+            Poll::Ready(v) => Poll::Ready(v),
+            Poll::Pending => {
+                waiters.push(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
+
+impl<T, B: OnceBackend<T>> Global<T, B> {
+    ///Returns a future that resolves once the global has been initialized elsewhere. This does
+    ///not initialize the global itself.
+    pub fn wait_ready(&self) -> WaitReady<'_, T, B> {
+        WaitReady { global: self }
+    }
+}
+
+///Error returned by [`Global::bind`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BindError {
+    ///The global is already initialized or has a future bound to it.
+    AlreadyInit,
+    ///The thread that drives the future could not be spawned.
+    Spawn(std::io::Error),
+}
+
+impl Display for BindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindError::AlreadyInit => f.write_str("global is already initialized or has a future bound to it"),
+            BindError::Spawn(e) => write!(f, "failed to spawn the thread driving the bound future: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for BindError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BindError::Spawn(e) => Some(e),
+            BindError::AlreadyInit => None,
+        }
+    }
+}
+
+fn unbind(addr: usize) {
+    BOUND.lock().unwrap_or_else(|e| e.into_inner()).retain(|&a| a != addr);
+    UNBOUND.notify_all();
+}
+
+impl<T, B: OnceBackend<T>> Global<T, B> {
+    ///Constructs a new global that is initialized by a future passed to
+    ///[`bind`](Global::bind), rather than by an initializer. Accessing it before a future is bound
+    ///panics, as does accessing it after the bound future panicked.
+    pub const fn from_future() -> Self {
+        Self::from_init(Init::Collect(unbound::<T>), B::EMPTY)
+    }
+}
+
+impl<T: Send + 'static, B: OnceBackend<T>> Global<T, B>
+where
+    Self: Sync,
+{
+    ///Installs a running computation, such as the join handle of a spawned task, whose output
+    ///becomes the value of the global when it completes. This lets startup overlap slow work,
+    ///like fetching configuration over the network, with everything else.
+    ///
+    ///The future is driven on a background thread, so it must not need to run inside a
+    ///particular executor; join handles don't. Until it completes, [`get`](Global::get) returns
+    ///`None` and [`wait_ready`](Global::wait_ready) is pending, while
+    ///[`force`](Global::force) and [`Deref`](core::ops::Deref) block until it is done. If the
+    ///future panics, the global is poisoned and the next access runs its initializer, or for a
+    ///global made with [`from_future`](Global::from_future), panics with the future's panic
+    ///message until another future is bound.
+    ///
+    ///Returns an error if the global is already initialized or has a future bound to it, or if
+    ///the thread could not be spawned, in which case the global is left unbound.
+    ///```rust
+    ///# use global_static::Global;
+    ///async fn fetch_config() -> String {
+    ///    "timeout = 30".to_owned()
+    ///}
+    ///
+    ///static CONFIG: Global<String> = Global::from_future();
+    ///
+    ///CONFIG.bind(fetch_config()).unwrap();
+    ///// ...other startup work...
+    ///assert_eq!(*CONFIG, "timeout = 30");
+    ///```
+    pub fn bind<F: Future<Output = T> + Send + 'static>(&'static self, fut: F) -> Result<(), BindError> {
+        let addr = self as *const Self as usize;
+        {
+            let mut bound = BOUND.lock().unwrap_or_else(|e| e.into_inner());
+            if self.data.get().is_some() || bound.contains(&addr) {
+                return Err(BindError::AlreadyInit);
+            }
+            //added before spawning, since the thread may finish before spawn returns
+            bound.push(addr);
+        }
+        let spawned = std::thread::Builder::new()
+            .name("global-static-bind".to_owned())
+            .spawn(move || {
+                match std::panic::catch_unwind(AssertUnwindSafe(|| block_on(fut))) {
+                    //the global may have been set in the meantime, in which case that value wins
+                    Ok(value) => {
+                        set_failed(addr, None);
+                        drop(self.set(value));
+                    },
+                    Err(panic) => {
+                        set_failed(addr, Some(error::message(&*panic)));
+                        self.poisoned.store(true, Ordering::Release);
+                    },
+                }
+                unbind(addr);
+            });
+        spawned.map(drop).map_err(|e| {
+            unbind(addr);
+            BindError::Spawn(e)
+        })
+    }
+}
@@ -0,0 +1,153 @@
+use std::sync::OnceLock;
+
+///A type that [`wit_export!`](crate::wit_export) can hand to the host of a Wasm component,
+///lowered as the component model's canonical ABI specifies.
+///
+///Primitives are returned directly. Strings and byte lists are returned as a pointer to their
+///address and length, which point straight into the global, so nothing is copied or freed.
+///
+///# Safety
+///`lower` must return the canonical ABI representation of a value of the WIT type `WIT`, and
+///anything it points to must stay valid for as long as the value does.
+pub unsafe trait WitValue: 'static {
+    ///The WIT type of the value, such as `u32` or `string`.
+    const WIT: &'static str;
+    ///The core Wasm type that exported functions return.
+    type Lowered;
+    ///Lowers the value, writing it to `area` if it is passed in memory.
+    fn lower(&'static self, area: &'static ReturnArea) -> Self::Lowered;
+}
+
+///Memory that an exported function returns values passed in memory in. Each export has its
+///own, and since globals are never modified, it is only written once.
+pub struct ReturnArea(OnceLock<[usize; 2]>);
+
+impl ReturnArea {
+    ///Constructs a new, empty area.
+    pub const fn new() -> Self {
+        Self(OnceLock::new())
+    }
+
+    fn list<T>(&'static self, items: &'static [T]) -> *const [usize; 2] {
+        self.0.get_or_init(|| [items.as_ptr() as usize, items.len()])
+    }
+}
+
+impl Default for ReturnArea {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+macro_rules! impl_wit_direct {
+    ($($ty:ty => $wit:literal),*) => {$(
+        unsafe impl WitValue for $ty {
+            const WIT: &'static str = $wit;
+            type Lowered = $ty;
+            fn lower(&'static self, _: &'static ReturnArea) -> $ty {
+                *self
+            }
+        }
+    )*};
+}
+
+impl_wit_direct!(
+    bool => "bool", u8 => "u8", u16 => "u16", u32 => "u32", u64 => "u64",
+    i8 => "s8", i16 => "s16", i32 => "s32", i64 => "s64", f32 => "f32", f64 => "f64"
+);
+
+unsafe impl WitValue for char {
+    const WIT: &'static str = "char";
+    type Lowered = u32;
+    fn lower(&'static self, _: &'static ReturnArea) -> u32 {
+        *self as u32
+    }
+}
+
+unsafe impl WitValue for String {
+    const WIT: &'static str = "string";
+    type Lowered = *const [usize; 2];
+    fn lower(&'static self, area: &'static ReturnArea) -> Self::Lowered {
+        area.list(self.as_bytes())
+    }
+}
+
+unsafe impl WitValue for &'static str {
+    const WIT: &'static str = "string";
+    type Lowered = *const [usize; 2];
+    fn lower(&'static self, area: &'static ReturnArea) -> Self::Lowered {
+        area.list(self.as_bytes())
+    }
+}
+
+unsafe impl WitValue for Vec<u8> {
+    const WIT: &'static str = "list<u8>";
+    type Lowered = *const [usize; 2];
+    fn lower(&'static self, area: &'static ReturnArea) -> Self::Lowered {
+        area.list(self)
+    }
+}
+
+//renders the interface for `wit_export!`, which is given as `namespace:package/interface`
+#[doc(hidden)]
+pub fn __wit_interface(path: &str, funcs: &[(&str, &str)]) -> String {
+    let (package, interface) = match path.rsplit_once('/') {
+        Some((package, interface)) => (Some(package), interface),
+        None => (None, path),
+    };
+    let mut out = String::new();
+    if let Some(package) = package {
+        out += &format!("package {package};\n\n");
+    }
+    out += &format!("interface {interface} {{\n");
+    for (name, ty) in funcs {
+        out += &format!("    {name}: func() -> {ty};\n");
+    }
+    out += "}\n";
+    out
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "wit")))]
+#[macro_export]
+///Export read access to globals from a Wasm component. Each entry becomes a function of the
+///WIT interface `namespace:package/interface` that returns the value of the global, initializing
+///it if needed. The type must implement [`WitValue`](crate::WitValue).
+///
+///The first line names a function generated alongside the exports, which returns the WIT
+///definition of the interface for the component's world to export. The exports themselves are
+///only generated when targeting Wasm.
+///```rust
+///# use global_static::{Global, wit_export};
+///static PORT: Global<u16> = Global::new(|| 8080);
+///static NAME: Global<String> = Global::new(|| "app".to_owned());
+///
+///wit_export! {
+///    fn settings_wit = "app:config/settings";
+///    "port" => PORT: u16;
+///    "name" => NAME: String;
+///}
+///
+///assert_eq!(settings_wit(), "package app:config;\n\ninterface settings {
+///    port: func() -> u16;
+///    name: func() -> string;
+///}\n");
+///```
+macro_rules! wit_export {
+    (fn $wit:ident = $path:literal; $($name:literal => $global:path: $type:ty;)*) => {
+        ///Returns the WIT definition of the interface exported by `wit_export!`.
+        pub fn $wit() -> ::std::string::String {
+            $crate::__wit_interface($path, &[$(($name, <$type as $crate::WitValue>::WIT)),*])
+        }
+        $(
+            #[cfg(target_family = "wasm")]
+            const _: () = {
+                #[export_name = concat!($path, "#", $name)]
+                extern "C" fn export() -> <$type as $crate::WitValue>::Lowered {
+                    static AREA: $crate::ReturnArea = $crate::ReturnArea::new();
+                    let value: &'static $type = $global.force();
+                    $crate::WitValue::lower(value, &AREA)
+                }
+            };
+        )*
+    };
+}